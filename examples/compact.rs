@@ -5,17 +5,16 @@ fn main() {
 
         let mut tree = TreeBuilder::new().with_root(0).build();
         let mut root = tree.root_mut().unwrap();
-        {
-            let mut one = root.append(1);
-            let mut two = one.append(2);
-            two.append(3);
-            two.append(4);
-        }
-        {
-            let mut five = root.append(5);
-            five.append(6).append(7);
-            five.append(8);
-        }
+        let one_id = root.append2(1);
+        let five_id = root.append2(5);
+        let two_id = tree.get_mut(one_id).unwrap().append2(2);
+        let mut two = tree.get_mut(two_id).unwrap();
+        two.append2(3);
+        two.append2(4);
+        let mut five = tree.get_mut(five_id).unwrap();
+        let six_id = five.append2(6);
+        five.append2(8);
+        tree.get_mut(six_id).unwrap().append2(7);
 
         println!("{}", tree);
 
@@ -0,0 +1,895 @@
+use std::fmt::{Debug, Display};
+
+use snowflake::ProcessUniqueId;
+
+use crate::behaviors::RemoveBehavior;
+use crate::node::Relatives;
+use crate::slab::Index;
+use crate::NodeId;
+
+#[derive(Debug)]
+struct ArrayNode<T> {
+    // the index this node was handed out under, generation included
+    index: Index,
+    data: T,
+    relatives: Relatives,
+}
+
+///
+/// The error returned when a `Node` is added to an `ArrayTree` that is already full.  It gives
+/// back the data that didn't fit.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CapacityError<T>(T);
+
+impl<T> CapacityError<T> {
+    ///
+    /// Returns the data that couldn't be added.
+    ///
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Display for CapacityError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the tree is full")
+    }
+}
+
+impl<T: Debug> std::error::Error for CapacityError<T> {}
+
+///
+/// A tree structure holding at most `N` `Node`s, stored inline in an array instead of on the heap.
+///
+/// An `ArrayTree` never allocates: adding a `Node` to a full `ArrayTree` returns a
+/// `CapacityError` instead of growing it.  Space freed by removed `Node`s is reused, and
+/// `NodeId`s are generational like `Tree`'s, so the `NodeId` of a removed `Node` stays invalid.
+/// Finding a free slot scans the array, so it is meant for small trees.
+///
+/// The API mirrors `Tree`'s: `NodeId`s identify `Node`s, and `ArrayNodeRef`/`ArrayNodeMut`
+/// views are handed out like `NodeRef`/`NodeMut`.
+///
+/// ```
+/// use nary_tree::array_tree::ArrayTree;
+///
+/// let mut tree: ArrayTree<&str, 3> = ArrayTree::new();
+/// tree.set_root("menu").unwrap();
+/// let mut root = tree.root_mut().expect("root doesn't exist?");
+/// root.append("settings").unwrap();
+/// root.append("about").unwrap();
+/// assert_eq!(root.append("exit").unwrap_err().into_inner(), "exit");
+///
+/// let root = tree.root().unwrap();
+/// let items = root.children().map(|child| *child.data()).collect::<Vec<&str>>();
+/// assert_eq!(items, vec!["settings", "about"]);
+/// ```
+///
+#[derive(Debug)]
+pub struct ArrayTree<T, const N: usize> {
+    id: ProcessUniqueId,
+    root_id: Option<NodeId>,
+    nodes: [Option<ArrayNode<T>>; N],
+    len: usize,
+    generation: usize,
+}
+
+impl<T, const N: usize> ArrayTree<T, N> {
+    ///
+    /// Creates a new, empty `ArrayTree`.
+    ///
+    /// ```
+    /// use nary_tree::array_tree::ArrayTree;
+    ///
+    /// let tree: ArrayTree<i32, 8> = ArrayTree::new();
+    ///
+    /// # assert!(tree.root().is_none());
+    /// # assert_eq!(tree.capacity(), 8);
+    /// ```
+    ///
+    pub fn new() -> ArrayTree<T, N> {
+        ArrayTree {
+            id: ProcessUniqueId::new(),
+            root_id: None,
+            nodes: std::array::from_fn(|_| None),
+            len: 0,
+            generation: 0,
+        }
+    }
+
+    ///
+    /// Returns the number of `Node`s the `ArrayTree` can hold, which is `N`.
+    ///
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    ///
+    /// Returns the number of `Node`s in the `ArrayTree`, orphaned ones included.
+    ///
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    ///
+    /// Returns `true` if the `ArrayTree` holds no `Node`s at all.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    ///
+    /// Sets the "root" of the `ArrayTree` to be `root`.  Like `Tree::set_root`, an existing root
+    /// (with its whole sub-tree) becomes the only child of the new root and keeps its `NodeId`.
+    /// Returns a `CapacityError` if the `ArrayTree` is full.
+    ///
+    /// ```
+    /// use nary_tree::array_tree::ArrayTree;
+    ///
+    /// let mut tree: ArrayTree<i32, 2> = ArrayTree::new();
+    /// let one_id = tree.set_root(1).unwrap();
+    /// let zero_id = tree.set_root(0).unwrap();
+    /// assert!(tree.set_root(-1).is_err());
+    ///
+    /// let root = tree.root().unwrap();
+    /// assert_eq!(root.node_id(), zero_id);
+    /// assert_eq!(root.first_child().unwrap().node_id(), one_id);
+    /// ```
+    ///
+    pub fn set_root(&mut self, root: T) -> Result<NodeId, CapacityError<T>> {
+        let new_root_id = self.insert(root)?;
+        if let Some(old_root_id) = self.root_id {
+            self.node_mut(old_root_id).relatives.parent = Some(new_root_id);
+            let relatives = &mut self.node_mut(new_root_id).relatives;
+            relatives.first_child = Some(old_root_id);
+            relatives.last_child = Some(old_root_id);
+        }
+        self.root_id = Some(new_root_id);
+        Ok(new_root_id)
+    }
+
+    ///
+    /// Returns the `NodeId` of the root `Node`, if there is one.
+    ///
+    pub fn root_id(&self) -> Option<NodeId> {
+        self.root_id
+    }
+
+    ///
+    /// Returns an `ArrayNodeRef` pointing to the root `Node`, if there is one.
+    ///
+    pub fn root(&self) -> Option<ArrayNodeRef<'_, T, N>> {
+        self.root_id.and_then(|root_id| self.get(root_id))
+    }
+
+    ///
+    /// Returns an `ArrayNodeMut` pointing to the root `Node`, if there is one.
+    ///
+    pub fn root_mut(&mut self) -> Option<ArrayNodeMut<'_, T, N>> {
+        self.root_id.and_then(move |root_id| self.get_mut(root_id))
+    }
+
+    ///
+    /// Returns an `ArrayNodeRef` pointing to the `Node` that `node_id` identifies.  Returns a
+    /// `None`-value if `node_id` points to nothing (or belongs to a different tree).
+    ///
+    pub fn get(&self, node_id: NodeId) -> Option<ArrayNodeRef<'_, T, N>> {
+        self.get_node(node_id).map(|_| ArrayNodeRef {
+            node_id,
+            tree: self,
+        })
+    }
+
+    ///
+    /// Returns an `ArrayNodeMut` pointing to the `Node` that `node_id` identifies.  Returns a
+    /// `None`-value if `node_id` points to nothing (or belongs to a different tree).
+    ///
+    pub fn get_mut(&mut self, node_id: NodeId) -> Option<ArrayNodeMut<'_, T, N>> {
+        match self.get_node(node_id) {
+            Some(_) => Some(ArrayNodeMut {
+                node_id,
+                tree: self,
+            }),
+            None => None,
+        }
+    }
+
+    ///
+    /// Removes the `Node` that `node_id` identifies and returns its data, freeing its slot.  Its
+    /// children are either dropped along with it or orphaned, depending on `behavior`.  Returns
+    /// a `None`-value if `node_id` points to nothing (or belongs to a different tree).
+    ///
+    /// ```
+    /// use nary_tree::array_tree::ArrayTree;
+    /// use nary_tree::behaviors::RemoveBehavior::DropChildren;
+    ///
+    /// let mut tree: ArrayTree<i32, 3> = ArrayTree::new();
+    /// tree.set_root(1).unwrap();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let two_id = root.append(2).unwrap();
+    /// root.append(3).unwrap();
+    ///
+    /// assert_eq!(tree.remove(two_id, DropChildren), Some(2));
+    /// assert_eq!(tree.len(), 2);
+    /// assert!(tree.root_mut().unwrap().append(4).is_ok());
+    /// ```
+    ///
+    pub fn remove(&mut self, node_id: NodeId, behavior: RemoveBehavior) -> Option<T> {
+        let relatives = self.get_node(node_id)?.relatives;
+        if let Some(prev_sibling_id) = relatives.prev_sibling {
+            self.node_mut(prev_sibling_id).relatives.next_sibling = relatives.next_sibling;
+        }
+        if let Some(next_sibling_id) = relatives.next_sibling {
+            self.node_mut(next_sibling_id).relatives.prev_sibling = relatives.prev_sibling;
+        }
+        if let Some(parent_id) = relatives.parent {
+            let parent = &mut self.node_mut(parent_id).relatives;
+            if parent.first_child == Some(node_id) {
+                parent.first_child = relatives.next_sibling;
+            }
+            if parent.last_child == Some(node_id) {
+                parent.last_child = relatives.prev_sibling;
+            }
+        }
+        if self.root_id == Some(node_id) {
+            self.root_id = None;
+        }
+
+        match behavior {
+            RemoveBehavior::DropChildren => self.drop_children(node_id),
+            RemoveBehavior::OrphanChildren => {
+                let mut child = relatives.first_child;
+                while let Some(child_id) = child {
+                    let child_relatives = &mut self.node_mut(child_id).relatives;
+                    child = child_relatives.next_sibling;
+                    child_relatives.parent = None;
+                    child_relatives.prev_sibling = None;
+                    child_relatives.next_sibling = None;
+                }
+            }
+        }
+        Some(self.take(node_id))
+    }
+
+    // drops the descendants of `node_id` leaf by leaf, so that no stack is needed
+    fn drop_children(&mut self, node_id: NodeId) {
+        let mut current = match self.node(node_id).relatives.first_child {
+            Some(first_child) => first_child,
+            None => return,
+        };
+        loop {
+            if let Some(first_child) = self.node(current).relatives.first_child {
+                current = first_child;
+                continue;
+            }
+            let relatives = self.node(current).relatives;
+            let parent_id = relatives.parent.expect("parent must exist");
+            self.take(current);
+            self.node_mut(parent_id).relatives.first_child = relatives.next_sibling;
+            current = match relatives.next_sibling {
+                Some(next_sibling) => next_sibling,
+                None if parent_id == node_id => return,
+                None => parent_id,
+            };
+        }
+    }
+
+    fn insert(&mut self, data: T) -> Result<NodeId, CapacityError<T>> {
+        let slot = match self.nodes.iter().position(Option::is_none) {
+            Some(slot) => slot,
+            None => return Err(CapacityError(data)),
+        };
+        let index = Index::new(slot, self.generation);
+        self.nodes[slot] = Some(ArrayNode {
+            index,
+            data,
+            relatives: Relatives {
+                parent: None,
+                prev_sibling: None,
+                next_sibling: None,
+                first_child: None,
+                last_child: None,
+            },
+        });
+        self.len += 1;
+        Ok(NodeId {
+            tree_id: self.id,
+            index,
+            #[cfg(feature = "experimental")]
+            compaction: 0,
+        })
+    }
+
+    fn take(&mut self, node_id: NodeId) -> T {
+        let node = self.nodes[node_id.index.index]
+            .take()
+            .expect("node must exist");
+        self.len -= 1;
+        self.generation += 1;
+        node.data
+    }
+
+    fn get_node(&self, node_id: NodeId) -> Option<&ArrayNode<T>> {
+        if node_id.tree_id != self.id {
+            return None;
+        }
+        self.nodes
+            .get(node_id.index.index)?
+            .as_ref()
+            .filter(|node| node.index == node_id.index)
+    }
+
+    fn node(&self, node_id: NodeId) -> &ArrayNode<T> {
+        self.get_node(node_id).expect("node must exist")
+    }
+
+    fn node_mut(&mut self, node_id: NodeId) -> &mut ArrayNode<T> {
+        debug_assert_eq!(node_id.tree_id, self.id);
+        self.nodes[node_id.index.index]
+            .as_mut()
+            .expect("node must exist")
+    }
+
+    // the first node of node_id's sub-tree in post-order
+    fn leftmost_leaf(&self, mut node_id: NodeId) -> NodeId {
+        while let Some(first_child) = self.node(node_id).relatives.first_child {
+            node_id = first_child;
+        }
+        node_id
+    }
+}
+
+impl<T, const N: usize> Default for ArrayTree<T, N> {
+    fn default() -> Self {
+        ArrayTree::new()
+    }
+}
+
+///
+/// An immutable reference to a given `Node`'s data and its relatives in an `ArrayTree`.
+///
+#[derive(Debug)]
+pub struct ArrayNodeRef<'a, T, const N: usize> {
+    node_id: NodeId,
+    tree: &'a ArrayTree<T, N>,
+}
+
+impl<'a, T, const N: usize> ArrayNodeRef<'a, T, N> {
+    ///
+    /// Returns the `NodeId` that identifies this `Node` in the tree.
+    ///
+    pub fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    ///
+    /// Returns a reference to the data contained by this `Node`.
+    ///
+    pub fn data(&self) -> &'a T {
+        &self.tree.node(self.node_id).data
+    }
+
+    ///
+    /// Returns an `ArrayNodeRef` pointing to this `Node`'s parent, if it has one.
+    ///
+    pub fn parent(&self) -> Option<ArrayNodeRef<'a, T, N>> {
+        self.relative(|relatives| relatives.parent)
+    }
+
+    ///
+    /// Returns an `ArrayNodeRef` pointing to this `Node`'s previous sibling, if it has one.
+    ///
+    pub fn prev_sibling(&self) -> Option<ArrayNodeRef<'a, T, N>> {
+        self.relative(|relatives| relatives.prev_sibling)
+    }
+
+    ///
+    /// Returns an `ArrayNodeRef` pointing to this `Node`'s next sibling, if it has one.
+    ///
+    pub fn next_sibling(&self) -> Option<ArrayNodeRef<'a, T, N>> {
+        self.relative(|relatives| relatives.next_sibling)
+    }
+
+    ///
+    /// Returns an `ArrayNodeRef` pointing to this `Node`'s first child, if it has any.
+    ///
+    pub fn first_child(&self) -> Option<ArrayNodeRef<'a, T, N>> {
+        self.relative(|relatives| relatives.first_child)
+    }
+
+    ///
+    /// Returns an `ArrayNodeRef` pointing to this `Node`'s last child, if it has any.
+    ///
+    pub fn last_child(&self) -> Option<ArrayNodeRef<'a, T, N>> {
+        self.relative(|relatives| relatives.last_child)
+    }
+
+    ///
+    /// Returns an `Iterator` over this `Node`'s children.
+    ///
+    pub fn children(&self) -> impl Iterator<Item = ArrayNodeRef<'a, T, N>> {
+        std::iter::successors(self.first_child(), |child| child.next_sibling())
+    }
+
+    ///
+    /// Returns an `Iterator` over this `Node` and its descendants in depth-first pre-order.  It
+    /// follows the links between `Node`s, so it doesn't allocate.
+    ///
+    /// ```
+    /// use nary_tree::array_tree::ArrayTree;
+    ///
+    /// let mut tree: ArrayTree<i32, 4> = ArrayTree::new();
+    /// tree.set_root(1).unwrap();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let two_id = root.append(2).unwrap();
+    /// root.append(4).unwrap();
+    /// tree.get_mut(two_id).unwrap().append(3).unwrap();
+    ///
+    /// let root = tree.root().unwrap();
+    /// let values = root.traverse_pre_order().map(|node| *node.data()).collect::<Vec<i32>>();
+    /// assert_eq!(values, vec![1, 2, 3, 4]);
+    /// ```
+    ///
+    pub fn traverse_pre_order(&self) -> ArrayPreOrder<'a, T, N> {
+        ArrayPreOrder {
+            start: self.node_id,
+            next: Some(self.node_id),
+            tree: self.tree,
+        }
+    }
+
+    ///
+    /// Returns an `Iterator` over this `Node`'s ancestors, starting with its parent.  It follows
+    /// the links between `Node`s, so it doesn't allocate.
+    ///
+    /// ```
+    /// use nary_tree::array_tree::ArrayTree;
+    ///
+    /// let mut tree: ArrayTree<i32, 3> = ArrayTree::new();
+    /// tree.set_root(1).unwrap();
+    /// let two_id = tree.root_mut().unwrap().append(2).unwrap();
+    /// let three_id = tree.get_mut(two_id).unwrap().append(3).unwrap();
+    ///
+    /// let three = tree.get(three_id).unwrap();
+    /// let values = three.ancestors().map(|node| *node.data()).collect::<Vec<i32>>();
+    /// assert_eq!(values, vec![2, 1]);
+    /// ```
+    ///
+    pub fn ancestors(&self) -> ArrayAncestors<'a, T, N> {
+        ArrayAncestors {
+            next: self.tree.node(self.node_id).relatives.parent,
+            tree: self.tree,
+        }
+    }
+
+    ///
+    /// Returns an `Iterator` over this `Node` and its descendants in depth-first post-order.  It
+    /// follows the links between `Node`s, so it doesn't allocate.
+    ///
+    /// ```
+    /// use nary_tree::array_tree::ArrayTree;
+    ///
+    /// let mut tree: ArrayTree<i32, 4> = ArrayTree::new();
+    /// tree.set_root(1).unwrap();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let two_id = root.append(2).unwrap();
+    /// root.append(4).unwrap();
+    /// tree.get_mut(two_id).unwrap().append(3).unwrap();
+    ///
+    /// let root = tree.root().unwrap();
+    /// let values = root.traverse_post_order().map(|node| *node.data()).collect::<Vec<i32>>();
+    /// assert_eq!(values, vec![3, 2, 4, 1]);
+    /// ```
+    ///
+    pub fn traverse_post_order(&self) -> ArrayPostOrder<'a, T, N> {
+        ArrayPostOrder {
+            start: self.node_id,
+            next: Some(self.tree.leftmost_leaf(self.node_id)),
+            tree: self.tree,
+        }
+    }
+
+    ///
+    /// Returns an `Iterator` over this `Node` and its descendants in breadth-first level-order.
+    /// It doesn't allocate: instead of queueing `Node`s, it walks the sub-tree again for every
+    /// level, so a whole traversal takes time proportional to the size of the sub-tree times its
+    /// height.
+    ///
+    /// ```
+    /// use nary_tree::array_tree::ArrayTree;
+    ///
+    /// let mut tree: ArrayTree<i32, 4> = ArrayTree::new();
+    /// tree.set_root(1).unwrap();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let two_id = root.append(2).unwrap();
+    /// root.append(3).unwrap();
+    /// tree.get_mut(two_id).unwrap().append(4).unwrap();
+    ///
+    /// let root = tree.root().unwrap();
+    /// let values = root.traverse_level_order().map(|node| *node.data()).collect::<Vec<i32>>();
+    /// assert_eq!(values, vec![1, 2, 3, 4]);
+    /// ```
+    ///
+    pub fn traverse_level_order(&self) -> ArrayLevelOrder<'a, T, N> {
+        ArrayLevelOrder {
+            start: self.node_id,
+            level: 0,
+            next: Some(self.node_id),
+            tree: self.tree,
+        }
+    }
+
+    fn relative<F: FnOnce(&Relatives) -> Option<NodeId>>(
+        &self,
+        f: F,
+    ) -> Option<ArrayNodeRef<'a, T, N>> {
+        f(&self.tree.node(self.node_id).relatives).map(|node_id| ArrayNodeRef {
+            node_id,
+            tree: self.tree,
+        })
+    }
+}
+
+///
+/// A mutable reference to a given `Node`'s data and its relatives in an `ArrayTree`.
+///
+#[derive(Debug)]
+pub struct ArrayNodeMut<'a, T, const N: usize> {
+    node_id: NodeId,
+    tree: &'a mut ArrayTree<T, N>,
+}
+
+impl<'a, T, const N: usize> ArrayNodeMut<'a, T, N> {
+    ///
+    /// Returns the `NodeId` that identifies this `Node` in the tree.
+    ///
+    pub fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    ///
+    /// Returns a mutable reference to the data contained by this `Node`.
+    ///
+    pub fn data(&mut self) -> &mut T {
+        &mut self.tree.node_mut(self.node_id).data
+    }
+
+    ///
+    /// Returns an `ArrayNodeMut` pointing to this `Node`'s parent, if it has one.
+    ///
+    pub fn parent(self) -> Option<ArrayNodeMut<'a, T, N>> {
+        let parent_id = self.tree.node(self.node_id).relatives.parent?;
+        Some(ArrayNodeMut {
+            node_id: parent_id,
+            tree: self.tree,
+        })
+    }
+
+    ///
+    /// Appends a new `Node` containing `data` as this `Node`'s last child and returns its
+    /// `NodeId`.  Returns a `CapacityError` holding `data` if the `ArrayTree` is full.
+    ///
+    pub fn append(&mut self, data: T) -> Result<NodeId, CapacityError<T>> {
+        let new_id = self.tree.insert(data)?;
+        let last_child = self.tree.node(self.node_id).relatives.last_child;
+
+        let new_relatives = &mut self.tree.node_mut(new_id).relatives;
+        new_relatives.parent = Some(self.node_id);
+        new_relatives.prev_sibling = last_child;
+
+        match last_child {
+            Some(last_child) => {
+                self.tree.node_mut(last_child).relatives.next_sibling = Some(new_id)
+            }
+            None => self.tree.node_mut(self.node_id).relatives.first_child = Some(new_id),
+        }
+        self.tree.node_mut(self.node_id).relatives.last_child = Some(new_id);
+        Ok(new_id)
+    }
+
+    ///
+    /// Returns an `ArrayNodeRef` pointing to this `Node`.
+    ///
+    pub fn as_ref(self) -> ArrayNodeRef<'a, T, N> {
+        ArrayNodeRef {
+            node_id: self.node_id,
+            tree: self.tree,
+        }
+    }
+}
+
+/// Depth-first pre-order iterator over an `ArrayTree`
+pub struct ArrayPreOrder<'a, T, const N: usize> {
+    start: NodeId,
+    next: Option<NodeId>,
+    tree: &'a ArrayTree<T, N>,
+}
+
+impl<'a, T, const N: usize> Iterator for ArrayPreOrder<'a, T, N> {
+    type Item = ArrayNodeRef<'a, T, N>;
+
+    fn next(&mut self) -> Option<ArrayNodeRef<'a, T, N>> {
+        let node_id = self.next?;
+        let relatives = &self.tree.node(node_id).relatives;
+        self.next = relatives.first_child.or_else(|| {
+            // climb until a node with a next sibling, without leaving the start node's sub-tree
+            let mut current = node_id;
+            loop {
+                if current == self.start {
+                    return None;
+                }
+                let relatives = &self.tree.node(current).relatives;
+                if relatives.next_sibling.is_some() {
+                    return relatives.next_sibling;
+                }
+                current = relatives.parent?;
+            }
+        });
+        Some(ArrayNodeRef {
+            node_id,
+            tree: self.tree,
+        })
+    }
+}
+
+/// Iterator over the ancestors of a `Node` in an `ArrayTree`
+pub struct ArrayAncestors<'a, T, const N: usize> {
+    next: Option<NodeId>,
+    tree: &'a ArrayTree<T, N>,
+}
+
+impl<'a, T, const N: usize> Iterator for ArrayAncestors<'a, T, N> {
+    type Item = ArrayNodeRef<'a, T, N>;
+
+    fn next(&mut self) -> Option<ArrayNodeRef<'a, T, N>> {
+        let node_id = self.next?;
+        self.next = self.tree.node(node_id).relatives.parent;
+        Some(ArrayNodeRef {
+            node_id,
+            tree: self.tree,
+        })
+    }
+}
+
+/// Depth-first post-order iterator over an `ArrayTree`
+pub struct ArrayPostOrder<'a, T, const N: usize> {
+    start: NodeId,
+    next: Option<NodeId>,
+    tree: &'a ArrayTree<T, N>,
+}
+
+impl<'a, T, const N: usize> Iterator for ArrayPostOrder<'a, T, N> {
+    type Item = ArrayNodeRef<'a, T, N>;
+
+    fn next(&mut self) -> Option<ArrayNodeRef<'a, T, N>> {
+        let node_id = self.next?;
+        self.next = if node_id == self.start {
+            None
+        } else {
+            // after a node comes the first leaf of its next sibling's sub-tree, or else its parent
+            let relatives = &self.tree.node(node_id).relatives;
+            match relatives.next_sibling {
+                Some(next_sibling) => Some(self.tree.leftmost_leaf(next_sibling)),
+                None => relatives.parent,
+            }
+        };
+        Some(ArrayNodeRef {
+            node_id,
+            tree: self.tree,
+        })
+    }
+}
+
+/// Breadth-first level-order iterator over an `ArrayTree`
+pub struct ArrayLevelOrder<'a, T, const N: usize> {
+    start: NodeId,
+    // the depth, below the start node, of the level being walked
+    level: usize,
+    next: Option<NodeId>,
+    tree: &'a ArrayTree<T, N>,
+}
+
+impl<'a, T, const N: usize> ArrayLevelOrder<'a, T, N> {
+    // walks the start node's sub-tree in pre-order from node_id, at the given depth, without going
+    // below the current level, and returns the first node found on that level
+    fn seek(&self, mut node_id: NodeId, mut depth: usize, skip_first: bool) -> Option<NodeId> {
+        let mut skip = skip_first;
+        loop {
+            if !skip && depth == self.level {
+                return Some(node_id);
+            }
+            skip = false;
+
+            let relatives = &self.tree.node(node_id).relatives;
+            if let Some(first_child) = relatives.first_child.filter(|_| depth < self.level) {
+                node_id = first_child;
+                depth += 1;
+                continue;
+            }
+            loop {
+                if node_id == self.start {
+                    return None;
+                }
+                let relatives = &self.tree.node(node_id).relatives;
+                if let Some(next_sibling) = relatives.next_sibling {
+                    node_id = next_sibling;
+                    break;
+                }
+                node_id = relatives.parent?;
+                depth -= 1;
+            }
+        }
+    }
+}
+
+impl<'a, T, const N: usize> Iterator for ArrayLevelOrder<'a, T, N> {
+    type Item = ArrayNodeRef<'a, T, N>;
+
+    fn next(&mut self) -> Option<ArrayNodeRef<'a, T, N>> {
+        let node_id = self.next?;
+        self.next = self.seek(node_id, self.level, true).or_else(|| {
+            // this level is done; the traversal ends at the first level with no nodes
+            self.level += 1;
+            self.seek(self.start, 0, false)
+        });
+        Some(ArrayNodeRef {
+            node_id,
+            tree: self.tree,
+        })
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod array_tree_tests {
+    use super::*;
+    use crate::behaviors::RemoveBehavior::{DropChildren, OrphanChildren};
+
+    #[test]
+    fn set_root_and_append() {
+        let mut tree: ArrayTree<i32, 4> = ArrayTree::new();
+        assert!(tree.is_empty());
+        let one_id = tree.set_root(1).unwrap();
+        let zero_id = tree.set_root(0).unwrap();
+        assert_eq!(tree.root_id(), Some(zero_id));
+
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append(2).unwrap();
+        let three_id = root.append(3).unwrap();
+        assert_eq!(root.append(4), Err(CapacityError(4)));
+        assert_eq!(tree.set_root(5).unwrap_err().into_inner(), 5);
+        assert_eq!(tree.len(), 4);
+
+        let root = tree.root().unwrap();
+        let children: Vec<NodeId> = root.children().map(|child| child.node_id()).collect();
+        assert_eq!(children, vec![one_id, two_id, three_id]);
+        assert_eq!(root.last_child().unwrap().node_id(), three_id);
+
+        let two = tree.get(two_id).unwrap();
+        assert_eq!(two.prev_sibling().unwrap().node_id(), one_id);
+        assert_eq!(two.next_sibling().unwrap().node_id(), three_id);
+        assert_eq!(two.parent().unwrap().node_id(), zero_id);
+        assert!(two.first_child().is_none());
+    }
+
+    #[test]
+    fn remove() {
+        let mut tree: ArrayTree<i32, 6> = ArrayTree::new();
+        let root_id = tree.set_root(0).unwrap();
+        let mut root = tree.root_mut().unwrap();
+        let one_id = root.append(1).unwrap();
+        let two_id = root.append(2).unwrap();
+        let mut one = tree.get_mut(one_id).unwrap();
+        let three_id = one.append(3).unwrap();
+        one.append(4).unwrap();
+        let five_id = tree.get_mut(three_id).unwrap().append(5).unwrap();
+
+        assert_eq!(tree.remove(one_id, DropChildren), Some(1));
+        assert_eq!(tree.len(), 2);
+        assert!(tree.get(one_id).is_none());
+        assert!(tree.get(five_id).is_none());
+        assert_eq!(tree.remove(one_id, DropChildren), None);
+        let root = tree.root().unwrap();
+        assert_eq!(root.first_child().unwrap().node_id(), two_id);
+        assert!(root.children().all(|child| child.prev_sibling().is_none()));
+
+        // freed slots are reused, but old ids stay invalid
+        let mut root = tree.root_mut().unwrap();
+        for i in 6..10 {
+            root.append(i).unwrap();
+        }
+        assert!(root.append(10).is_err());
+        assert!(tree.get(three_id).is_none());
+
+        let seven_id = tree.root().unwrap().children().nth(2).unwrap().node_id();
+        assert!(tree.get_mut(seven_id).unwrap().append(10).is_err());
+        assert_eq!(tree.remove(two_id, OrphanChildren), Some(2));
+        let ten_id = tree.get_mut(seven_id).unwrap().append(10).unwrap();
+        assert_eq!(tree.remove(seven_id, OrphanChildren), Some(7));
+        assert!(tree.get(ten_id).unwrap().parent().is_none());
+
+        assert_eq!(tree.remove(root_id, DropChildren), Some(0));
+        assert!(tree.root().is_none());
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn traverse_pre_order() {
+        let mut tree: ArrayTree<i32, 8> = ArrayTree::new();
+        tree.set_root(1).unwrap();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append(2).unwrap();
+        let five_id = root.append(5).unwrap();
+        let mut two = tree.get_mut(two_id).unwrap();
+        two.append(3).unwrap();
+        two.append(4).unwrap();
+        tree.get_mut(five_id).unwrap().append(6).unwrap();
+
+        let values: Vec<i32> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| *node.data())
+            .collect();
+        assert_eq!(values, vec![1, 2, 3, 4, 5, 6]);
+
+        let values: Vec<i32> = tree
+            .get(two_id)
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| *node.data())
+            .collect();
+        assert_eq!(values, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn ancestors_and_other_traversals() {
+        let mut tree: ArrayTree<i32, 8> = ArrayTree::new();
+        tree.set_root(1).unwrap();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append(2).unwrap();
+        let five_id = root.append(5).unwrap();
+        let mut two = tree.get_mut(two_id).unwrap();
+        let three_id = two.append(3).unwrap();
+        two.append(4).unwrap();
+        tree.get_mut(five_id).unwrap().append(6).unwrap();
+        let seven_id = tree.get_mut(three_id).unwrap().append(7).unwrap();
+
+        let values = |iter: &mut dyn Iterator<Item = ArrayNodeRef<'_, i32, 8>>| {
+            iter.map(|node| *node.data()).collect::<Vec<i32>>()
+        };
+
+        let seven = tree.get(seven_id).unwrap();
+        assert_eq!(values(&mut seven.ancestors()), vec![3, 2, 1]);
+        let root = tree.root().unwrap();
+        assert_eq!(values(&mut root.ancestors()), Vec::<i32>::new());
+
+        assert_eq!(
+            values(&mut root.traverse_post_order()),
+            vec![7, 3, 4, 2, 6, 5, 1]
+        );
+        assert_eq!(
+            values(&mut root.traverse_level_order()),
+            vec![1, 2, 5, 3, 4, 6, 7]
+        );
+
+        // traversals stay within the sub-tree they start from
+        let two = tree.get(two_id).unwrap();
+        assert_eq!(values(&mut two.traverse_post_order()), vec![7, 3, 4, 2]);
+        assert_eq!(values(&mut two.traverse_level_order()), vec![2, 3, 4, 7]);
+        assert_eq!(values(&mut seven.traverse_post_order()), vec![7]);
+        assert_eq!(values(&mut seven.traverse_level_order()), vec![7]);
+    }
+
+    #[test]
+    fn foreign_ids() {
+        let mut tree: ArrayTree<i32, 2> = ArrayTree::new();
+        tree.set_root(1).unwrap();
+        let mut other: ArrayTree<i32, 2> = ArrayTree::new();
+        let other_root_id = other.set_root(1).unwrap();
+
+        assert!(tree.get(other_root_id).is_none());
+        assert!(tree.get_mut(other_root_id).is_none());
+        assert_eq!(tree.remove(other_root_id, DropChildren), None);
+    }
+}
@@ -20,3 +20,47 @@ pub enum RemoveBehavior {
     ///
     OrphanChildren,
 }
+
+///
+/// Describes how a Tree grows once its capacity is exhausted.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum GrowthPolicy {
+    ///
+    /// Let the underlying storage grow as it sees fit, which roughly doubles the capacity every
+    /// time it runs out.  This is the default.
+    ///
+    #[default]
+    Double,
+
+    ///
+    /// Grow by exactly the given number of Nodes every time the capacity runs out (a value of 0
+    /// is treated as 1).
+    ///
+    /// This wastes less memory than `Double` when the Tree grows in predictable bursts, but
+    /// trades away amortized constant-time insertion for Trees that keep growing.
+    ///
+    Fixed(usize),
+}
+
+///
+/// Tells `NodeRef::walk` how to carry on after visiting a Node.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Walk {
+    ///
+    /// Go on to the Node's children, if it has any, and then on with the rest of the walk.
+    ///
+    Continue,
+
+    ///
+    /// Leave out the Node's children (and all Nodes in each of their sub-trees) but go on with
+    /// the rest of the walk.
+    ///
+    SkipChildren,
+
+    ///
+    /// End the walk right away.
+    ///
+    Stop,
+}
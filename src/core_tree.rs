@@ -1,7 +1,31 @@
+use crate::behaviors::GrowthPolicy;
 use crate::node::Node;
 use crate::slab::{self, Slab};
 use crate::NodeId;
 use snowflake::ProcessUniqueId;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::OnceLock;
+
+pub(crate) type Hook = Box<dyn FnMut(NodeId) + Send + Sync>;
+
+///
+/// The callbacks registered on a tree, called whenever a node is inserted or removed.
+///
+#[derive(Default)]
+pub(crate) struct Hooks {
+    pub(crate) on_insert: Option<Hook>,
+    pub(crate) on_remove: Option<Hook>,
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hooks")
+            .field("on_insert", &self.on_insert.is_some())
+            .field("on_remove", &self.on_remove.is_some())
+            .finish()
+    }
+}
 
 ///
 /// A wrapper around a Slab containing Node<T> values.
@@ -9,55 +33,252 @@ use snowflake::ProcessUniqueId;
 /// Groups a collection of Node<T>s with a process unique id.
 ///
 #[derive(Debug)]
-pub(crate) struct CoreTree<T> {
+pub(crate) struct CoreTree<T, E = ()> {
     id: ProcessUniqueId,
-    slab: Slab<Node<T>>,
+    slab: Slab<Node<T, E>>,
+    growth_policy: GrowthPolicy,
+    pub(crate) hooks: Hooks,
+    // bumped on every change to the shape of the tree, and on every mutable access to data
+    version: u64,
+    pub(crate) data_version: u64,
+    // the pre-order of the nodes reachable from the root, dropped whenever the version changes
+    pub(crate) pre_order: OnceLock<Vec<NodeId>>,
+    // the position of every node in that pre-order, dropped along with it
+    pub(crate) pre_order_positions: OnceLock<HashMap<NodeId, usize>>,
+    // user-provided keys, both ways: key to node, and slab index to key
+    keys: HashMap<u64, NodeId>,
+    node_keys: HashMap<usize, u64>,
+    // the nodes marked dirty through tracked data access, until they are cleared
+    pub(crate) dirty: HashSet<NodeId>,
+    // bumped every time a compaction moves nodes; with the experimental feature, also stamped on
+    // every NodeId handed out
+    compaction: u64,
 }
 
-impl<T> CoreTree<T> {
-    pub(crate) fn new(capacity: usize) -> CoreTree<T> {
+impl<T, E> CoreTree<T, E> {
+    pub(crate) fn new(capacity: usize) -> CoreTree<T, E> {
         CoreTree {
             id: ProcessUniqueId::new(),
             slab: Slab::new(capacity),
+            growth_policy: GrowthPolicy::default(),
+            hooks: Hooks::default(),
+            version: 0,
+            data_version: 0,
+            pre_order: OnceLock::new(),
+            pre_order_positions: OnceLock::new(),
+            keys: HashMap::new(),
+            node_keys: HashMap::new(),
+            dirty: HashSet::new(),
+            compaction: 0,
+        }
+    }
+
+    pub(crate) fn set_growth_policy(&mut self, growth_policy: GrowthPolicy) {
+        self.growth_policy = growth_policy;
+    }
+
+    // drops every node but keeps the allocated storage; the tree gets a new id so that none of
+    // the NodeIds handed out so far stay valid
+    pub(crate) fn recycle(&mut self) {
+        self.id = ProcessUniqueId::new();
+        self.slab.clear();
+        self.growth_policy = GrowthPolicy::default();
+        self.hooks = Hooks::default();
+        self.keys.clear();
+        self.node_keys.clear();
+        self.dirty.clear();
+        self.structure_changed();
+    }
+
+    pub(crate) fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub(crate) fn compaction_generation(&self) -> u64 {
+        self.compaction
+    }
+
+    // the same NodeId, stamped with the current compaction generation
+    #[cfg(feature = "experimental")]
+    pub(crate) fn stamp(&self, node_id: NodeId) -> NodeId {
+        NodeId {
+            compaction: self.compaction,
+            ..node_id
         }
     }
 
+    // NodeIds carry no stamp without the experimental feature
+    #[cfg(not(feature = "experimental"))]
+    pub(crate) fn stamp(&self, node_id: NodeId) -> NodeId {
+        node_id
+    }
+
+    pub(crate) fn structure_changed(&mut self) {
+        self.version += 1;
+        self.pre_order.take();
+        self.pre_order_positions.take();
+    }
+
     pub(crate) fn capacity(&self) -> usize {
         self.slab.capacity()
     }
 
+    pub(crate) fn len(&self) -> usize {
+        self.slab.len()
+    }
+
+    // the slab, plus the entries allocated for the key maps, the dirty set and the caches; hash
+    // tables are counted by capacity, without their control bytes
+    pub(crate) fn memory_footprint(&self) -> usize {
+        use std::mem::size_of;
+
+        let pre_order = self.pre_order.get().map_or(0, Vec::capacity);
+        let pre_order_positions = self.pre_order_positions.get().map_or(0, HashMap::capacity);
+        self.slab.memory_footprint()
+            + self.keys.capacity() * size_of::<(u64, NodeId)>()
+            + self.node_keys.capacity() * size_of::<(usize, u64)>()
+            + self.dirty.capacity() * size_of::<NodeId>()
+            + pre_order * size_of::<NodeId>()
+            + pre_order_positions * size_of::<(NodeId, usize)>()
+    }
+
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        self.slab.reserve_exact(additional);
+    }
+
     pub(crate) fn insert(&mut self, data: T) -> NodeId {
+        if let GrowthPolicy::Fixed(additional) = self.growth_policy {
+            if self.slab.len() == self.slab.capacity() {
+                self.slab.reserve_exact(additional.max(1));
+            }
+        }
         let key = self.slab.insert(Node::new(data));
-        self.new_node_id(key)
+        self.structure_changed();
+        let node_id = self.new_node_id(key);
+        if let Some(on_insert) = &mut self.hooks.on_insert {
+            on_insert(node_id);
+        }
+        node_id
     }
 
     pub(crate) fn remove(&mut self, node_id: NodeId) -> Option<T> {
-        self.filter_by_tree_id(node_id)
+        let data = self
+            .filter_by_tree_id(node_id)
             .and_then(|id| self.slab.try_remove(id.index))
-            .map(|node| node.data)
+            .map(|node| node.data);
+        if data.is_some() {
+            self.forget(node_id);
+        }
+        data
     }
 
-    pub(crate) fn get(&self, node_id: NodeId) -> Option<&Node<T>> {
+    // drops everything kept about a node whose slot was vacated, and tells the hook about it
+    pub(crate) fn forget(&mut self, node_id: NodeId) {
+        self.structure_changed();
+        if let Some(key) = self.node_keys.remove(&node_id.index.index) {
+            self.keys.remove(&key);
+        }
+        self.dirty.remove(&node_id);
+        if let Some(on_remove) = &mut self.hooks.on_remove {
+            on_remove(node_id);
+        }
+    }
+
+    pub(crate) fn replace_data_with<F: FnOnce(T) -> T>(&mut self, node_id: NodeId, f: F) -> bool {
+        let replaced = match self.filter_by_tree_id(node_id) {
+            Some(id) => self.slab.replace_with(id.index, |node| Node {
+                data: f(node.data),
+                edge: node.edge,
+                relatives: node.relatives,
+            }),
+            None => false,
+        };
+        if replaced {
+            self.data_version += 1;
+        }
+        replaced
+    }
+
+    // gives the node `key`, replacing any key it had; fails if another node has `key`
+    pub(crate) fn set_key(&mut self, node_id: NodeId, key: u64) -> bool {
+        if self.get(node_id).is_none() {
+            return false;
+        }
+        match self.keys.get(&key) {
+            Some(&other) if other != node_id => return false,
+            _ => {}
+        }
+        if let Some(old_key) = self.node_keys.insert(node_id.index.index, key) {
+            self.keys.remove(&old_key);
+        }
+        self.keys.insert(key, self.stamp(node_id));
+        true
+    }
+
+    pub(crate) fn key_of(&self, node_id: NodeId) -> Option<u64> {
+        self.get(node_id)?;
+        self.node_keys.get(&node_id.index.index).copied()
+    }
+
+    pub(crate) fn find_by_key(&self, key: u64) -> Option<NodeId> {
+        self.keys.get(&key).copied()
+    }
+
+    pub(crate) fn get(&self, node_id: NodeId) -> Option<&Node<T, E>> {
         self.filter_by_tree_id(node_id)
             .and_then(|id| self.slab.get(id.index))
     }
 
-    pub(crate) fn get_mut(&mut self, node_id: NodeId) -> Option<&mut Node<T>> {
+    pub(crate) fn get_mut(&mut self, node_id: NodeId) -> Option<&mut Node<T, E>> {
         self.filter_by_tree_id(node_id)
             .and_then(move |id| self.slab.get_mut(id.index))
     }
 
-    #[cfg(feature = "experimental")]
-    pub(crate) fn compact(&mut self) -> usize {
-        use std::collections::HashMap;
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (NodeId, &Node<T, E>)> {
+        self.slab
+            .iter()
+            .map(move |(index, node)| (self.new_node_id(index), node))
+    }
+
+    pub(crate) fn get_many_mut(&mut self, node_ids: &[NodeId]) -> Option<Vec<&mut Node<T, E>>> {
+        let mut indices = Vec::with_capacity(node_ids.len());
+        for node_id in node_ids {
+            indices.push(self.filter_by_tree_id(*node_id)?.index);
+        }
+        let nodes = self.slab.get_many_mut(&indices)?;
+        self.data_version += 1;
+        Some(nodes)
+    }
+
+    pub(crate) fn get_disjoint_mut<const N: usize>(
+        &mut self,
+        node_ids: [NodeId; N],
+    ) -> Option<[&mut Node<T, E>; N]> {
+        if node_ids.iter().any(|node_id| node_id.tree_id != self.id) {
+            return None;
+        }
+        let nodes = self
+            .slab
+            .get_disjoint_mut(node_ids.map(|node_id| node_id.index))?;
+        self.data_version += 1;
+        Some(nodes)
+    }
 
+    // returns the mapping from each moved node's old slab index to its new NodeId
+    pub(crate) fn compact_rekeyed(&mut self) -> HashMap<usize, NodeId> {
         // collect a vec of rekeyed indices
         let mut rekey_tuples = Vec::new();
+        self.structure_changed();
         self.slab.compact(|from, to| {
             // cannot create NodeId here because 'self' can't be used inside this closure
             rekey_tuples.push((from, to));
         });
 
+        // every NodeId handed out so far predates this compaction
+        if !rekey_tuples.is_empty() {
+            self.compaction += 1;
+        }
+
         // convert to a hashmap of from(usize)->to(NodeId) for easier rekey check
         let mut rekeys = HashMap::new();
         for (from, to) in rekey_tuples.into_iter() {
@@ -69,60 +290,114 @@ impl<T> CoreTree<T> {
             self.fix_rekeyed_node(*from, *to, &rekeys);
         }
 
-        self.capacity()
+        // keys follow their nodes
+        let moved_keys: Vec<(u64, NodeId)> = rekeys
+            .iter()
+            .filter_map(|(from, to)| Some((self.node_keys.remove(from)?, *to)))
+            .collect();
+        for (key, to) in moved_keys {
+            self.node_keys.insert(to.index.index, key);
+            self.keys.insert(key, to);
+        }
+
+        // and so do dirty marks
+        self.dirty = self
+            .dirty
+            .drain()
+            .map(|node_id| *rekeys.get(&node_id.index.index).unwrap_or(&node_id))
+            .collect();
+
+        #[cfg(feature = "experimental")]
+        if !rekeys.is_empty() {
+            self.restamp();
+        }
+        rekeys
     }
 
+    // stamps every NodeId stored in the tree with the current compaction generation, so that
+    // the ones handed out from now on are current
     #[cfg(feature = "experimental")]
-    fn fix_rekeyed_node(
-        &mut self,
-        from: usize,
-        to: NodeId,
-        rekeys: &std::collections::HashMap<usize, NodeId>,
-    ) {
-        let relatives = { self.get(to).unwrap().relatives };
+    fn restamp(&mut self) {
+        let node_ids: Vec<NodeId> = self.iter().map(|(node_id, _)| node_id).collect();
+        for node_id in node_ids {
+            let relatives = self.get(node_id).expect("node must exist").relatives;
+            let stamp = |id: Option<NodeId>| id.map(|id| self.stamp(id));
+            let relatives = crate::node::Relatives {
+                parent: stamp(relatives.parent),
+                prev_sibling: stamp(relatives.prev_sibling),
+                next_sibling: stamp(relatives.next_sibling),
+                first_child: stamp(relatives.first_child),
+                last_child: stamp(relatives.last_child),
+            };
+            self.get_mut(node_id).expect("node must exist").relatives = relatives;
+        }
+        let compaction = self.compaction;
+        for node_id in self.keys.values_mut() {
+            node_id.compaction = compaction;
+        }
+        self.dirty = self
+            .dirty
+            .drain()
+            .map(|node_id| NodeId {
+                compaction,
+                ..node_id
+            })
+            .collect();
+    }
+
+    // every node is supposed to be present after a compaction; if one isn't, the relation that
+    // points at it is left alone rather than panicking halfway through the fix-up
+    fn fix_rekeyed_node(&mut self, from: usize, to: NodeId, rekeys: &HashMap<usize, NodeId>) {
+        let rekeyed = |node_id: NodeId| *rekeys.get(&node_id.index.index).unwrap_or(&node_id);
+        let is_from = |node_id: Option<NodeId>| node_id.is_some_and(|id| id.index.index == from);
+
+        let Some(relatives) = self.get(to).map(|node| node.relatives) else {
+            debug_assert!(false, "rekeyed node must be present");
+            return;
+        };
 
         // first fix the parent relation
         if let Some(parent) = relatives.parent {
-            // check if parent was rekeyed
-            let parent = *rekeys.get(&parent.index.index).unwrap_or(&parent);
-
-            // unwrap() because all data are supposed to be present
-            let parent = self.get_mut(parent).unwrap();
-            if parent.relatives.first_child.unwrap().index.index == from {
-                parent.relatives.first_child = Some(to);
-            }
-            if parent.relatives.last_child.unwrap().index.index == from {
-                parent.relatives.last_child = Some(to);
+            match self.get_mut(rekeyed(parent)) {
+                Some(parent) => {
+                    if is_from(parent.relatives.first_child) {
+                        parent.relatives.first_child = Some(to);
+                    }
+                    if is_from(parent.relatives.last_child) {
+                        parent.relatives.last_child = Some(to);
+                    }
+                }
+                None => debug_assert!(false, "parent of a rekeyed node must be present"),
             }
         }
 
         // second fix the siblings relations
         if let Some(prev) = relatives.prev_sibling {
-            // check if prev_sibling was rekeyed
-            let prev = *rekeys.get(&prev.index.index).unwrap_or(&prev);
-
-            // unwrap() because all data are supposed to be present
-            let prev = self.get_mut(prev).unwrap();
-            if prev.relatives.next_sibling.unwrap().index.index == from {
-                prev.relatives.next_sibling = Some(to);
+            match self.get_mut(rekeyed(prev)) {
+                Some(prev) if is_from(prev.relatives.next_sibling) => {
+                    prev.relatives.next_sibling = Some(to);
+                }
+                Some(_) => {}
+                None => debug_assert!(false, "sibling of a rekeyed node must be present"),
             }
         }
         if let Some(next) = relatives.next_sibling {
-            // check if next_sibling was rekeyed
-            let next = *rekeys.get(&next.index.index).unwrap_or(&next);
-
-            // unwrap() because all data are supposed to be present
-            let next = self.get_mut(next).unwrap();
-            if next.relatives.prev_sibling.unwrap().index.index == from {
-                next.relatives.prev_sibling = Some(to);
+            match self.get_mut(rekeyed(next)) {
+                Some(next) if is_from(next.relatives.prev_sibling) => {
+                    next.relatives.prev_sibling = Some(to);
+                }
+                Some(_) => {}
+                None => debug_assert!(false, "sibling of a rekeyed node must be present"),
             }
         }
 
         // third fix the children relations
         let mut next_child_id = relatives.first_child;
         while let Some(child_id) = next_child_id {
-            // unwrap() because the data is supposed to be present
-            let child = self.get_mut(child_id).unwrap();
+            let Some(child) = self.get_mut(rekeyed(child_id)) else {
+                debug_assert!(false, "child of a rekeyed node must be present");
+                return;
+            };
             child.relatives.parent = Some(to);
             next_child_id = child.relatives.next_sibling;
         }
@@ -132,6 +407,8 @@ impl<T> CoreTree<T> {
         NodeId {
             tree_id: self.id,
             index,
+            #[cfg(feature = "experimental")]
+            compaction: self.compaction,
         }
     }
 
@@ -159,9 +436,25 @@ mod tests {
         assert_eq!(tree.capacity(), capacity);
     }
 
+    #[test]
+    fn insert_with_fixed_growth() {
+        let mut tree = CoreTree::<_>::new(0);
+        tree.set_growth_policy(GrowthPolicy::Fixed(3));
+
+        tree.insert(1);
+        assert_eq!(tree.capacity(), 3);
+
+        tree.insert(2);
+        tree.insert(3);
+        assert_eq!(tree.capacity(), 3);
+
+        tree.insert(4);
+        assert_eq!(tree.capacity(), 6);
+    }
+
     #[test]
     fn insert() {
-        let mut tree = CoreTree::new(0);
+        let mut tree = CoreTree::<_>::new(0);
 
         let id = tree.insert(1);
         let id2 = tree.insert(3);
@@ -172,7 +465,7 @@ mod tests {
 
     #[test]
     fn remove() {
-        let mut tree = CoreTree::new(0);
+        let mut tree = CoreTree::<_>::new(0);
 
         let id = tree.insert(1);
         assert_eq!(tree.get(id).unwrap().data, 1);
@@ -186,7 +479,7 @@ mod tests {
 
     #[test]
     fn get() {
-        let mut tree = CoreTree::new(0);
+        let mut tree = CoreTree::<_>::new(0);
 
         let id = tree.insert(1);
         let id2 = tree.insert(3);
@@ -197,7 +490,7 @@ mod tests {
 
     #[test]
     fn get_mut() {
-        let mut tree = CoreTree::new(0);
+        let mut tree = CoreTree::<_>::new(0);
 
         let id = tree.insert(1);
         let id2 = tree.insert(3);
@@ -206,9 +499,98 @@ mod tests {
         assert_eq!(tree.get_mut(id2).unwrap().data, 3);
     }
 
+    #[test]
+    fn hooks() {
+        use std::sync::{Arc, Mutex};
+
+        let mut tree = CoreTree::<_>::new(0);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let insert_events = Arc::clone(&events);
+        tree.hooks.on_insert = Some(Box::new(move |node_id| {
+            insert_events.lock().unwrap().push(("insert", node_id))
+        }));
+        let remove_events = Arc::clone(&events);
+        tree.hooks.on_remove = Some(Box::new(move |node_id| {
+            remove_events.lock().unwrap().push(("remove", node_id))
+        }));
+
+        let id = tree.insert(1);
+        let id2 = tree.insert(2);
+        tree.remove(id);
+        tree.remove(id);
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![("insert", id), ("insert", id2), ("remove", id)]
+        );
+    }
+
+    #[test]
+    fn recycle() {
+        let mut tree = CoreTree::<_>::new(5);
+        tree.set_growth_policy(GrowthPolicy::Fixed(1));
+        let old_tree_id = tree.id;
+        let id = tree.insert(1);
+
+        tree.recycle();
+
+        assert_ne!(tree.id, old_tree_id);
+        assert_eq!(tree.capacity(), 5);
+        assert_eq!(tree.growth_policy, GrowthPolicy::Double);
+        assert!(tree.get(id).is_none());
+        assert_eq!(tree.iter().count(), 0);
+
+        let new_id = tree.insert(2);
+        assert_eq!(new_id.index.index, id.index.index);
+        assert!(tree.get(id).is_none());
+    }
+
+    #[test]
+    fn get_many_mut() {
+        let mut tree = CoreTree::<_>::new(0);
+        let tree2: CoreTree<i32> = CoreTree::new(0);
+
+        let id = tree.insert(1);
+        let id2 = tree.insert(3);
+
+        let many = tree.get_many_mut(&[id2, id]).unwrap();
+        assert_eq!(many[0].data, 3);
+        assert_eq!(many[1].data, 1);
+
+        let mut bad_id = id;
+        bad_id.tree_id = tree2.id; // oops, wrong tree id.
+
+        assert!(tree.get_many_mut(&[id2, bad_id]).is_none());
+    }
+
+    #[test]
+    fn data_version_only_changes_on_success() {
+        let mut tree = CoreTree::<_>::new(0);
+        let id = tree.insert(1);
+        let id2 = tree.insert(2);
+        let data_version = tree.data_version;
+
+        assert!(tree.get_many_mut(&[id, id]).is_none());
+        assert!(!tree.replace_data_with(
+            NodeId {
+                tree_id: ProcessUniqueId::new(),
+                ..id
+            },
+            |x| x
+        ));
+        tree.remove(id2);
+        assert!(!tree.replace_data_with(id2, |x| x));
+        assert_eq!(tree.data_version, data_version);
+
+        assert!(tree.replace_data_with(id, |x| x + 1));
+        assert_eq!(tree.data_version, data_version + 1);
+        assert!(tree.get_many_mut(&[id]).is_some());
+        assert_eq!(tree.data_version, data_version + 2);
+    }
+
     #[test]
     fn get_with_bad_id() {
-        let mut tree = CoreTree::new(0);
+        let mut tree = CoreTree::<_>::new(0);
         let tree2: CoreTree<i32> = CoreTree::new(0);
 
         let mut id = tree.insert(1);
@@ -0,0 +1,294 @@
+use crate::node::NodeRef;
+use crate::tree::Tree;
+
+///
+/// A single operation of an edit script produced by `tree_diff`.
+///
+/// `Node`s are addressed by their path from the root: the child positions to follow starting at
+/// the root, so an empty path is the root itself.  Paths describe the shape of the `Tree` at the
+/// moment the edit is applied, which means the edits of a script must be applied in order.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum TreeEdit<T> {
+    ///
+    /// Insert a new leaf `Node` containing `data` at `path`, shifting the `Node` previously at
+    /// that position (and all of its next siblings) one position to the right.  An empty path
+    /// inserts a new root.
+    ///
+    Insert { path: Vec<usize>, data: T },
+
+    ///
+    /// Remove the `Node` at `path` along with all of its descendants.
+    ///
+    Remove { path: Vec<usize> },
+
+    ///
+    /// Replace the data of the `Node` at `path` with `data`.
+    ///
+    Update { path: Vec<usize>, data: T },
+}
+
+///
+/// Computes the edits that transform `old` into `new`.
+///
+/// Children are aligned by position: the n-th child of a `Node` in `old` is compared with the
+/// n-th child of the matching `Node` in `new`.  Data that differs produces an `Update`, surplus
+/// children of `old` produce `Remove`s (last child first) and surplus children of `new` produce
+/// `Insert`s of their whole sub-tree in pre-order.  The result is not a minimal edit script; for
+/// instance, prepending a child to a `Node` updates every existing child and inserts a new last
+/// one.
+///
+/// ```
+/// use nary_tree::diff::{tree_diff, TreeEdit};
+/// use nary_tree::tree::TreeBuilder;
+///
+/// let mut old = TreeBuilder::new().with_root(1).build();
+/// let mut root = old.root_mut().unwrap();
+/// root.append2(2);
+/// root.append2(3);
+///
+/// let mut new = TreeBuilder::new().with_root(1).build();
+/// new.root_mut().unwrap().append(4).append(5);
+///
+/// assert_eq!(
+///     tree_diff(&old, &new),
+///     vec![
+///         TreeEdit::Remove { path: vec![1] },
+///         TreeEdit::Update { path: vec![0], data: 4 },
+///         TreeEdit::Insert { path: vec![0, 0], data: 5 },
+///     ]
+/// );
+/// ```
+///
+pub fn tree_diff<T: PartialEq + Clone>(old: &Tree<T>, new: &Tree<T>) -> Vec<TreeEdit<T>> {
+    let mut edits = Vec::new();
+    match (old.root(), new.root()) {
+        (None, None) => {}
+        (Some(_), None) => edits.push(TreeEdit::Remove { path: vec![] }),
+        (None, Some(new_root)) => push_inserts(new_root, vec![], &mut edits),
+        (Some(old_root), Some(new_root)) => {
+            let mut stack = vec![(old_root, new_root, vec![])];
+            while let Some((old_node, new_node, path)) = stack.pop() {
+                if old_node.data() != new_node.data() {
+                    edits.push(TreeEdit::Update {
+                        path: path.clone(),
+                        data: new_node.data().clone(),
+                    });
+                }
+
+                let old_children: Vec<NodeRef<T>> = old_node.children().collect();
+                let mut new_children: Vec<NodeRef<T>> = new_node.children().collect();
+
+                // removing back to front keeps the positions of the remaining children stable
+                for position in (new_children.len()..old_children.len()).rev() {
+                    edits.push(TreeEdit::Remove {
+                        path: child_path(&path, position),
+                    });
+                }
+
+                let common = old_children.len().min(new_children.len());
+                let surplus = new_children.split_off(common);
+                for (position, new_child) in surplus.into_iter().enumerate() {
+                    push_inserts(new_child, child_path(&path, common + position), &mut edits);
+                }
+
+                // push in reverse so that the children are diffed first to last
+                let pairs = old_children.into_iter().zip(new_children).enumerate();
+                for (position, (old_child, new_child)) in pairs.rev() {
+                    stack.push((old_child, new_child, child_path(&path, position)));
+                }
+            }
+        }
+    }
+    edits
+}
+
+fn push_inserts<T: Clone>(node: NodeRef<T>, path: Vec<usize>, edits: &mut Vec<TreeEdit<T>>) {
+    let mut stack = vec![(node, path)];
+    while let Some((node, path)) = stack.pop() {
+        let children: Vec<NodeRef<T>> = node.children().collect();
+        for (position, child) in children.into_iter().enumerate().rev() {
+            stack.push((child, child_path(&path, position)));
+        }
+        edits.push(TreeEdit::Insert {
+            path,
+            data: node.data().clone(),
+        });
+    }
+}
+
+fn child_path(path: &[usize], position: usize) -> Vec<usize> {
+    let mut child_path = Vec::with_capacity(path.len() + 1);
+    child_path.extend_from_slice(path);
+    child_path.push(position);
+    child_path
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod diff_tests {
+    use super::*;
+    use crate::tree::TreeBuilder;
+
+    #[test]
+    fn diff_empty_trees() {
+        let old: Tree<i32> = Tree::new();
+        let new: Tree<i32> = Tree::new();
+        assert!(tree_diff(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn diff_equal_trees() {
+        let mut old = TreeBuilder::new().with_root(1).build();
+        old.root_mut().unwrap().append(2).append(3);
+        let mut new = TreeBuilder::new().with_root(1).build();
+        new.root_mut().unwrap().append(2).append(3);
+
+        assert!(tree_diff(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn diff_from_empty() {
+        let old = Tree::new();
+        let mut new = TreeBuilder::new().with_root(1).build();
+        let mut root = new.root_mut().unwrap();
+        let two_id = root.append2(2);
+        root.append2(4);
+        new.get_mut(two_id).unwrap().append(3);
+
+        assert_eq!(
+            tree_diff(&old, &new),
+            vec![
+                TreeEdit::Insert {
+                    path: vec![],
+                    data: 1
+                },
+                TreeEdit::Insert {
+                    path: vec![0],
+                    data: 2
+                },
+                TreeEdit::Insert {
+                    path: vec![0, 0],
+                    data: 3
+                },
+                TreeEdit::Insert {
+                    path: vec![1],
+                    data: 4
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_to_empty() {
+        let mut old = TreeBuilder::new().with_root(1).build();
+        old.root_mut().unwrap().append2(2);
+        let new = Tree::new();
+
+        assert_eq!(
+            tree_diff(&old, &new),
+            vec![TreeEdit::Remove { path: vec![] }]
+        );
+    }
+
+    #[test]
+    fn diff_removes_back_to_front() {
+        let mut old = TreeBuilder::new().with_root(1).build();
+        let mut root = old.root_mut().unwrap();
+        root.append2(2);
+        root.append2(3);
+        root.append2(4);
+        let mut new = TreeBuilder::new().with_root(1).build();
+        new.root_mut().unwrap().append2(2);
+
+        assert_eq!(
+            tree_diff(&old, &new),
+            vec![
+                TreeEdit::Remove { path: vec![2] },
+                TreeEdit::Remove { path: vec![1] },
+            ]
+        );
+    }
+
+    #[test]
+    fn apply_diff_round_trip() {
+        let mut old = TreeBuilder::new().with_root(0).build();
+        let mut root = old.root_mut().unwrap();
+        let one_id = root.append2(1);
+        root.append2(5);
+        root.append2(9);
+        old.get_mut(one_id).unwrap().append(2).append(3);
+
+        let mut new = TreeBuilder::new().with_root(0).build();
+        let mut root = new.root_mut().unwrap();
+        root.append2(1);
+        let six_id = root.append2(6);
+        let mut six = new.get_mut(six_id).unwrap();
+        six.append2(7);
+        six.append(8).append(10);
+
+        let edits = tree_diff(&old, &new);
+        assert!(!edits.is_empty());
+        assert!(old.apply_edits(&edits));
+        assert!(tree_diff(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn apply_diff_round_trip_with_empty_trees() {
+        let mut old = Tree::new();
+        let mut new = TreeBuilder::new().with_root(1).build();
+        new.root_mut().unwrap().append(2).append(3);
+
+        assert!(old.apply_edits(&tree_diff(&old, &new)));
+        assert!(tree_diff(&old, &new).is_empty());
+
+        let empty = Tree::new();
+        assert!(old.apply_edits(&tree_diff(&old, &empty)));
+        assert!(old.root().is_none());
+    }
+
+    #[test]
+    fn apply_invalid_edit() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let edits = vec![
+            TreeEdit::Update {
+                path: vec![],
+                data: 2,
+            },
+            TreeEdit::Insert {
+                path: vec![1],
+                data: 3,
+            },
+            TreeEdit::Update {
+                path: vec![],
+                data: 4,
+            },
+        ];
+
+        assert!(!tree.apply_edits(&edits));
+        assert_eq!(tree.root().unwrap().data(), &2);
+        assert!(tree.root().unwrap().first_child().is_none());
+    }
+
+    #[test]
+    fn diff_updates_nested_data() {
+        let mut old = TreeBuilder::new().with_root(1).build();
+        old.root_mut().unwrap().append(2).append(3);
+        let mut new = TreeBuilder::new().with_root(0).build();
+        new.root_mut().unwrap().append(2).append(6);
+
+        assert_eq!(
+            tree_diff(&old, &new),
+            vec![
+                TreeEdit::Update {
+                    path: vec![],
+                    data: 0
+                },
+                TreeEdit::Update {
+                    path: vec![0, 0],
+                    data: 6
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,453 @@
+use crate::behaviors::RemoveBehavior;
+use crate::node::{NodeMut, NodeRef};
+use crate::tree::{MoveError, Tree};
+use crate::NodeId;
+
+///
+/// A collection of disconnected trees sharing the same storage.
+///
+/// Each tree in a `Forest` has its own root, and the roots are kept in the order the trees were
+/// added.  Since all of the `Node`s live in the same storage, `NodeId`s are valid across the
+/// whole `Forest` and sub-trees can be moved from one tree to another without copying anything.
+/// `Node`s are read through the same `NodeRef`s as in a `Tree`, and changed through
+/// `ForestNodeMut`s, which leave out the `NodeMut` methods that could orphan `Node`s behind the
+/// `Forest`'s back.
+///
+/// ```
+/// use nary_tree::forest::Forest;
+///
+/// let mut forest = Forest::new();
+/// let animals_id = forest.add_root("animals");
+/// let plants_id = forest.add_root("plants");
+/// let cat_id = forest.get_mut(animals_id).unwrap().append2("cat");
+/// let fern_id = forest.get_mut(animals_id).unwrap().append2("fern");
+///
+/// // the fern was filed under the wrong tree
+/// forest.move_subtree(fern_id, plants_id).unwrap();
+///
+/// let roots = forest.iter_roots().map(|root| *root.data()).collect::<Vec<&str>>();
+/// assert_eq!(roots, vec!["animals", "plants"]);
+/// assert_eq!(forest.get(plants_id).unwrap().first_child().unwrap().node_id(), fern_id);
+/// assert_eq!(forest.get(animals_id).unwrap().last_child().unwrap().node_id(), cat_id);
+/// ```
+///
+#[derive(Debug)]
+pub struct Forest<T> {
+    // the shared storage; its own root is never set, every tree's root is in `roots`
+    tree: Tree<T>,
+    roots: Vec<NodeId>,
+}
+
+impl<T> Forest<T> {
+    ///
+    /// Creates a new, empty `Forest`.
+    ///
+    pub fn new() -> Forest<T> {
+        Forest {
+            tree: Tree::new(),
+            roots: Vec::new(),
+        }
+    }
+
+    ///
+    /// Adds a new tree made of a single root `Node` holding `data`, and returns its `NodeId`.
+    ///
+    pub fn add_root(&mut self, data: T) -> NodeId {
+        let root_id = self.tree.core_tree.insert(data);
+        self.roots.push(root_id);
+        root_id
+    }
+
+    ///
+    /// Moves the `Node`s of `tree` reachable from its root into the `Forest` as a new tree, and
+    /// returns the `NodeId` of its root.  The `Node`s get new `NodeId`s.  Returns a `None`-value
+    /// (and adds nothing) if `tree` has no root.
+    ///
+    /// ```
+    /// use nary_tree::forest::Forest;
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// tree.root_mut().unwrap().append2(2);
+    ///
+    /// let mut forest = Forest::new();
+    /// let root_id = forest.add_tree(tree).unwrap();
+    /// assert_eq!(forest.get(root_id).unwrap().first_child().unwrap().data(), &2);
+    /// ```
+    ///
+    pub fn add_tree(&mut self, tree: Tree<T>) -> Option<NodeId> {
+        let root_id = self.tree.adopt(tree)?;
+        self.roots.push(root_id);
+        Some(root_id)
+    }
+
+    ///
+    /// Returns the `NodeId`s of the roots of the trees in the `Forest`, in order.
+    ///
+    pub fn root_ids(&self) -> &[NodeId] {
+        &self.roots
+    }
+
+    ///
+    /// Returns an `Iterator` over the roots of the trees in the `Forest`, in order.
+    ///
+    pub fn iter_roots(&self) -> impl Iterator<Item = NodeRef<'_, T>> {
+        self.roots
+            .iter()
+            .map(move |root_id| self.tree.get(*root_id).expect("root must exist"))
+    }
+
+    ///
+    /// Returns a `NodeRef` pointing to the `Node` that `node_id` identifies, in any of the trees.
+    /// Returns a `None`-value if `node_id` points to nothing (or belongs to a different `Forest`).
+    ///
+    pub fn get(&self, node_id: NodeId) -> Option<NodeRef<'_, T>> {
+        self.tree.get(node_id)
+    }
+
+    ///
+    /// Returns a `ForestNodeMut` pointing to the `Node` that `node_id` identifies, in any of the
+    /// trees.  Returns a `None`-value if `node_id` points to nothing (or belongs to a different
+    /// `Forest`).
+    ///
+    pub fn get_mut(&mut self, node_id: NodeId) -> Option<ForestNodeMut<'_, T>> {
+        self.tree.get_mut(node_id).map(ForestNodeMut::new)
+    }
+
+    ///
+    /// Moves the `Node` identified by `node_id` (along with its sub-tree) under the `Node`
+    /// identified by `new_parent`, as its last child.  Both can be in the same tree or in
+    /// different ones; moving the root of a tree merges that tree into the other.
+    ///
+    /// Returns a `MoveError` (and moves nothing) if either `NodeId` points to nothing (or
+    /// belongs to a different `Forest`), or if `new_parent` is part of the sub-tree of
+    /// `node_id`.
+    ///
+    pub fn move_subtree(&mut self, node_id: NodeId, new_parent: NodeId) -> Result<(), MoveError> {
+        self.tree.reparent_many(&[node_id], new_parent)?;
+        self.roots.retain(|root_id| *root_id != node_id);
+        Ok(())
+    }
+
+    ///
+    /// Removes the `Node` identified by `node_id` and returns its data.  With `DropChildren` its
+    /// sub-tree goes along; with `OrphanChildren` each of its children becomes the root of a
+    /// new tree, added at the end of the `Forest`.  Returns a `None`-value if `node_id` points to
+    /// nothing (or belongs to a different `Forest`).
+    ///
+    /// ```
+    /// use nary_tree::behaviors::RemoveBehavior::OrphanChildren;
+    /// use nary_tree::forest::Forest;
+    ///
+    /// let mut forest = Forest::new();
+    /// let root_id = forest.add_root(1);
+    /// let mut root = forest.get_mut(root_id).unwrap();
+    /// let two_id = root.append2(2);
+    /// let three_id = root.append2(3);
+    ///
+    /// assert_eq!(forest.remove(root_id, OrphanChildren), Some(1));
+    /// assert_eq!(forest.root_ids(), &[two_id, three_id]);
+    /// ```
+    ///
+    pub fn remove(&mut self, node_id: NodeId, behavior: RemoveBehavior) -> Option<T> {
+        let children = match behavior {
+            RemoveBehavior::OrphanChildren => self.tree.get(node_id)?.children_ids(),
+            RemoveBehavior::DropChildren => Vec::new(),
+        };
+        let data = self.tree.remove(node_id, behavior)?;
+        self.roots.retain(|root_id| *root_id != node_id);
+        self.roots.extend(children);
+        Some(data)
+    }
+
+    ///
+    /// Returns the number of trees in the `Forest`.
+    ///
+    pub fn tree_count(&self) -> usize {
+        self.roots.len()
+    }
+}
+
+impl<T: PartialEq> Forest<T> {
+    ///
+    /// Returns the `NodeId`s of every `Node` whose data is equal to `data`, across all of the
+    /// trees, in order of the trees and in level-order within each tree (like `Tree::find`).
+    /// Returns a `None`-value if there are none.
+    ///
+    /// ```
+    /// use nary_tree::forest::Forest;
+    ///
+    /// let mut forest = Forest::new();
+    /// let one_id = forest.add_root(1);
+    /// let two_id = forest.add_root(2);
+    /// let other_one_id = forest.get_mut(two_id).unwrap().append2(1);
+    ///
+    /// assert_eq!(forest.find(&1), Some(vec![one_id, other_one_id]));
+    /// assert_eq!(forest.find(&3), None);
+    /// ```
+    ///
+    pub fn find(&self, data: &T) -> Option<Vec<NodeId>> {
+        let matches: Vec<NodeId> = self
+            .iter_roots()
+            .flat_map(|root| root.traverse_level_order())
+            .filter(|node| node.data() == data)
+            .map(|node| node.node_id())
+            .collect();
+        if matches.is_empty() {
+            None
+        } else {
+            Some(matches)
+        }
+    }
+}
+
+impl<T> Default for Forest<T> {
+    fn default() -> Self {
+        Forest::new()
+    }
+}
+
+///
+/// A mutable reference to a given `Node`'s data and its relatives in a `Forest`.
+///
+/// It offers the parts of `NodeMut` that can't leave a `Node` without a parent: removing a child
+/// always drops the child's sub-tree along with it.  Use `Forest::remove` with `OrphanChildren`
+/// to turn children into trees of their own.
+///
+/// ```
+/// use nary_tree::forest::Forest;
+///
+/// let mut forest = Forest::new();
+/// let root_id = forest.add_root(1);
+/// forest.get_mut(root_id).unwrap().append(2).append(3);
+///
+/// let mut two = forest.get_mut(root_id).unwrap().first_child().unwrap();
+/// *two.data() *= 10;
+/// assert_eq!(two.remove_first(), Some(3));
+/// assert_eq!(forest.get(root_id).unwrap().first_child().unwrap().data(), &20);
+/// ```
+///
+#[derive(Debug)]
+pub struct ForestNodeMut<'a, T> {
+    node: NodeMut<'a, T>,
+}
+
+impl<'a, T> ForestNodeMut<'a, T> {
+    fn new(node: NodeMut<'a, T>) -> ForestNodeMut<'a, T> {
+        ForestNodeMut { node }
+    }
+
+    ///
+    /// Returns the `NodeId` that identifies this `Node` in the `Forest`.
+    ///
+    pub fn node_id(&self) -> NodeId {
+        self.node.node_id()
+    }
+
+    ///
+    /// Returns a mutable reference to the data contained by this `Node`.
+    ///
+    pub fn data(&mut self) -> &mut T {
+        self.node.data()
+    }
+
+    ///
+    /// Returns a `ForestNodeMut` pointing to this `Node`'s parent, if it has one.
+    ///
+    pub fn parent(self) -> Option<ForestNodeMut<'a, T>> {
+        self.node.parent().map(ForestNodeMut::new)
+    }
+
+    ///
+    /// Returns a `ForestNodeMut` pointing to this `Node`'s previous sibling, if it has one.
+    ///
+    pub fn prev_sibling(self) -> Option<ForestNodeMut<'a, T>> {
+        self.node.prev_sibling().map(ForestNodeMut::new)
+    }
+
+    ///
+    /// Returns a `ForestNodeMut` pointing to this `Node`'s next sibling, if it has one.
+    ///
+    pub fn next_sibling(self) -> Option<ForestNodeMut<'a, T>> {
+        self.node.next_sibling().map(ForestNodeMut::new)
+    }
+
+    ///
+    /// Returns a `ForestNodeMut` pointing to this `Node`'s first child, if it has any.
+    ///
+    pub fn first_child(self) -> Option<ForestNodeMut<'a, T>> {
+        self.node.first_child().map(ForestNodeMut::new)
+    }
+
+    ///
+    /// Returns a `ForestNodeMut` pointing to this `Node`'s last child, if it has any.
+    ///
+    pub fn last_child(self) -> Option<ForestNodeMut<'a, T>> {
+        self.node.last_child().map(ForestNodeMut::new)
+    }
+
+    ///
+    /// Appends a new `Node` containing `data` as this `Node`'s last child and returns a
+    /// `ForestNodeMut` pointing to it.
+    ///
+    pub fn append(self, data: T) -> ForestNodeMut<'a, T> {
+        ForestNodeMut::new(self.node.append(data))
+    }
+
+    ///
+    /// Appends a new `Node` containing `data` as this `Node`'s last child and returns its
+    /// `NodeId`.
+    ///
+    pub fn append2(&mut self, data: T) -> NodeId {
+        self.node.append2(data)
+    }
+
+    ///
+    /// Prepends a new `Node` containing `data` as this `Node`'s first child and returns a
+    /// `ForestNodeMut` pointing to it.
+    ///
+    pub fn prepend(self, data: T) -> ForestNodeMut<'a, T> {
+        ForestNodeMut::new(self.node.prepend(data))
+    }
+
+    ///
+    /// Prepends a new `Node` containing `data` as this `Node`'s first child and returns its
+    /// `NodeId`.
+    ///
+    pub fn prepend2(&mut self, data: T) -> NodeId {
+        self.node.prepend2(data)
+    }
+
+    ///
+    /// Removes this `Node`'s first child, along with its sub-tree, and returns the child's data.
+    /// Returns a `None`-value if this `Node` has no children.
+    ///
+    pub fn remove_first(&mut self) -> Option<T> {
+        self.node.remove_first(RemoveBehavior::DropChildren)
+    }
+
+    ///
+    /// Removes this `Node`'s last child, along with its sub-tree, and returns the child's data.
+    /// Returns a `None`-value if this `Node` has no children.
+    ///
+    pub fn remove_last(&mut self) -> Option<T> {
+        self.node.remove_last(RemoveBehavior::DropChildren)
+    }
+
+    ///
+    /// Returns a `NodeRef` pointing to this `Node`.
+    ///
+    pub fn as_ref(self) -> NodeRef<'a, T> {
+        self.node.as_ref()
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod forest_tests {
+    use super::*;
+    use crate::behaviors::RemoveBehavior::{DropChildren, OrphanChildren};
+    use crate::tree::TreeBuilder;
+
+    #[test]
+    fn add_and_iterate() {
+        let mut forest = Forest::new();
+        assert_eq!(forest.tree_count(), 0);
+        assert!(forest.add_tree(Tree::new()).is_none());
+
+        let one_id = forest.add_root(1);
+        let mut tree = TreeBuilder::new().with_root(2).build();
+        let three_id = tree.root_mut().unwrap().append2(3);
+        let two_id = forest.add_tree(tree).unwrap();
+
+        assert_eq!(forest.tree_count(), 2);
+        assert_eq!(forest.root_ids(), &[one_id, two_id]);
+        assert!(forest.get(three_id).is_none());
+        let values: Vec<i32> = forest
+            .iter_roots()
+            .flat_map(|root| root.traverse_pre_order())
+            .map(|node| *node.data())
+            .collect();
+        assert_eq!(values, vec![1, 2, 3]);
+        assert!(forest.get(one_id).unwrap().parent().is_none());
+    }
+
+    #[test]
+    fn move_subtree() {
+        let mut forest = Forest::new();
+        let a_id = forest.add_root("a");
+        let b_id = forest.add_root("b");
+        let c_id = forest.get_mut(a_id).unwrap().append2("c");
+        let d_id = forest.get_mut(c_id).unwrap().append2("d");
+
+        forest.move_subtree(c_id, b_id).unwrap();
+        assert!(forest.get(a_id).unwrap().first_child().is_none());
+        assert_eq!(forest.get(d_id).unwrap().parent().unwrap().node_id(), c_id);
+        assert_eq!(forest.get(c_id).unwrap().parent().unwrap().node_id(), b_id);
+
+        assert_eq!(
+            forest.move_subtree(b_id, d_id),
+            Err(MoveError::WouldCreateCycle(b_id))
+        );
+
+        // moving a root merges its tree into the other one
+        forest.move_subtree(a_id, d_id).unwrap();
+        assert_eq!(forest.root_ids(), &[b_id]);
+        assert_eq!(forest.get(a_id).unwrap().parent().unwrap().node_id(), d_id);
+    }
+
+    #[test]
+    fn remove_and_find() {
+        let mut forest = Forest::new();
+        let one_id = forest.add_root(1);
+        let mut one = forest.get_mut(one_id).unwrap();
+        let two_id = one.append2(2);
+        let three_id = one.append2(3);
+        let four_id = forest.get_mut(two_id).unwrap().append2(4);
+        let five_id = forest.add_root(5);
+        forest.get_mut(five_id).unwrap().append2(2);
+
+        assert_eq!(forest.find(&2).unwrap().len(), 2);
+
+        assert_eq!(forest.remove(one_id, OrphanChildren), Some(1));
+        assert_eq!(forest.root_ids(), &[five_id, two_id, three_id]);
+        assert_eq!(forest.remove(two_id, DropChildren), Some(2));
+        assert!(forest.get(four_id).is_none());
+        assert_eq!(forest.root_ids(), &[five_id, three_id]);
+        assert_eq!(forest.remove(two_id, DropChildren), None);
+        assert_eq!(forest.find(&4), None);
+        assert_eq!(forest.find(&2).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn get_mut_keeps_every_node_in_a_tree() {
+        let mut forest = Forest::new();
+        let one_id = forest.add_root(1);
+        let mut one = forest.get_mut(one_id).unwrap();
+        let two_id = one.append2(2);
+        one.append2(5);
+        let mut two = forest.get_mut(two_id).unwrap();
+        two.append2(3);
+        two.append2(4);
+        let zero_id = forest.get_mut(one_id).unwrap().prepend2(0);
+
+        // removing a child through the handle drops its sub-tree instead of orphaning it
+        let mut two = forest.get_mut(zero_id).unwrap().next_sibling().unwrap();
+        assert_eq!(two.remove_first(), Some(3));
+        assert_eq!(forest.get_mut(one_id).unwrap().remove_last(), Some(5));
+        assert_eq!(forest.get_mut(one_id).unwrap().remove_last(), Some(2));
+        assert!(forest.find(&4).is_none());
+
+        let reachable: usize = forest
+            .iter_roots()
+            .map(|root| root.traverse_pre_order().count())
+            .sum();
+        assert_eq!(reachable, forest.tree.core_tree.len());
+        assert_eq!(forest.root_ids(), &[one_id]);
+
+        let mut zero = forest.get_mut(zero_id).unwrap();
+        *zero.data() = 10;
+        let one = zero.parent().unwrap().as_ref();
+        assert_eq!(one.first_child().unwrap().data(), &10);
+    }
+}
@@ -1,24 +1,28 @@
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::rc::Rc;
+
 use crate::node::*;
 use crate::tree::Tree;
 use crate::NodeId;
 
 // todo: document this
 
-pub struct Ancestors<'a, T> {
+pub struct Ancestors<'a, T, E = ()> {
     node_id: Option<NodeId>,
-    tree: &'a Tree<T>,
+    tree: &'a Tree<T, E>,
 }
 
-impl<'a, T> Ancestors<'a, T> {
-    pub(crate) fn new(node_id: Option<NodeId>, tree: &'a Tree<T>) -> Ancestors<T> {
+impl<'a, T, E> Ancestors<'a, T, E> {
+    pub(crate) fn new(node_id: Option<NodeId>, tree: &'a Tree<T, E>) -> Ancestors<'a, T, E> {
         Ancestors { node_id, tree }
     }
 }
 
-impl<'a, T> Iterator for Ancestors<'a, T> {
-    type Item = NodeRef<'a, T>;
+impl<'a, T, E> Iterator for Ancestors<'a, T, E> {
+    type Item = NodeRef<'a, T, E>;
 
-    fn next(&mut self) -> Option<NodeRef<'a, T>> {
+    fn next(&mut self) -> Option<NodeRef<'a, T, E>> {
         self.node_id
             .take()
             .and_then(|node_id| self.tree.get_node_relatives(node_id).parent)
@@ -30,21 +34,21 @@ impl<'a, T> Iterator for Ancestors<'a, T> {
 }
 
 // possibly re-name this, not sure how I feel about it
-pub struct NextSiblings<'a, T> {
+pub struct NextSiblings<'a, T, E = ()> {
     node_id: Option<NodeId>,
-    tree: &'a Tree<T>,
+    tree: &'a Tree<T, E>,
 }
 
-impl<'a, T> NextSiblings<'a, T> {
-    pub(crate) fn new(node_id: Option<NodeId>, tree: &'a Tree<T>) -> NextSiblings<T> {
+impl<'a, T, E> NextSiblings<'a, T, E> {
+    pub(crate) fn new(node_id: Option<NodeId>, tree: &'a Tree<T, E>) -> NextSiblings<'a, T, E> {
         NextSiblings { node_id, tree }
     }
 }
 
-impl<'a, T> Iterator for NextSiblings<'a, T> {
-    type Item = NodeRef<'a, T>;
+impl<'a, T, E> Iterator for NextSiblings<'a, T, E> {
+    type Item = NodeRef<'a, T, E>;
 
-    fn next(&mut self) -> Option<NodeRef<'a, T>> {
+    fn next(&mut self) -> Option<NodeRef<'a, T, E>> {
         self.node_id.take().map(|node_id| {
             self.node_id = self.tree.get_node_relatives(node_id).next_sibling;
             NodeRef::new(node_id, self.tree)
@@ -53,14 +57,14 @@ impl<'a, T> Iterator for NextSiblings<'a, T> {
 }
 
 /// Depth-first pre-order iterator
-pub struct PreOrder<'a, T> {
-    start: Option<NodeRef<'a, T>>,
-    children: Vec<NextSiblings<'a, T>>,
-    tree: &'a Tree<T>,
+pub struct PreOrder<'a, T, E = ()> {
+    start: Option<NodeRef<'a, T, E>>,
+    children: Vec<NextSiblings<'a, T, E>>,
+    tree: &'a Tree<T, E>,
 }
 
-impl<'a, T> PreOrder<'a, T> {
-    pub(crate) fn new(node: &NodeRef<'a, T>, tree: &'a Tree<T>) -> PreOrder<'a, T> {
+impl<'a, T, E> PreOrder<'a, T, E> {
+    pub(crate) fn new(node: &NodeRef<'a, T, E>, tree: &'a Tree<T, E>) -> PreOrder<'a, T, E> {
         let children = vec![];
         let start = tree.get(node.node_id());
         PreOrder {
@@ -71,10 +75,10 @@ impl<'a, T> PreOrder<'a, T> {
     }
 }
 
-impl<'a, T> Iterator for PreOrder<'a, T> {
-    type Item = NodeRef<'a, T>;
+impl<'a, T, E> Iterator for PreOrder<'a, T, E> {
+    type Item = NodeRef<'a, T, E>;
 
-    fn next(&mut self) -> Option<NodeRef<'a, T>> {
+    fn next(&mut self) -> Option<NodeRef<'a, T, E>> {
         if let Some(node) = self.start.take() {
             let first_child_id = node.first_child().map(|child_ref| child_ref.node_id());
             self.children
@@ -96,14 +100,134 @@ impl<'a, T> Iterator for PreOrder<'a, T> {
     }
 }
 
+/// Depth-first pre-order iterator yielding each node along with its depth relative to the start
+/// node and its index among its siblings
+pub struct PositionedPreOrder<'a, T, E = ()> {
+    start: Option<NodeRef<'a, T, E>>,
+    // for each level below the start node: the next child to visit, its depth and its index
+    stack: Vec<(Option<NodeId>, usize, usize)>,
+    tree: &'a Tree<T, E>,
+}
+
+impl<'a, T, E> PositionedPreOrder<'a, T, E> {
+    pub(crate) fn new(
+        node: &NodeRef<'a, T, E>,
+        tree: &'a Tree<T, E>,
+    ) -> PositionedPreOrder<'a, T, E> {
+        PositionedPreOrder {
+            start: tree.get(node.node_id()),
+            stack: Vec::new(),
+            tree,
+        }
+    }
+}
+
+impl<'a, T, E> Iterator for PositionedPreOrder<'a, T, E> {
+    type Item = (NodeRef<'a, T, E>, usize, usize);
+
+    fn next(&mut self) -> Option<(NodeRef<'a, T, E>, usize, usize)> {
+        if let Some(node) = self.start.take() {
+            let first_child_id = self.tree.get_node_relatives(node.node_id()).first_child;
+            self.stack.push((first_child_id, 1, 0));
+            return Some((node, 0, 0));
+        }
+
+        while let Some(level) = self.stack.last_mut() {
+            if let (Some(node_id), depth, index) = *level {
+                let relatives = self.tree.get_node_relatives(node_id);
+                *level = (relatives.next_sibling, depth, index + 1);
+                self.stack.push((relatives.first_child, depth + 1, 0));
+                return Some((NodeRef::new(node_id, self.tree), depth, index));
+            }
+            self.stack.pop();
+        }
+        None
+    }
+}
+
+/// Depth-first pre-order iterator visiting each node's children in the order given by a comparator
+pub struct PreOrderBy<'a, T, E, F> {
+    stack: Vec<NodeRef<'a, T, E>>,
+    compare: F,
+}
+
+impl<'a, T, E, F: FnMut(&T, &T) -> Ordering> PreOrderBy<'a, T, E, F> {
+    pub(crate) fn new(
+        node: &NodeRef<'a, T, E>,
+        tree: &'a Tree<T, E>,
+        compare: F,
+    ) -> PreOrderBy<'a, T, E, F> {
+        let stack = tree.get(node.node_id()).into_iter().collect();
+        PreOrderBy { stack, compare }
+    }
+}
+
+impl<'a, T, E, F: FnMut(&T, &T) -> Ordering> Iterator for PreOrderBy<'a, T, E, F> {
+    type Item = NodeRef<'a, T, E>;
+
+    fn next(&mut self) -> Option<NodeRef<'a, T, E>> {
+        let node = self.stack.pop()?;
+        // the sorted children go on the stack last first, so that the first one is popped next
+        let start = self.stack.len();
+        self.stack.extend(node.children());
+        let compare = &mut self.compare;
+        self.stack[start..].sort_by(|a, b| compare(a.data(), b.data()));
+        self.stack[start..].reverse();
+        Some(node)
+    }
+}
+
+/// Depth-first pre-order iterator yielding each node along with a context derived from its
+/// parent's context
+pub struct ContextPreOrder<'a, T, E, C, F> {
+    // each node still to visit, along with its parent's context
+    stack: Vec<(NodeRef<'a, T, E>, Rc<C>)>,
+    derive: F,
+}
+
+impl<'a, T, E, C, F: FnMut(&C, &T) -> C> ContextPreOrder<'a, T, E, C, F> {
+    pub(crate) fn new(
+        node: &NodeRef<'a, T, E>,
+        tree: &'a Tree<T, E>,
+        root_ctx: C,
+        derive: F,
+    ) -> ContextPreOrder<'a, T, E, C, F> {
+        let root_ctx = Rc::new(root_ctx);
+        let stack = tree
+            .get(node.node_id())
+            .into_iter()
+            .map(|node| (node, root_ctx.clone()))
+            .collect();
+        ContextPreOrder { stack, derive }
+    }
+}
+
+impl<'a, T, E, C: Clone, F: FnMut(&C, &T) -> C> Iterator for ContextPreOrder<'a, T, E, C, F> {
+    type Item = (NodeRef<'a, T, E>, C);
+
+    fn next(&mut self) -> Option<(NodeRef<'a, T, E>, C)> {
+        let (node, parent_ctx) = self.stack.pop()?;
+        let ctx = (self.derive)(&parent_ctx, node.data());
+        if node.first_child().is_some() {
+            // the children share one copy of this node's context
+            let shared = Rc::new(ctx.clone());
+            let start = self.stack.len();
+            self.stack
+                .extend(node.children().map(|child| (child, shared.clone())));
+            self.stack[start..].reverse();
+        }
+        Some((node, ctx))
+    }
+}
+
 /// Depth-first post-order iterator
-pub struct PostOrder<'a, T> {
-    nodes: Vec<(NodeRef<'a, T>, NextSiblings<'a, T>)>,
-    tree: &'a Tree<T>,
+pub struct PostOrder<'a, T, E = ()> {
+    nodes: Vec<(NodeRef<'a, T, E>, NextSiblings<'a, T, E>)>,
+    tree: &'a Tree<T, E>,
 }
 
-impl<'a, T> PostOrder<'a, T> {
-    pub(crate) fn new(node: &NodeRef<'a, T>, tree: &'a Tree<T>) -> PostOrder<'a, T> {
+impl<'a, T, E> PostOrder<'a, T, E> {
+    pub(crate) fn new(node: &NodeRef<'a, T, E>, tree: &'a Tree<T, E>) -> PostOrder<'a, T, E> {
         let node = tree
             .get(node.node_id())
             .expect("getting node of node ref id");
@@ -113,10 +237,10 @@ impl<'a, T> PostOrder<'a, T> {
     }
 }
 
-impl<'a, T> Iterator for PostOrder<'a, T> {
-    type Item = NodeRef<'a, T>;
+impl<'a, T, E> Iterator for PostOrder<'a, T, E> {
+    type Item = NodeRef<'a, T, E>;
 
-    fn next(&mut self) -> Option<NodeRef<'a, T>> {
+    fn next(&mut self) -> Option<NodeRef<'a, T, E>> {
         if let Some((node, mut children)) = self.nodes.pop() {
             if let Some(next) = children.next() {
                 self.nodes.push((node, children));
@@ -142,14 +266,14 @@ impl<'a, T> Iterator for PostOrder<'a, T> {
 }
 
 /// Depth-first level-order iterator
-pub struct LevelOrder<'a, T> {
-    start: NodeRef<'a, T>,
-    levels: Vec<(NodeId, NextSiblings<'a, T>)>,
-    tree: &'a Tree<T>,
+pub struct LevelOrder<'a, T, E = ()> {
+    start: NodeRef<'a, T, E>,
+    levels: Vec<(NodeId, NextSiblings<'a, T, E>)>,
+    tree: &'a Tree<T, E>,
 }
 
-impl<'a, T> LevelOrder<'a, T> {
-    pub(crate) fn new(node: &NodeRef<'a, T>, tree: &'a Tree<T>) -> LevelOrder<'a, T> {
+impl<'a, T, E> LevelOrder<'a, T, E> {
+    pub(crate) fn new(node: &NodeRef<'a, T, E>, tree: &'a Tree<T, E>) -> LevelOrder<'a, T, E> {
         let start = tree
             .get(node.node_id())
             .expect("getting node of node ref id");
@@ -162,10 +286,10 @@ impl<'a, T> LevelOrder<'a, T> {
     }
 }
 
-impl<'a, T> Iterator for LevelOrder<'a, T> {
-    type Item = NodeRef<'a, T>;
+impl<'a, T, E> Iterator for LevelOrder<'a, T, E> {
+    type Item = NodeRef<'a, T, E>;
 
-    fn next(&mut self) -> Option<NodeRef<'a, T>> {
+    fn next(&mut self) -> Option<NodeRef<'a, T, E>> {
         if self.levels.is_empty() {
             let first_child_id = self.start.first_child().map(|child| child.node_id());
             self.levels.push((
@@ -221,3 +345,35 @@ impl<'a, T> Iterator for LevelOrder<'a, T> {
         }
     }
 }
+
+/// Depth-first pre-order iterator yielding each node's id along with its path
+pub struct Paths<'a, T, E = ()> {
+    stack: Vec<(NodeRef<'a, T, E>, String)>,
+    separator: &'a str,
+}
+
+impl<'a, T: Display, E> Paths<'a, T, E> {
+    pub(crate) fn new(root: Option<NodeRef<'a, T, E>>, separator: &'a str) -> Paths<'a, T, E> {
+        let stack = root
+            .map(|root| {
+                let path = root.data().to_string();
+                vec![(root, path)]
+            })
+            .unwrap_or_default();
+        Paths { stack, separator }
+    }
+}
+
+impl<'a, T: Display, E> Iterator for Paths<'a, T, E> {
+    type Item = (NodeId, String);
+
+    fn next(&mut self) -> Option<(NodeId, String)> {
+        let (node, path) = self.stack.pop()?;
+        let children: Vec<NodeRef<'a, T, E>> = node.children().collect();
+        for child in children.into_iter().rev() {
+            let child_path = format!("{}{}{}", path, self.separator, child.data());
+            self.stack.push((child, child_path));
+        }
+        Some((node.node_id(), path))
+    }
+}
@@ -71,28 +71,85 @@
 
 extern crate slab as slab_tokio;
 
+pub mod array_tree;
 pub mod behaviors;
 mod core_tree;
+pub mod diff;
+pub mod forest;
 pub mod iter;
 pub mod node;
+pub mod parse;
+pub mod pool;
 mod slab;
 pub mod tree;
+pub mod vec_tree;
+pub mod view;
 
+pub use crate::array_tree::ArrayTree;
+pub use crate::behaviors::GrowthPolicy;
 pub use crate::behaviors::RemoveBehavior;
+pub use crate::behaviors::Walk;
+pub use crate::diff::tree_diff;
+pub use crate::diff::TreeEdit;
+pub use crate::forest::Forest;
+pub use crate::forest::ForestNodeMut;
 pub use crate::iter::Ancestors;
 pub use crate::iter::NextSiblings;
+pub use crate::node::NavError;
 pub use crate::node::NodeMut;
 pub use crate::node::NodeRef;
+pub use crate::parse::ParseError;
+pub use crate::pool::TreePool;
+pub use crate::tree::BuildError;
+pub use crate::tree::MoveError;
+pub use crate::tree::StackBuilder;
+pub use crate::tree::SwapError;
 pub use crate::tree::Tree;
 pub use crate::tree::TreeBuilder;
+pub use crate::tree::TreeStats;
+pub use crate::vec_tree::VecTree;
+pub use crate::view::TreeView;
 use snowflake::ProcessUniqueId;
 
 ///
 /// An identifier used to differentiate between Nodes and tie
 /// them to a specific tree.
 ///
-#[derive(Copy, Clone, PartialEq, PartialOrd, Eq, Ord, Debug, Hash)]
+/// With the `experimental` feature, a `NodeId` also remembers the compaction generation of its
+/// `Tree` at the time it was handed out (see `Tree::is_current`).  That snapshot takes no part
+/// in comparing or hashing `NodeId`s.
+///
+#[derive(Copy, Clone, Debug)]
 pub struct NodeId {
     tree_id: ProcessUniqueId,
     index: slab::Index,
+    #[cfg(feature = "experimental")]
+    compaction: u64,
+}
+
+impl PartialEq for NodeId {
+    fn eq(&self, other: &Self) -> bool {
+        self.tree_id == other.tree_id && self.index == other.index
+    }
+}
+
+impl Eq for NodeId {}
+
+impl PartialOrd for NodeId {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NodeId {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.tree_id, self.index).cmp(&(other.tree_id, other.index))
+    }
+}
+
+impl std::hash::Hash for NodeId {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.tree_id.hash(state);
+        self.index.hash(state);
+    }
 }
@@ -1,11 +1,42 @@
 mod node_mut;
 mod node_ref;
+mod subtree;
 
 pub use self::node_mut::NodeMut;
 pub use self::node_ref::NodeRef;
+pub use self::subtree::Subtree;
+
+use std::fmt::Display;
 
 use crate::NodeId;
 
+///
+/// The error returned when moving from a `Node` to one of its relatives fails.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NavError {
+    ///
+    /// The `Node` has no parent: it is the root (or an orphan).
+    ///
+    IsRoot,
+
+    ///
+    /// The `Node` the handle points to isn't in the `Tree` anymore.
+    ///
+    Dangling(NodeId),
+}
+
+impl Display for NavError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NavError::IsRoot => write!(f, "the node has no parent"),
+            NavError::Dangling(node_id) => write!(f, "node {:?} no longer exists", node_id),
+        }
+    }
+}
+
+impl std::error::Error for NavError {}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub(crate) struct Relatives {
     pub(crate) parent: Option<NodeId>,
@@ -16,15 +47,18 @@ pub(crate) struct Relatives {
 }
 
 #[derive(Debug, PartialEq)]
-pub(crate) struct Node<T> {
+pub(crate) struct Node<T, E = ()> {
     pub(crate) data: T,
+    // the data of the edge from this node to its parent, if it was given one
+    pub(crate) edge: Option<E>,
     pub(crate) relatives: Relatives,
 }
 
-impl<T> Node<T> {
-    pub(crate) fn new(data: T) -> Node<T> {
+impl<T, E> Node<T, E> {
+    pub(crate) fn new(data: T) -> Node<T, E> {
         Node {
             data,
+            edge: None,
             relatives: Relatives {
                 parent: None,
                 prev_sibling: None,
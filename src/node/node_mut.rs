@@ -1,4 +1,5 @@
 use crate::behaviors::RemoveBehavior;
+use crate::node::NavError;
 use crate::node::Node;
 use crate::node::NodeRef;
 use crate::tree::Tree;
@@ -8,13 +9,13 @@ use crate::NodeId;
 /// A mutable reference to a given `Node`'s data and its relatives.
 ///
 #[derive(Debug)]
-pub struct NodeMut<'a, T> {
+pub struct NodeMut<'a, T, E = ()> {
     node_id: NodeId,
-    tree: &'a mut Tree<T>,
+    tree: &'a mut Tree<T, E>,
 }
 
-impl<'a, T> NodeMut<'a, T> {
-    pub(crate) fn new(node_id: NodeId, tree: &mut Tree<T>) -> NodeMut<T> {
+impl<'a, T, E> NodeMut<'a, T, E> {
+    pub(crate) fn new(node_id: NodeId, tree: &mut Tree<T, E>) -> NodeMut<'_, T, E> {
         NodeMut { node_id, tree }
     }
 
@@ -56,6 +57,7 @@ impl<'a, T> NodeMut<'a, T> {
     /// ```
     ///
     pub fn data(&mut self) -> &mut T {
+        self.tree.core_tree.data_version += 1;
         if let Some(node) = self.tree.get_node_mut(self.node_id) {
             &mut node.data
         } else {
@@ -63,6 +65,39 @@ impl<'a, T> NodeMut<'a, T> {
         }
     }
 
+    ///
+    /// Returns a mutable reference to the data contained by the given `Node`, like `data`, and
+    /// marks this `Node` and all of its ancestors dirty.  The dirty `Node`s can be listed with
+    /// `Tree::dirty_nodes` and unmarked with `Tree::clear_dirty`.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let two_id = root.append2(2);
+    /// let three_id = root.append2(3);
+    ///
+    /// *tree.get_mut(three_id).unwrap().data_mut_tracked() = 4;
+    ///
+    /// let mut dirty = tree.dirty_nodes();
+    /// dirty.sort();
+    /// let mut expected = vec![root_id, three_id];
+    /// expected.sort();
+    /// assert_eq!(dirty, expected);
+    /// ```
+    ///
+    pub fn data_mut_tracked(&mut self) -> &mut T {
+        let mut current = Some(self.node_id);
+        while let Some(node_id) = current {
+            let node_id = self.tree.core_tree.stamp(node_id);
+            self.tree.core_tree.dirty.insert(node_id);
+            current = self.tree.get_node_relatives(node_id).parent;
+        }
+        self.data()
+    }
+
     ///
     /// Returns a `NodeMut` pointing to this `Node`'s parent.  Returns a `Some`-value containing
     /// the `NodeMut` if this `Node` has a parent; otherwise returns a `None`.
@@ -76,13 +111,46 @@ impl<'a, T> NodeMut<'a, T> {
     /// assert!(root.parent().is_none());
     /// ```
     ///
-    pub fn parent(self) -> Option<NodeMut<'a, T>> {
+    pub fn parent(self) -> Option<NodeMut<'a, T, E>> {
         self.get_self_as_node()
             .relatives
             .parent
             .map(move |id| NodeMut::new(id, self.tree))
     }
 
+    ///
+    /// Returns a `NodeMut` pointing to this `Node`'s parent, like `parent`, but borrows this
+    /// `NodeMut` instead of consuming it and tells apart the ways it can fail: a
+    /// `NavError::IsRoot` if this `Node` has no parent, and a `NavError::Dangling` if this
+    /// `Node` isn't in the `Tree` anymore.  The latter can't happen through the public API,
+    /// since a `NodeMut` holds the only borrow of its `Tree`, but it is reported rather than
+    /// treated as unreachable.
+    ///
+    /// ```
+    /// use nary_tree::node::NavError;
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let two_id = root.append2(2);
+    /// assert_eq!(root.try_parent().err(), Some(NavError::IsRoot));
+    ///
+    /// let mut two = tree.get_mut(two_id).unwrap();
+    /// *two.try_parent().unwrap().data() += 10;
+    /// assert_eq!(tree.root().unwrap().data(), &11);
+    /// ```
+    ///
+    pub fn try_parent(&mut self) -> Result<NodeMut<'_, T, E>, NavError> {
+        let parent_id = self
+            .tree
+            .get_node(self.node_id)
+            .ok_or(NavError::Dangling(self.node_id))?
+            .relatives
+            .parent
+            .ok_or(NavError::IsRoot)?;
+        Ok(NodeMut::new(parent_id, self.tree))
+    }
+
     ///
     /// Returns a `NodeMut` pointing to this `Node`'s previous sibling.  Returns a `Some`-value
     /// containing the `NodeMut` if this `Node` has a previous sibling; otherwise returns a `None`.
@@ -96,7 +164,7 @@ impl<'a, T> NodeMut<'a, T> {
     /// assert!(root.prev_sibling().is_none());
     /// ```
     ///
-    pub fn prev_sibling(self) -> Option<NodeMut<'a, T>> {
+    pub fn prev_sibling(self) -> Option<NodeMut<'a, T, E>> {
         self.get_self_as_node()
             .relatives
             .prev_sibling
@@ -116,7 +184,7 @@ impl<'a, T> NodeMut<'a, T> {
     /// assert!(root.next_sibling().is_none());
     /// ```
     ///
-    pub fn next_sibling(self) -> Option<NodeMut<'a, T>> {
+    pub fn next_sibling(self) -> Option<NodeMut<'a, T, E>> {
         self.get_self_as_node()
             .relatives
             .next_sibling
@@ -136,7 +204,7 @@ impl<'a, T> NodeMut<'a, T> {
     /// assert!(root.first_child().is_none());
     /// ```
     ///
-    pub fn first_child(self) -> Option<NodeMut<'a, T>> {
+    pub fn first_child(self) -> Option<NodeMut<'a, T, E>> {
         self.get_self_as_node()
             .relatives
             .first_child
@@ -156,7 +224,7 @@ impl<'a, T> NodeMut<'a, T> {
     /// assert!(root.last_child().is_none());
     /// ```
     ///
-    pub fn last_child(self) -> Option<NodeMut<'a, T>> {
+    pub fn last_child(self) -> Option<NodeMut<'a, T, E>> {
         self.get_self_as_node()
             .relatives
             .last_child
@@ -208,7 +276,7 @@ impl<'a, T> NodeMut<'a, T> {
     /// ");
     /// ```
     ///
-    pub fn append(self, data: T) -> NodeMut<'a, T> {
+    pub fn append(self, data: T) -> NodeMut<'a, T, E> {
         let new_id = self.tree.core_tree.insert(data);
 
         let relatives = self.tree.get_node_relatives(self.node_id);
@@ -294,6 +362,146 @@ impl<'a, T> NodeMut<'a, T> {
         new_id
     }
 
+    ///
+    /// Appends a new `Node` for each element of `data` as this `Node`'s last children, in order.
+    /// Room for all of them is reserved up front, so the `Tree` grows at most once.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// root.append2(1);
+    /// root.append_slice(&[2, 3, 4]);
+    ///
+    /// let values = tree.root().unwrap().children()
+    ///     .map(|child| *child.data()).collect::<Vec<i32>>();
+    /// assert_eq!(values, vec![1, 2, 3, 4]);
+    /// ```
+    ///
+    pub fn append_slice(&mut self, data: &[T])
+    where
+        T: Copy,
+    {
+        self.tree.core_tree.reserve(data.len());
+        for &data in data {
+            self.append2(data);
+        }
+    }
+
+    ///
+    /// Appends a new `Node` for each item of `iter` as this `Node`'s last children, in order, and
+    /// returns their `NodeId`s in the same order.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("menu").build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let ids = root.append_iter_ids(vec!["open", "save", "quit"]);
+    ///
+    /// assert_eq!(ids.len(), 3);
+    /// assert_eq!(tree.get(ids[1]).unwrap().data(), &"save");
+    /// ```
+    ///
+    pub fn append_iter_ids<I: IntoIterator<Item = T>>(&mut self, iter: I) -> Vec<NodeId> {
+        let iter = iter.into_iter();
+        self.tree.core_tree.reserve(iter.size_hint().0);
+        iter.map(|data| self.append2(data)).collect()
+    }
+
+    ///
+    /// Appends a new `Node` holding `data` as this `Node`'s last child, with `edge` as the data of
+    /// the edge between them, and returns its `NodeId`.  The edge data stays with the new `Node`
+    /// and can be read back with `NodeRef::parent_edge`.
+    ///
+    /// ```
+    /// use nary_tree::tree::{Tree, TreeBuilder};
+    ///
+    /// let mut tree: Tree<&str, &str> = TreeBuilder::new().with_root("ceo").build_with_edges();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let cto_id = root.append_with_edge("cto", "reports to");
+    /// let advisor_id = root.append_with_edge("advisor", "advises");
+    ///
+    /// assert_eq!(tree.get(cto_id).unwrap().parent_edge(), Some(&"reports to"));
+    /// assert_eq!(tree.get(advisor_id).unwrap().parent_edge(), Some(&"advises"));
+    /// ```
+    ///
+    pub fn append_with_edge(&mut self, data: T, edge: E) -> NodeId {
+        let new_id = self.append2(data);
+        if let Some(node) = self.tree.get_node_mut(new_id) {
+            node.edge = Some(edge);
+        }
+        new_id
+    }
+    ///
+    /// Appends a new `Node` holding `data` as this `Node`'s last child, gives it the stable key
+    /// `key` (see `Tree::set_key`) and returns its `NodeId`.
+    ///
+    /// Returns a `None`-value, without appending anything, if another `Node` already has `key`.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    ///
+    /// let two_id = root.append_with_key(2, 20).unwrap();
+    /// assert!(root.append_with_key(3, 20).is_none());
+    ///
+    /// assert_eq!(tree.find_by_key(20), Some(two_id));
+    /// assert_eq!(tree.root().unwrap().children().count(), 1);
+    /// ```
+    ///
+    pub fn append_with_key(&mut self, data: T, key: u64) -> Option<NodeId> {
+        if self.tree.find_by_key(key).is_some() {
+            return None;
+        }
+        let new_id = self.append2(data);
+        self.tree.set_key(new_id, key);
+        Some(new_id)
+    }
+
+    ///
+    /// Moves all the `Node`s of `subtree` into this `Node`'s `Tree`, grafting its root as this
+    /// `Node`'s last child.  Returns a `NodeMut` pointing to the grafted root, so that building
+    /// can go on from there.
+    ///
+    /// The grafted `Node`s get new `NodeId`s; the ones of `subtree` are not valid in this
+    /// `Tree`.  If `subtree` is empty nothing is grafted and a `NodeMut` pointing to this `Node`
+    /// is returned.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut fragment = TreeBuilder::new().with_root("b").build();
+    /// fragment.root_mut().unwrap().append2("c");
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("a").build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// root.append_subtree(fragment).append("d");
+    ///
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// a
+    /// └── b
+    ///     ├── c
+    ///     └── d
+    /// ");
+    /// ```
+    ///
+    pub fn append_subtree(&mut self, subtree: Tree<T, E>) -> NodeMut<'_, T, E> {
+        match self.tree.adopt(subtree) {
+            Some(grafted_id) => {
+                let last_child = self.tree.get_node_relatives(self.node_id).last_child;
+                self.tree.link_child(grafted_id, self.node_id, last_child);
+                NodeMut::new(grafted_id, self.tree)
+            }
+            None => NodeMut::new(self.node_id, self.tree),
+        }
+    }
+
     ///
     /// Prepends a new `Node` as this `Node`'s first child (and last child if it has none).
     /// Returns a `NodeMut` pointing to the newly added `Node`.
@@ -324,7 +532,7 @@ impl<'a, T> NodeMut<'a, T> {
     /// assert_eq!(parent.as_mut().unwrap().data(), &mut 1);
     /// ```
     ///
-    pub fn prepend(self, data: T) -> NodeMut<'a, T> {
+    pub fn prepend(self, data: T) -> NodeMut<'a, T, E> {
         let new_id = self.tree.core_tree.insert(data);
 
         let relatives = self.tree.get_node_relatives(self.node_id);
@@ -475,6 +683,159 @@ impl<'a, T> NodeMut<'a, T> {
         self.tree.remove(last_id, behavior)
     }
 
+    ///
+    /// Keeps the first `len` children of this `Node` (along with their descendants) and removes
+    /// the rest, dropping their sub-trees.  Does nothing if this `Node` has `len` children or
+    /// fewer.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// root.append2(2);
+    /// root.append2(3);
+    /// root.append2(4);
+    ///
+    /// root.truncate_children(1);
+    ///
+    /// let root = root.as_ref();
+    /// assert_eq!(root.first_child().unwrap().data(), &2);
+    /// assert_eq!(root.last_child().unwrap().data(), &2);
+    /// ```
+    ///
+    pub fn truncate_children(&mut self, len: usize) {
+        let removed_ids: Vec<NodeId> = NodeRef::new(self.node_id, self.tree)
+            .children()
+            .skip(len)
+            .map(|child| child.node_id())
+            .collect();
+        for id in removed_ids.into_iter().rev() {
+            self.tree.remove(id, RemoveBehavior::DropChildren);
+        }
+    }
+
+    ///
+    /// Moves the child at position `from` among this `Node`'s children to position `to`,
+    /// shifting the children in between.  Positions past the last child are clamped to the last
+    /// child.  Does nothing if both positions are the same or if this `Node` has no children.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// root.append2(2);
+    /// root.append2(3);
+    /// root.append2(4);
+    ///
+    /// root.move_child(0, 2);
+    /// assert_eq!(
+    ///     tree.root().unwrap().children().map(|child_ref| *child_ref.data())
+    ///         .collect::<Vec<i32>>(),
+    ///     vec![3, 4, 2]);
+    ///
+    /// tree.root_mut().unwrap().move_child(10, 0);
+    /// assert_eq!(
+    ///     tree.root().unwrap().children().map(|child_ref| *child_ref.data())
+    ///         .collect::<Vec<i32>>(),
+    ///     vec![2, 3, 4]);
+    /// ```
+    ///
+    pub fn move_child(&mut self, from: usize, to: usize) {
+        let child_ids = NodeRef::new(self.node_id, self.tree).children_ids();
+        let last = match child_ids.len().checked_sub(1) {
+            Some(last) => last,
+            None => return,
+        };
+        let (from, to) = (from.min(last), to.min(last));
+        if from == to {
+            return;
+        }
+
+        // the new previous sibling, as seen once the moved child is out of the way
+        let prev_sibling = match to {
+            0 => None,
+            _ if to > from => Some(child_ids[to]),
+            _ => Some(child_ids[to - 1]),
+        };
+        let moved_id = child_ids[from];
+        self.tree.unlink(moved_id);
+        self.tree.link_child(moved_id, self.node_id, prev_sibling);
+    }
+
+    ///
+    /// Reorders this `Node`'s children so that the ones whose data matches `pred` come first,
+    /// keeping the relative order within each group, and returns the number of matching
+    /// children (the position of the first child that doesn't match).
+    ///
+    /// Only sibling links are rewired: no data is moved and every `NodeId` stays valid.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// for i in 1..=6 {
+    ///     root.append2(i);
+    /// }
+    ///
+    /// assert_eq!(root.partition_children(|data| data % 3 == 0), 2);
+    /// assert_eq!(
+    ///     tree.root().unwrap().children().map(|child_ref| *child_ref.data())
+    ///         .collect::<Vec<i32>>(),
+    ///     vec![3, 6, 1, 2, 4, 5]);
+    /// ```
+    ///
+    pub fn partition_children<F: FnMut(&T) -> bool>(&mut self, mut pred: F) -> usize {
+        let (mut children, rest): (Vec<_>, Vec<_>) = NodeRef::new(self.node_id, self.tree)
+            .children()
+            .partition(|child| pred(child.data()));
+        let partition_point = children.len();
+        children.extend(rest);
+
+        let child_ids: Vec<NodeId> = children.iter().map(|child| child.node_id()).collect();
+        self.tree.link_children(self.node_id, &child_ids);
+        partition_point
+    }
+
+    ///
+    /// Returns mutable references to the data of all of this `Node`'s children at once, in
+    /// order.  The children can then be read and written as a group, e.g. to share something out
+    /// between siblings based on all of their values.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0.0).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// root.append2(1.0);
+    /// root.append2(3.0);
+    ///
+    /// // scale the children's sizes so that they add up to 100
+    /// let mut sizes = root.children_slice_mut();
+    /// let total: f64 = sizes.iter().map(|size| **size).sum();
+    /// for size in sizes.iter_mut() {
+    ///     **size *= 100.0 / total;
+    /// }
+    ///
+    /// assert_eq!(
+    ///     tree.root().unwrap().children().map(|child_ref| *child_ref.data())
+    ///         .collect::<Vec<f64>>(),
+    ///     vec![25.0, 75.0]);
+    /// ```
+    ///
+    pub fn children_slice_mut(&mut self) -> Vec<&mut T> {
+        let child_ids = NodeRef::new(self.node_id, self.tree).children_ids();
+        self.tree
+            .core_tree
+            .get_many_mut(&child_ids)
+            .expect("children must exist")
+            .into_iter()
+            .map(|node| &mut node.data)
+            .collect()
+    }
+
     ///
     /// Returns a `NodeRef` pointing to this `NodeMut`.
     ///
@@ -490,7 +851,7 @@ impl<'a, T> NodeMut<'a, T> {
     /// assert_eq!(root.data(), &1);
     /// ```
     ///
-    pub fn as_ref(self) -> NodeRef<'a, T> {
+    pub fn as_ref(self) -> NodeRef<'a, T, E> {
         NodeRef::new(self.node_id, self.tree)
     }
 
@@ -865,7 +1226,7 @@ impl<'a, T> NodeMut<'a, T> {
         }
     }
 
-    fn get_self_as_node(&self) -> &Node<T> {
+    fn get_self_as_node(&self) -> &Node<T, E> {
         if let Some(node) = self.tree.get_node(self.node_id) {
             node
         } else {
@@ -878,7 +1239,8 @@ impl<'a, T> NodeMut<'a, T> {
 #[cfg(test)]
 mod node_mut_tests {
     use crate::behaviors::RemoveBehavior::{DropChildren, OrphanChildren};
-    use crate::tree::Tree;
+    use crate::node::{NavError, NodeMut};
+    use crate::tree::{Tree, TreeBuilder};
 
     #[test]
     fn node_id() {
@@ -981,6 +1343,112 @@ mod node_mut_tests {
         assert_eq!(new_node.data(), &2);
     }
 
+    #[test]
+    fn append_slice() {
+        let mut tree = TreeBuilder::new().with_root(0).with_capacity(1).build();
+        let root_id = tree.root_id().expect("root doesn't exist?");
+
+        let mut root_mut = tree.get_mut(root_id).unwrap();
+        root_mut.append_slice(&[]);
+        assert_eq!(tree.capacity(), 1);
+
+        let mut root_mut = tree.get_mut(root_id).unwrap();
+        root_mut.append2(1);
+        root_mut.append_slice(&[2, 3, 4, 5]);
+        assert_eq!(tree.capacity(), 6);
+
+        let root = tree.get(root_id).unwrap();
+        let values: Vec<i32> = root.children().map(|child| *child.data()).collect();
+        assert_eq!(values, vec![1, 2, 3, 4, 5]);
+        assert_eq!(root.last_child().unwrap().data(), &5);
+        assert!(root.children().all(|child| child.first_child().is_none()));
+    }
+
+    #[test]
+    fn append_iter_ids() {
+        let mut tree = Tree::new();
+        tree.set_root(0);
+        let root_id = tree.root_id().expect("root doesn't exist?");
+
+        let mut root_mut = tree.get_mut(root_id).unwrap();
+        assert!(root_mut.append_iter_ids(Vec::new()).is_empty());
+        let first_id = root_mut.append2(1);
+        let ids = root_mut.append_iter_ids((2..5).filter(|i| i % 2 == 0 || *i == 3));
+
+        let root = tree.get(root_id).unwrap();
+        let children: Vec<_> = root.children().map(|child| child.node_id()).collect();
+        assert_eq!(children[0], first_id);
+        assert_eq!(children[1..], ids[..]);
+        let values: Vec<i32> = ids
+            .iter()
+            .map(|id| *tree.get(*id).unwrap().data())
+            .collect();
+        assert_eq!(values, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn children_slice_mut() {
+        let mut tree = Tree::new();
+        tree.set_root(0);
+        let root_id = tree.root_id().expect("root doesn't exist?");
+
+        let mut root_mut = tree.get_mut(root_id).unwrap();
+        assert!(root_mut.children_slice_mut().is_empty());
+        let two_id = root_mut.append2(2);
+        root_mut.append2(3);
+        root_mut.append2(5);
+        tree.get_mut(two_id).unwrap().append2(7);
+
+        let data_version = tree.data_version();
+        let mut root_mut = tree.get_mut(root_id).unwrap();
+        let mut children = root_mut.children_slice_mut();
+        children.swap(0, 2);
+        let sum: i32 = children.iter().map(|data| **data).sum();
+        for data in children {
+            *data = sum - *data;
+        }
+        assert_ne!(tree.data_version(), data_version);
+
+        let values: Vec<i32> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| *node.data())
+            .collect();
+        assert_eq!(values, vec![0, 8, 7, 7, 5]);
+    }
+
+    #[test]
+    fn try_parent() {
+        let mut tree = Tree::new();
+        let root_id = tree.set_root(1);
+        let two_id = tree.root_mut().unwrap().append2(2);
+        let three_id = tree.get_mut(two_id).unwrap().append2(3);
+
+        let mut three = tree.get_mut(three_id).unwrap();
+        let mut two = three.try_parent().unwrap();
+        assert_eq!(two.node_id(), two_id);
+        assert_eq!(two.try_parent().unwrap().node_id(), root_id);
+        // the handle is only borrowed, so it can be used again
+        assert_eq!(three.data(), &mut 3);
+
+        assert_eq!(
+            tree.root_mut().unwrap().try_parent().err(),
+            Some(NavError::IsRoot)
+        );
+
+        tree.remove(two_id, OrphanChildren);
+        let mut three = tree.get_mut(three_id).unwrap();
+        assert_eq!(three.try_parent().err(), Some(NavError::IsRoot));
+
+        tree.remove(three_id, DropChildren);
+        let mut dangling = NodeMut::new(three_id, &mut tree);
+        assert_eq!(
+            dangling.try_parent().err(),
+            Some(NavError::Dangling(three_id))
+        );
+    }
+
     #[test]
     fn append_single_child_present() {
         let mut tree = Tree::new();
@@ -1412,6 +1880,166 @@ mod node_mut_tests {
         assert_eq!(three.relatives.parent, None);
     }
 
+    #[test]
+    fn append_subtree() {
+        let mut subtree = Tree::new();
+        let two_id = subtree.set_root(2);
+        let mut two = subtree.get_mut(two_id).unwrap();
+        let three_id = two.append2(3);
+        two.append2(5);
+        subtree.get_mut(three_id).unwrap().append2(4);
+        let orphan_id = subtree.get_mut(two_id).unwrap().append2(9);
+        subtree.remove(orphan_id, OrphanChildren);
+
+        let mut tree = Tree::new();
+        let root_id = tree.set_root(1);
+        let mut root_mut = tree.get_mut(root_id).unwrap();
+        root_mut.append2(0);
+        let grafted_id = root_mut.append_subtree(subtree).node_id();
+        assert!(tree.get(two_id).is_none());
+
+        let root = tree.get(root_id).unwrap();
+        assert_eq!(root.last_child().unwrap().node_id(), grafted_id);
+        let values: Vec<i32> = root.traverse_pre_order().map(|n| *n.data()).collect();
+        assert_eq!(values, vec![1, 0, 2, 3, 4, 5]);
+        let grafted = tree.get(grafted_id).unwrap();
+        assert_eq!(grafted.parent().unwrap().node_id(), root_id);
+        assert_eq!(grafted.prev_sibling().unwrap().data(), &0);
+        assert_eq!(grafted.last_child().unwrap().data(), &5);
+
+        let mut root_mut = tree.get_mut(root_id).unwrap();
+        let same = root_mut.append_subtree(Tree::new()).node_id();
+        assert_eq!(same, root_id);
+        assert_eq!(tree.get(root_id).unwrap().children_ids().len(), 2);
+    }
+
+    #[test]
+    fn partition_children() {
+        let mut tree = Tree::new();
+        tree.set_root(0);
+        let root_id = tree.root_id().expect("root doesn't exist?");
+
+        let mut root_mut = tree.get_mut(root_id).unwrap();
+        let ids: Vec<_> = (1..=5).map(|i| root_mut.append2(i)).collect();
+
+        assert_eq!(root_mut.partition_children(|data| data % 2 == 0), 2);
+
+        // 0 -> [2, 4, 1, 3, 5]
+        let expected = [ids[1], ids[3], ids[0], ids[2], ids[4]];
+        let root = tree.get(root_id).unwrap();
+        assert_eq!(root.children_ids(), expected);
+        assert_eq!(root.last_child().unwrap().node_id(), ids[4]);
+        for (position, node_id) in expected.iter().enumerate() {
+            let node = tree.get_node(*node_id).unwrap();
+            assert_eq!(node.relatives.parent, Some(root_id));
+            assert_eq!(
+                node.relatives.prev_sibling,
+                position.checked_sub(1).map(|prev| expected[prev])
+            );
+            assert_eq!(
+                node.relatives.next_sibling,
+                expected.get(position + 1).copied()
+            );
+        }
+
+        let mut root_mut = tree.get_mut(root_id).unwrap();
+        assert_eq!(root_mut.partition_children(|_| false), 0);
+        assert_eq!(root_mut.partition_children(|_| true), 5);
+        assert_eq!(tree.get(root_id).unwrap().children_ids(), expected);
+
+        let mut leaf = tree.get_mut(ids[0]).unwrap();
+        assert_eq!(leaf.partition_children(|_| true), 0);
+        assert!(leaf.as_ref().first_child().is_none());
+    }
+
+    #[test]
+    fn move_child() {
+        let mut tree = Tree::new();
+        tree.set_root(1);
+        let root_id = tree.root_id().expect("root doesn't exist?");
+
+        let mut root_mut = tree.get_mut(root_id).unwrap();
+        let two_id = root_mut.append2(2);
+        let three_id = root_mut.append2(3);
+        let four_id = root_mut.append2(4);
+
+        root_mut.move_child(1, 1);
+        root_mut.move_child(2, 1);
+
+        // 1 -> [2, 4, 3]
+        let root_node = tree.get_node(root_id).unwrap();
+        assert_eq!(root_node.relatives.first_child, Some(two_id));
+        assert_eq!(root_node.relatives.last_child, Some(three_id));
+
+        let two = tree.get_node(two_id).unwrap();
+        assert_eq!(two.relatives.prev_sibling, None);
+        assert_eq!(two.relatives.next_sibling, Some(four_id));
+
+        let four = tree.get_node(four_id).unwrap();
+        assert_eq!(four.relatives.parent, Some(root_id));
+        assert_eq!(four.relatives.prev_sibling, Some(two_id));
+        assert_eq!(four.relatives.next_sibling, Some(three_id));
+
+        let three = tree.get_node(three_id).unwrap();
+        assert_eq!(three.relatives.prev_sibling, Some(four_id));
+        assert_eq!(three.relatives.next_sibling, None);
+
+        tree.get_mut(root_id).unwrap().move_child(0, 7);
+
+        // 1 -> [4, 3, 2]
+        let root_node = tree.get_node(root_id).unwrap();
+        assert_eq!(root_node.relatives.first_child, Some(four_id));
+        assert_eq!(root_node.relatives.last_child, Some(two_id));
+
+        let two = tree.get_node(two_id).unwrap();
+        assert_eq!(two.relatives.prev_sibling, Some(three_id));
+        assert_eq!(two.relatives.next_sibling, None);
+
+        let four = tree.get_node(four_id).unwrap();
+        assert_eq!(four.relatives.prev_sibling, None);
+
+        let mut two_mut = tree.get_mut(two_id).unwrap();
+        two_mut.move_child(0, 1);
+        assert!(two_mut.first_child().is_none());
+    }
+
+    #[test]
+    fn truncate_children() {
+        let mut tree = Tree::new();
+        tree.set_root(1);
+        let root_id = tree.root_id().expect("root doesn't exist?");
+
+        let mut root_mut = tree.get_mut(root_id).unwrap();
+        let two_id = root_mut.append2(2);
+        let three_id = root_mut.append2(3);
+        let four_id = root_mut.append2(4);
+        let five_id = tree.get_mut(four_id).unwrap().append2(5);
+
+        let mut root_mut = tree.get_mut(root_id).unwrap();
+        root_mut.truncate_children(3);
+        root_mut.truncate_children(5);
+        assert!(tree.get_node(four_id).is_some());
+
+        tree.get_mut(root_id).unwrap().truncate_children(2);
+
+        let root_node = tree.get_node(root_id).unwrap();
+        assert_eq!(root_node.relatives.first_child, Some(two_id));
+        assert_eq!(root_node.relatives.last_child, Some(three_id));
+
+        let three = tree.get_node(three_id).unwrap();
+        assert_eq!(three.relatives.next_sibling, None);
+
+        assert!(tree.get_node(four_id).is_none());
+        assert!(tree.get_node(five_id).is_none());
+
+        tree.get_mut(root_id).unwrap().truncate_children(0);
+
+        let root_node = tree.get_node(root_id).unwrap();
+        assert_eq!(root_node.relatives.first_child, None);
+        assert_eq!(root_node.relatives.last_child, None);
+        assert!(tree.get_node(two_id).is_none());
+    }
+
     #[test]
     fn remove_last_no_children_present() {
         let mut tree = Tree::new();
@@ -1,22 +1,31 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::behaviors::Walk;
 use crate::iter::Ancestors;
+use crate::iter::ContextPreOrder;
 use crate::iter::LevelOrder;
 use crate::iter::NextSiblings;
+use crate::iter::PositionedPreOrder;
 use crate::iter::PostOrder;
 use crate::iter::PreOrder;
+use crate::iter::PreOrderBy;
 use crate::node::Node;
+use crate::node::Subtree;
 use crate::tree::Tree;
 use crate::NodeId;
 
 ///
 /// An immutable reference to a given `Node`'s data and its relatives.
 ///
-pub struct NodeRef<'a, T> {
+pub struct NodeRef<'a, T, E = ()> {
     node_id: NodeId,
-    tree: &'a Tree<T>,
+    tree: &'a Tree<T, E>,
 }
 
-impl<'a, T> NodeRef<'a, T> {
-    pub(crate) fn new(node_id: NodeId, tree: &'a Tree<T>) -> NodeRef<T> {
+impl<'a, T, E> NodeRef<'a, T, E> {
+    pub(crate) fn new(node_id: NodeId, tree: &'a Tree<T, E>) -> NodeRef<'a, T, E> {
         NodeRef { node_id, tree }
     }
 
@@ -74,7 +83,7 @@ impl<'a, T> NodeRef<'a, T> {
     /// assert!(root.parent().is_none());
     /// ```
     ///
-    pub fn parent(&self) -> Option<NodeRef<T>> {
+    pub fn parent(&self) -> Option<NodeRef<'_, T, E>> {
         self.get_self_as_node()
             .relatives
             .parent
@@ -95,7 +104,7 @@ impl<'a, T> NodeRef<'a, T> {
     /// assert!(root.prev_sibling().is_none());
     /// ```
     ///
-    pub fn prev_sibling(&self) -> Option<NodeRef<T>> {
+    pub fn prev_sibling(&self) -> Option<NodeRef<'_, T, E>> {
         self.get_self_as_node()
             .relatives
             .prev_sibling
@@ -116,13 +125,83 @@ impl<'a, T> NodeRef<'a, T> {
     /// assert!(root.next_sibling().is_none());
     /// ```
     ///
-    pub fn next_sibling(&self) -> Option<NodeRef<T>> {
+    pub fn next_sibling(&self) -> Option<NodeRef<'_, T, E>> {
         self.get_self_as_node()
             .relatives
             .next_sibling
             .map(|id| NodeRef::new(id, self.tree))
     }
 
+    ///
+    /// Returns `NodeRef`s pointing to this `Node`'s previous and next siblings, in that order.
+    /// Each one is a `None`-value if this `Node` has no sibling on that side.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// root.append2(2);
+    /// let three_id = root.append2(3);
+    ///
+    /// let three = tree.get(three_id).unwrap();
+    /// let (prev, next) = three.neighbors();
+    /// assert_eq!(prev.unwrap().data(), &2);
+    /// assert!(next.is_none());
+    /// ```
+    ///
+    pub fn neighbors(&self) -> (Option<Self>, Option<Self>) {
+        let relatives = self.get_self_as_node().relatives;
+        (
+            relatives.prev_sibling.map(|id| NodeRef::new(id, self.tree)),
+            relatives.next_sibling.map(|id| NodeRef::new(id, self.tree)),
+        )
+    }
+
+    ///
+    /// Returns a `Subtree` view over this `Node` and all of its descendants.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().unwrap().append2(2);
+    ///
+    /// let root = tree.root().unwrap();
+    /// let subtree = root.subtree();
+    /// assert_eq!(subtree.len(), 2);
+    /// assert!(subtree.contains(two_id));
+    /// ```
+    ///
+    pub fn subtree(&self) -> Subtree<'a, T, E> {
+        Subtree::new(self.node_id, self.tree)
+    }
+
+    ///
+    /// Returns the data of the edge between this `Node` and its parent, if the `Node` was given
+    /// one (see `NodeMut::append_with_edge`).  Returns a `None`-value for a `Node` without a
+    /// parent.
+    ///
+    /// A `Node` keeps its edge data when it is moved under another parent.
+    ///
+    /// ```
+    /// use nary_tree::tree::{Tree, TreeBuilder};
+    ///
+    /// let mut tree: Tree<&str, &str> = TreeBuilder::new().with_root("ceo").build_with_edges();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let cto_id = root.append_with_edge("cto", "reports to");
+    /// let intern_id = root.append2("intern");
+    ///
+    /// assert_eq!(tree.get(cto_id).unwrap().parent_edge(), Some(&"reports to"));
+    /// assert!(tree.get(intern_id).unwrap().parent_edge().is_none());
+    /// assert!(tree.root().unwrap().parent_edge().is_none());
+    /// ```
+    ///
+    pub fn parent_edge(&self) -> Option<&'a E> {
+        let node = self.tree.get_node(self.node_id)?;
+        node.relatives.parent.and(node.edge.as_ref())
+    }
+
     ///
     /// Returns a `NodeRef` pointing to this `Node`'s first child.  Returns a `Some`-value
     /// containing the `NodeRef` if this `Node` has a first child; otherwise returns a `None`.
@@ -137,7 +216,7 @@ impl<'a, T> NodeRef<'a, T> {
     /// assert!(root.first_child().is_none());
     /// ```
     ///
-    pub fn first_child(&self) -> Option<NodeRef<T>> {
+    pub fn first_child(&self) -> Option<NodeRef<'_, T, E>> {
         self.get_self_as_node()
             .relatives
             .first_child
@@ -158,13 +237,219 @@ impl<'a, T> NodeRef<'a, T> {
     /// assert!(root.last_child().is_none());
     /// ```
     ///
-    pub fn last_child(&self) -> Option<NodeRef<T>> {
+    pub fn last_child(&self) -> Option<NodeRef<'_, T, E>> {
         self.get_self_as_node()
             .relatives
             .last_child
             .map(|id| NodeRef::new(id, self.tree))
     }
 
+    ///
+    /// Returns a reference to the data of this `Node`'s first child, or `None` if it has no
+    /// children.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// assert_eq!(tree.root().unwrap().first_child_data(), None);
+    ///
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// root.append2(2);
+    /// root.append2(3);
+    ///
+    /// assert_eq!(tree.root().unwrap().first_child_data(), Some(&2));
+    /// ```
+    ///
+    pub fn first_child_data(&self) -> Option<&'a T> {
+        let first_child_id = self.get_self_as_node().relatives.first_child?;
+        self.tree.get_node(first_child_id).map(|node| &node.data)
+    }
+
+    ///
+    /// Returns a reference to the data of this `Node`'s last child, or `None` if it has no
+    /// children.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// assert_eq!(tree.root().unwrap().last_child_data(), None);
+    ///
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// root.append2(2);
+    /// root.append2(3);
+    ///
+    /// assert_eq!(tree.root().unwrap().last_child_data(), Some(&3));
+    /// ```
+    ///
+    pub fn last_child_data(&self) -> Option<&'a T> {
+        let last_child_id = self.get_self_as_node().relatives.last_child?;
+        self.tree.get_node(last_child_id).map(|node| &node.data)
+    }
+
+    ///
+    /// Returns a `NodeRef` pointing to the `Node` that comes last in a pre-order traversal of
+    /// this `Node`'s sub-tree, found by following last children down to a leaf.  A leaf returns
+    /// itself.
+    ///
+    /// Together with this `Node` it bounds the sub-tree's (inclusive) range of pre-order
+    /// positions.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    ///
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let two_id = root.append2(2);
+    /// let three_id = root.append2(3);
+    /// tree.get_mut(two_id).unwrap().append2(4);
+    ///
+    /// assert_eq!(tree.root().unwrap().last_descendant().node_id(), three_id);
+    /// assert_eq!(tree.get(two_id).unwrap().last_descendant().data(), &4);
+    /// assert_eq!(tree.get(three_id).unwrap().last_descendant().node_id(), three_id);
+    /// ```
+    ///
+    pub fn last_descendant(&self) -> NodeRef<'a, T, E> {
+        let mut node_id = self.node_id;
+        while let Some(last_child) = self
+            .tree
+            .get_node(node_id)
+            .and_then(|node| node.relatives.last_child)
+        {
+            node_id = last_child;
+        }
+        NodeRef::new(node_id, self.tree)
+    }
+
+    ///
+    /// Returns a hash of this `Node`'s sub-tree, covering the data of every `Node` in it along
+    /// with the number of children each one has and the edge data leading to each of them.  Sub-
+    /// trees with the same shape, data and edges hash the same, whatever their `NodeId`s or the
+    /// `Tree`s they live in.  The edge from this `Node` to its own parent lies outside the
+    /// sub-tree, so it is not part of the hash.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let first_id = root.append2(1);
+    /// let second_id = root.append2(1);
+    /// tree.get_mut(first_id).unwrap().append2(2);
+    /// tree.get_mut(second_id).unwrap().append2(2);
+    ///
+    /// let first = tree.get(first_id).unwrap();
+    /// let second = tree.get(second_id).unwrap();
+    /// assert_eq!(first.subtree_hash(), second.subtree_hash());
+    /// ```
+    ///
+    pub fn subtree_hash(&self) -> u64
+    where
+        T: Hash,
+        E: Hash,
+    {
+        let mut hasher = DefaultHasher::new();
+        for node in self.traverse_pre_order() {
+            node.data().hash(&mut hasher);
+            if node.node_id() != self.node_id {
+                node.parent_edge().hash(&mut hasher);
+            }
+            node.children().count().hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    ///
+    /// Returns a `NodeRef` pointing to the next leaf in pre-order.
+    ///
+    /// From a `Node` with children, this is the first leaf of its sub-tree, found by following
+    /// first children down.  From a leaf, it is the first leaf that comes strictly after it in
+    /// pre-order; `None` is returned if there is none.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let two_id = root.append2(2);
+    /// let five_id = root.append2(5);
+    /// let mut two = tree.get_mut(two_id).unwrap();
+    /// let three_id = two.append2(3);
+    /// two.append2(4);
+    ///
+    /// assert_eq!(tree.root().unwrap().next_leaf().unwrap().node_id(), three_id);
+    /// let three = tree.get(three_id).unwrap();
+    /// assert_eq!(three.next_leaf().unwrap().data(), &4);
+    /// let four = three.next_leaf().unwrap();
+    /// assert_eq!(four.next_leaf().unwrap().node_id(), five_id);
+    /// assert!(tree.get(five_id).unwrap().next_leaf().is_none());
+    /// ```
+    ///
+    pub fn next_leaf(&self) -> Option<NodeRef<'a, T, E>> {
+        let mut node_id = self.node_id;
+        if self.get_self_as_node().relatives.first_child.is_none() {
+            // climb to the closest `Node` with a next sibling and start over from that sibling
+            loop {
+                let relatives = self.tree.get_node_relatives(node_id);
+                if let Some(next_sibling) = relatives.next_sibling {
+                    node_id = next_sibling;
+                    break;
+                }
+                node_id = relatives.parent?;
+            }
+        }
+        while let Some(first_child) = self.tree.get_node_relatives(node_id).first_child {
+            node_id = first_child;
+        }
+        Some(NodeRef::new(node_id, self.tree))
+    }
+
+    ///
+    /// Returns `true` if this `Node` is the first child of its parent.  Returns `false` if it
+    /// has a previous sibling or no parent at all.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    ///
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let two_id = root.append2(2);
+    /// let three_id = root.append2(3);
+    ///
+    /// assert!(tree.get(two_id).unwrap().is_first_child());
+    /// assert!(!tree.get(three_id).unwrap().is_first_child());
+    /// assert!(!tree.root().unwrap().is_first_child());
+    /// ```
+    ///
+    pub fn is_first_child(&self) -> bool {
+        self.tree.is_node_first_last_child(self.node_id).0
+    }
+
+    ///
+    /// Returns `true` if this `Node` is the last child of its parent.  Returns `false` if it
+    /// has a next sibling or no parent at all.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    ///
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let two_id = root.append2(2);
+    /// let three_id = root.append2(3);
+    ///
+    /// assert!(!tree.get(two_id).unwrap().is_last_child());
+    /// assert!(tree.get(three_id).unwrap().is_last_child());
+    /// assert!(!tree.root().unwrap().is_last_child());
+    /// ```
+    ///
+    pub fn is_last_child(&self) -> bool {
+        self.tree.is_node_first_last_child(self.node_id).1
+    }
+
     ///
     /// Returns a `Iterator` over the given `Node`'s ancestors.  Each call to `Iterator::next()`
     /// returns a `NodeRef` pointing to the current `Node`'s parent.
@@ -188,10 +473,62 @@ impl<'a, T> NodeRef<'a, T> {
     /// }
     /// ```
     ///
-    pub fn ancestors(&self) -> Ancestors<'a, T> {
+    pub fn ancestors(&self) -> Ancestors<'a, T, E> {
         Ancestors::new(Some(self.node_id), self.tree)
     }
 
+    ///
+    /// Returns a `NodeRef` pointing to the closest ancestor of this `Node` whose data matches
+    /// `pred`, walking up the parent chain.  This `Node` itself isn't considered.  Returns a
+    /// `None`-value if no ancestor matches.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("section:intro").build();
+    /// let para_id = tree.root_mut().unwrap()
+    ///     .append("section:details")
+    ///     .append("list")
+    ///     .append("section:item")
+    ///     .node_id();
+    ///
+    /// let para = tree.get(para_id).unwrap();
+    /// let section = para.find_ancestor(|data| data.starts_with("section:")).unwrap();
+    /// assert_eq!(section.data(), &"section:details");
+    /// assert!(para.find_ancestor(|data| data.starts_with("table")).is_none());
+    /// ```
+    ///
+    pub fn find_ancestor<F: FnMut(&T) -> bool>(&self, mut pred: F) -> Option<NodeRef<'a, T, E>> {
+        self.ancestors().find(|ancestor| pred(ancestor.data()))
+    }
+
+    ///
+    /// Returns a `NodeRef` pointing to the closest descendant of this `Node` whose data matches
+    /// `pred`, searching breadth-first so that the match with the fewest edges in between wins
+    /// (ties go to the first one in level-order).  This `Node` itself isn't considered.  Returns
+    /// a `None`-value if nothing in the sub-tree matches.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("body").build();
+    /// let mut body = tree.root_mut().unwrap();
+    /// let div_id = body.append2("div");
+    /// let table_id = body.append2("table");
+    /// tree.get_mut(div_id).unwrap().append2("table");
+    ///
+    /// let body = tree.root().unwrap();
+    /// let table = body.find_descendant(|data| *data == "table").unwrap();
+    /// assert_eq!(table.node_id(), table_id);
+    /// assert!(body.find_descendant(|data| *data == "body").is_none());
+    /// ```
+    ///
+    pub fn find_descendant<F: FnMut(&T) -> bool>(&self, mut pred: F) -> Option<NodeRef<'a, T, E>> {
+        self.traverse_level_order()
+            .skip(1)
+            .find(|descendant| pred(descendant.data()))
+    }
+
     ///
     /// Returns a `Iterator` over the given `Node`'s children.  Each call to `Iterator::next()`
     /// returns a `NodeRef` pointing to the next child of the given `Node`.
@@ -214,11 +551,68 @@ impl<'a, T> NodeRef<'a, T> {
     /// }
     /// ```
     ///
-    pub fn children(&self) -> NextSiblings<'a, T> {
+    pub fn children(&self) -> NextSiblings<'a, T, E> {
         let first_child_id = self.tree.get_node_relatives(self.node_id).first_child;
         NextSiblings::new(first_child_id, self.tree)
     }
 
+    ///
+    /// Returns an `Iterator` over all the children of this `Node`'s parent, this `Node`
+    /// included, in order.  A `Node` without a parent (such as the root) is its only sibling.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    ///
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// root.append2(2);
+    /// let three_id = root.append2(3);
+    /// root.append2(4);
+    ///
+    /// let three = tree.get(three_id).unwrap();
+    /// let values = three.siblings_including_self()
+    ///     .map(|sibling| *sibling.data()).collect::<Vec<i32>>();
+    /// assert_eq!(values, vec![2, 3, 4]);
+    /// ```
+    ///
+    pub fn siblings_including_self(&self) -> NextSiblings<'a, T, E> {
+        let first_sibling_id = match self.tree.get_node_relatives(self.node_id).parent {
+            Some(parent_id) => self.tree.get_node_relatives(parent_id).first_child,
+            None => Some(self.node_id),
+        };
+        NextSiblings::new(first_sibling_id, self.tree)
+    }
+
+    ///
+    /// Returns the `NodeId`s of the given `Node`'s children, in order.
+    ///
+    /// Unlike `children()`, the returned `Vec` doesn't borrow the `Tree`, so it can be iterated
+    /// while the `Tree` is being mutated.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    ///
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// root.append2(2);
+    /// root.append2(3);
+    ///
+    /// let child_ids = tree.root().unwrap().children_ids();
+    /// for child_id in child_ids {
+    ///     *tree.get_mut(child_id).unwrap().data() *= 10;
+    /// }
+    ///
+    /// let values = tree.root().unwrap().children()
+    ///     .map(|child| *child.data()).collect::<Vec<i32>>();
+    /// assert_eq!(values, vec![20, 30]);
+    /// ```
+    ///
+    pub fn children_ids(&self) -> Vec<NodeId> {
+        self.children().map(|child| child.node_id()).collect()
+    }
+
     /// Depth-first pre-order traversal.
     ///
     /// ```
@@ -234,10 +628,84 @@ impl<'a, T> NodeRef<'a, T> {
     ///     .map(|node_ref| node_ref.data().clone()).collect::<Vec<i64>>();
     /// assert_eq!(pre_order, vec![0, 1, 2, 3, 4]);
     /// ```
-    pub fn traverse_pre_order(&self) -> PreOrder<'a, T> {
+    pub fn traverse_pre_order(&self) -> PreOrder<'a, T, E> {
         PreOrder::new(self, self.tree)
     }
 
+    /// Depth-first pre-order traversal that yields each `Node` along with its depth below this
+    /// `Node` and its index among its siblings.  This `Node` itself comes first, at depth 0 and
+    /// index 0.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0i64).build();
+    /// let root_id = tree.root().unwrap().node_id();
+    /// let one_id = tree.get_mut(root_id).unwrap().append(1).node_id();
+    /// tree.get_mut(one_id).unwrap().append(2);
+    /// tree.get_mut(one_id).unwrap().append(3);
+    /// tree.get_mut(root_id).unwrap().append(4);
+    /// let positions = tree.root().unwrap().traverse_pre_order_positioned()
+    ///     .map(|(node_ref, depth, index)| (*node_ref.data(), depth, index))
+    ///     .collect::<Vec<(i64, usize, usize)>>();
+    /// assert_eq!(positions, vec![(0, 0, 0), (1, 1, 0), (2, 2, 0), (3, 2, 1), (4, 1, 1)]);
+    /// ```
+    pub fn traverse_pre_order_positioned(&self) -> PositionedPreOrder<'a, T, E> {
+        PositionedPreOrder::new(self, self.tree)
+    }
+
+    /// Depth-first pre-order traversal that yields each `Node` along with a context derived from
+    /// its parent's.  `derive` is called with the parent's context and the `Node`'s data to
+    /// compute the `Node`'s context; this `Node` is given `root_ctx` as its parent's context.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let two_id = root.append2(2);
+    /// root.append2(3);
+    /// tree.get_mut(two_id).unwrap().append2(4);
+    ///
+    /// // the sum of the data on the path from the root to each node
+    /// let sums = tree.root().unwrap()
+    ///     .traverse_with_context(0, |parent_sum, data| parent_sum + data)
+    ///     .map(|(node, sum)| (*node.data(), sum))
+    ///     .collect::<Vec<(i32, i32)>>();
+    /// assert_eq!(sums, vec![(1, 1), (2, 3), (4, 7), (3, 4)]);
+    /// ```
+    pub fn traverse_with_context<C: Clone, F: FnMut(&C, &T) -> C>(
+        &self,
+        root_ctx: C,
+        derive: F,
+    ) -> ContextPreOrder<'a, T, E, C, F> {
+        ContextPreOrder::new(self, self.tree, root_ctx, derive)
+    }
+
+    /// Depth-first pre-order traversal that visits the children of each `Node` in the order given
+    /// by `compare` on their data, instead of the order they are stored in.  Children that compare
+    /// equal keep their stored order.  The `Tree` itself isn't changed.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0i64).build();
+    /// let root_id = tree.root().unwrap().node_id();
+    /// let one_id = tree.get_mut(root_id).unwrap().append(1).node_id();
+    /// tree.get_mut(one_id).unwrap().append(2);
+    /// tree.get_mut(one_id).unwrap().append(3);
+    /// tree.get_mut(root_id).unwrap().append(4);
+    /// let descending = tree.root().unwrap().traverse_pre_order_by(|a, b| b.cmp(a))
+    ///     .map(|node_ref| node_ref.data().clone()).collect::<Vec<i64>>();
+    /// assert_eq!(descending, vec![0, 4, 1, 3, 2]);
+    /// ```
+    pub fn traverse_pre_order_by<F: FnMut(&T, &T) -> Ordering>(
+        &self,
+        compare: F,
+    ) -> PreOrderBy<'a, T, E, F> {
+        PreOrderBy::new(self, self.tree, compare)
+    }
+
     /// Depth-first post-order traversal.
     ///
     /// ```
@@ -253,7 +721,7 @@ impl<'a, T> NodeRef<'a, T> {
     ///     .map(|node_ref| node_ref.data().clone()).collect::<Vec<i64>>();
     /// assert_eq!(post_order, vec![2, 3, 1, 4, 0]);
     /// ```
-    pub fn traverse_post_order(&self) -> PostOrder<'a, T> {
+    pub fn traverse_post_order(&self) -> PostOrder<'a, T, E> {
         PostOrder::new(self, self.tree)
     }
 
@@ -272,11 +740,65 @@ impl<'a, T> NodeRef<'a, T> {
     ///     .map(|node_ref| node_ref.data().clone()).collect::<Vec<i64>>();
     /// assert_eq!(level_order, vec![0, 1, 4, 2, 3]);
     /// ```
-    pub fn traverse_level_order(&self) -> LevelOrder<'a, T> {
+    pub fn traverse_level_order(&self) -> LevelOrder<'a, T, E> {
         LevelOrder::new(self, self.tree)
     }
 
-    fn get_self_as_node(&self) -> &Node<T> {
+    ///
+    /// Walks this `Node`'s sub-tree (this `Node` included) in pre-order, calling `f` on the data
+    /// of each `Node` it visits.  What `f` returns decides how the walk goes on: `Walk::Continue`
+    /// visits the `Node`'s children next, `Walk::SkipChildren` leaves them out, and `Walk::Stop`
+    /// ends the walk.
+    ///
+    /// ```
+    /// use nary_tree::behaviors::Walk;
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let one_id = root.append2(1);
+    /// let three_id = root.append2(3);
+    /// root.append2(5);
+    /// tree.get_mut(one_id).unwrap().append2(2);
+    /// tree.get_mut(three_id).unwrap().append2(4);
+    ///
+    /// let mut visited = Vec::new();
+    /// tree.root().unwrap().walk(|data| {
+    ///     visited.push(*data);
+    ///     match data {
+    ///         1 => Walk::SkipChildren,
+    ///         4 => Walk::Stop,
+    ///         _ => Walk::Continue,
+    ///     }
+    /// });
+    /// assert_eq!(visited, vec![0, 1, 3, 4]);
+    /// ```
+    ///
+    pub fn walk<F: FnMut(&T) -> Walk>(&self, mut f: F) {
+        let mut next = Some(self.node_id);
+        while let Some(node_id) = next {
+            let node = self.tree.get_node(node_id).expect("node must exist");
+            next = match f(&node.data) {
+                Walk::Continue => node.relatives.first_child,
+                Walk::SkipChildren => None,
+                Walk::Stop => return,
+            };
+
+            // climb until there's a next sibling, without leaving this `Node`'s sub-tree
+            let mut current = node_id;
+            while next.is_none() && current != self.node_id {
+                let relatives = self
+                    .tree
+                    .get_node(current)
+                    .expect("node must exist")
+                    .relatives;
+                next = relatives.next_sibling;
+                current = relatives.parent.expect("parent must exist");
+            }
+        }
+    }
+
+    fn get_self_as_node(&self) -> &Node<T, E> {
         if let Some(node) = self.tree.get_node(self.node_id) {
             node
         } else {
@@ -288,7 +810,8 @@ impl<'a, T> NodeRef<'a, T> {
 #[cfg_attr(tarpaulin, skip)]
 #[cfg(test)]
 mod node_ref_tests {
-    use crate::tree::Tree;
+    use crate::behaviors::Walk;
+    use crate::tree::{Tree, TreeBuilder};
 
     #[test]
     fn data() {
@@ -344,6 +867,394 @@ mod node_ref_tests {
         assert!(root_ref.last_child().is_none());
     }
 
+    #[test]
+    fn first_last_child_data() {
+        let mut tree = Tree::new();
+        tree.set_root(1);
+        assert_eq!(tree.root().unwrap().first_child_data(), None);
+        assert_eq!(tree.root().unwrap().last_child_data(), None);
+
+        let mut root = tree.root_mut().expect("root doesn't exist");
+        let two_id = root.append2(2);
+        assert_eq!(tree.root().unwrap().first_child_data(), Some(&2));
+        assert_eq!(tree.root().unwrap().last_child_data(), Some(&2));
+
+        tree.root_mut().unwrap().append2(3);
+        tree.get_mut(two_id).unwrap().append2(4);
+        let root = tree.root().unwrap();
+        assert_eq!(root.first_child_data(), Some(&2));
+        assert_eq!(root.last_child_data(), Some(&3));
+    }
+
+    #[test]
+    fn subtree_hash() {
+        let mut tree = Tree::new();
+        tree.set_root(0);
+
+        let mut root = tree.root_mut().expect("root doesn't exist");
+        let one_id = root.append2(1);
+        let two_id = root.append2(1);
+        let three_id = root.append2(1);
+        tree.get_mut(one_id).unwrap().append2(2);
+        tree.get_mut(one_id).unwrap().append2(3);
+        tree.get_mut(two_id).unwrap().append2(2);
+        tree.get_mut(two_id).unwrap().append2(3);
+        // same pre-order data as the others, but a different shape
+        let two = tree.get_mut(three_id).unwrap().append2(2);
+        tree.get_mut(two).unwrap().append2(3);
+
+        let hash_of = |tree: &Tree<i32>, node_id| tree.get(node_id).unwrap().subtree_hash();
+        assert_eq!(hash_of(&tree, one_id), hash_of(&tree, two_id));
+        assert_ne!(hash_of(&tree, one_id), hash_of(&tree, three_id));
+
+        let copy = tree.clone_subtree(one_id).unwrap();
+        assert_eq!(copy.root().unwrap().subtree_hash(), hash_of(&tree, one_id));
+
+        // edges inside the sub-tree count, the one above it doesn't
+        let mut tree: Tree<i32, &str> = TreeBuilder::new().with_root(0).build_with_edges();
+        let mut root = tree.root_mut().unwrap();
+        let one_id = root.append_with_edge(1, "a");
+        let two_id = root.append_with_edge(1, "b");
+        let three_id = root.append_with_edge(1, "c");
+        tree.get_mut(one_id).unwrap().append_with_edge(2, "x");
+        tree.get_mut(two_id).unwrap().append_with_edge(2, "x");
+        tree.get_mut(three_id).unwrap().append_with_edge(2, "y");
+
+        let hash_of = |node_id| tree.get(node_id).unwrap().subtree_hash();
+        assert_eq!(hash_of(one_id), hash_of(two_id));
+        assert_ne!(hash_of(one_id), hash_of(three_id));
+    }
+
+    #[test]
+    fn traverse_with_context() {
+        let mut tree = Tree::new();
+        tree.set_root("a");
+
+        let mut root = tree.root_mut().expect("root doesn't exist");
+        let b_id = root.append2("b");
+        root.append2("c");
+        let d_id = tree.get_mut(b_id).unwrap().append2("d");
+        tree.get_mut(b_id).unwrap().append2("e");
+        tree.get_mut(d_id).unwrap().append2("f");
+
+        let paths: Vec<String> = tree
+            .root()
+            .unwrap()
+            .traverse_with_context(String::new(), |parent, data| format!("{}/{}", parent, data))
+            .map(|(_, path)| path)
+            .collect();
+        assert_eq!(
+            paths,
+            vec!["/a", "/a/b", "/a/b/d", "/a/b/d/f", "/a/b/e", "/a/c"]
+        );
+
+        let depths: Vec<(&str, usize)> = tree
+            .get(b_id)
+            .unwrap()
+            .traverse_with_context(0, |parent, _| parent + 1)
+            .map(|(node, depth)| (*node.data(), depth))
+            .collect();
+        assert_eq!(depths, vec![("b", 1), ("d", 2), ("f", 3), ("e", 2)]);
+    }
+
+    #[test]
+    fn next_leaf() {
+        let mut tree = Tree::new();
+        tree.set_root(1);
+        assert!(tree.root().unwrap().next_leaf().is_none());
+
+        let mut root = tree.root_mut().expect("root doesn't exist");
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+        let four_id = tree.get_mut(two_id).unwrap().append2(4);
+        let five_id = tree.get_mut(four_id).unwrap().append2(5);
+        let six_id = tree.get_mut(three_id).unwrap().append2(6);
+
+        let leaves: Vec<i32> =
+            std::iter::successors(tree.root().unwrap().next_leaf(), |leaf| leaf.next_leaf())
+                .map(|leaf| *leaf.data())
+                .collect();
+        assert_eq!(leaves, vec![5, 6]);
+
+        assert_eq!(
+            tree.get(three_id).unwrap().next_leaf().unwrap().node_id(),
+            six_id
+        );
+        assert_eq!(
+            tree.get(four_id).unwrap().next_leaf().unwrap().node_id(),
+            five_id
+        );
+        assert!(tree.get(six_id).unwrap().next_leaf().is_none());
+    }
+
+    #[test]
+    fn siblings_including_self() {
+        let mut tree = Tree::new();
+        tree.set_root(1);
+        let root = tree.root().unwrap();
+        let roots: Vec<i32> = root.siblings_including_self().map(|n| *n.data()).collect();
+        assert_eq!(roots, vec![1]);
+
+        let mut root = tree.root_mut().expect("root doesn't exist");
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+        let four_id = tree.get_mut(three_id).unwrap().append2(4);
+
+        for node_id in [two_id, three_id] {
+            let node = tree.get(node_id).unwrap();
+            let siblings: Vec<i32> = node.siblings_including_self().map(|n| *n.data()).collect();
+            assert_eq!(siblings, vec![2, 3]);
+        }
+        let four = tree.get(four_id).unwrap();
+        let siblings: Vec<i32> = four.siblings_including_self().map(|n| *n.data()).collect();
+        assert_eq!(siblings, vec![4]);
+    }
+
+    #[test]
+    fn find_ancestor() {
+        let mut tree = Tree::new();
+        tree.set_root(1);
+        let two_id = tree.root_mut().unwrap().append2(2);
+        let three_id = tree.get_mut(two_id).unwrap().append2(3);
+        let four_id = tree.get_mut(three_id).unwrap().append2(4);
+
+        let four = tree.get(four_id).unwrap();
+        assert_eq!(
+            four.find_ancestor(|n| n % 2 == 0).unwrap().node_id(),
+            two_id
+        );
+        assert_eq!(four.find_ancestor(|n| *n < 4).unwrap().node_id(), three_id);
+        // the node itself is never a match
+        assert!(four.find_ancestor(|n| *n == 4).is_none());
+        assert!(tree.root().unwrap().find_ancestor(|_| true).is_none());
+    }
+
+    #[test]
+    fn find_descendant() {
+        let mut tree = Tree::new();
+        tree.set_root(1);
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+        let four_id = tree.get_mut(two_id).unwrap().append2(4);
+        let six_id = tree.get_mut(three_id).unwrap().append2(6);
+
+        let root = tree.root().unwrap();
+        // the shallower match wins even though a deeper one comes first in pre-order
+        assert_eq!(
+            root.find_descendant(|n| *n == 4 || *n == 3)
+                .unwrap()
+                .node_id(),
+            three_id
+        );
+        assert_eq!(root.find_descendant(|n| *n > 3).unwrap().node_id(), four_id);
+        assert!(root.find_descendant(|n| *n == 1).is_none());
+
+        let three = tree.get(three_id).unwrap();
+        assert_eq!(
+            three.find_descendant(|n| n % 2 == 0).unwrap().node_id(),
+            six_id
+        );
+        assert!(three.find_descendant(|n| *n == 4).is_none());
+        assert!(tree
+            .get(six_id)
+            .unwrap()
+            .find_descendant(|_| true)
+            .is_none());
+    }
+
+    #[test]
+    fn neighbors() {
+        let mut tree = Tree::new();
+        tree.set_root(1);
+
+        let mut root = tree.root_mut().expect("root doesn't exist");
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+        let four_id = root.append2(4);
+
+        let three = tree.get(three_id).unwrap();
+        let (prev, next) = three.neighbors();
+        assert_eq!(prev.unwrap().node_id(), two_id);
+        assert_eq!(next.unwrap().node_id(), four_id);
+
+        let two = tree.get(two_id).unwrap();
+        let (prev, next) = two.neighbors();
+        assert!(prev.is_none());
+        assert_eq!(next.unwrap().node_id(), three_id);
+
+        let root = tree.root().unwrap();
+        let (prev, next) = root.neighbors();
+        assert!(prev.is_none() && next.is_none());
+    }
+
+    #[test]
+    fn walk() {
+        let mut tree = Tree::new();
+        tree.set_root(1);
+
+        let mut root = tree.root_mut().expect("root doesn't exist");
+        let two_id = root.append2(2);
+        root.append2(5);
+        let mut two = tree.get_mut(two_id).unwrap();
+        two.append2(3);
+        two.append2(4);
+
+        let mut visited = Vec::new();
+        tree.root().unwrap().walk(|data| {
+            visited.push(*data);
+            Walk::Continue
+        });
+        assert_eq!(visited, vec![1, 2, 3, 4, 5]);
+
+        // the walk doesn't leave the sub-tree it started from
+        let mut visited = Vec::new();
+        tree.get(two_id).unwrap().walk(|data| {
+            visited.push(*data);
+            Walk::Continue
+        });
+        assert_eq!(visited, vec![2, 3, 4]);
+
+        let mut visited = Vec::new();
+        tree.get(two_id).unwrap().walk(|data| {
+            visited.push(*data);
+            Walk::SkipChildren
+        });
+        assert_eq!(visited, vec![2]);
+
+        let mut visited = Vec::new();
+        tree.root().unwrap().walk(|data| {
+            visited.push(*data);
+            if *data == 3 {
+                Walk::Stop
+            } else {
+                Walk::Continue
+            }
+        });
+        assert_eq!(visited, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn last_descendant() {
+        let mut tree = Tree::new();
+        tree.set_root(1);
+        let root_id = tree.root_id().unwrap();
+        assert_eq!(tree.root().unwrap().last_descendant().node_id(), root_id);
+
+        let mut root = tree.root_mut().expect("root doesn't exist");
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+        let four_id = tree.get_mut(three_id).unwrap().append2(4);
+        tree.get_mut(two_id).unwrap().append2(5);
+
+        let root = tree.root().unwrap();
+        assert_eq!(root.last_descendant().node_id(), four_id);
+        assert_eq!(root.traverse_pre_order().last().unwrap().node_id(), four_id);
+        assert_eq!(tree.get(two_id).unwrap().last_descendant().data(), &5);
+    }
+
+    #[test]
+    fn traverse_pre_order_by() {
+        let mut tree = Tree::new();
+        tree.set_root((0, 'a'));
+
+        let mut root = tree.root_mut().expect("root doesn't exist");
+        let one_id = root.append2((3, 'b'));
+        root.append2((1, 'c'));
+        root.append2((3, 'd'));
+        let mut one = tree.get_mut(one_id).unwrap();
+        one.append2((2, 'e'));
+        one.append2((1, 'f'));
+
+        let visited: Vec<char> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order_by(|a, b| a.0.cmp(&b.0))
+            .map(|node| node.data().1)
+            .collect();
+        assert_eq!(visited, vec!['a', 'c', 'b', 'f', 'e', 'd']);
+
+        // the stored order is left alone
+        let visited: Vec<char> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| node.data().1)
+            .collect();
+        assert_eq!(visited, vec!['a', 'b', 'e', 'f', 'c', 'd']);
+
+        let visited: Vec<char> = tree
+            .get(one_id)
+            .unwrap()
+            .traverse_pre_order_by(|a, b| b.0.cmp(&a.0))
+            .map(|node| node.data().1)
+            .collect();
+        assert_eq!(visited, vec!['b', 'e', 'f']);
+    }
+
+    #[test]
+    fn traverse_pre_order_positioned() {
+        let mut tree = Tree::new();
+        tree.set_root(1);
+
+        let mut root = tree.root_mut().expect("root doesn't exist");
+        root.append2(2);
+        let three_id = root.append2(3);
+        let mut three = tree.get_mut(three_id).unwrap();
+        three.append2(4);
+        let five_id = three.append2(5);
+        tree.get_mut(five_id).unwrap().append2(6);
+
+        let positions: Vec<(i32, usize, usize)> = tree
+            .get(three_id)
+            .unwrap()
+            .traverse_pre_order_positioned()
+            .map(|(node, depth, index)| (*node.data(), depth, index))
+            .collect();
+        assert_eq!(positions, vec![(3, 0, 0), (4, 1, 0), (5, 1, 1), (6, 2, 0)]);
+
+        let nodes: Vec<i32> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order_positioned()
+            .map(|(node, _, _)| *node.data())
+            .collect();
+        let pre_order: Vec<i32> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| *node.data())
+            .collect();
+        assert_eq!(nodes, pre_order);
+    }
+
+    #[test]
+    fn is_first_last_child() {
+        let mut tree = Tree::new();
+        tree.set_root(1);
+
+        let mut root = tree.root_mut().expect("root doesn't exist");
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+        let four_id = root.append2(4);
+
+        let two = tree.get(two_id).unwrap();
+        assert!(two.is_first_child());
+        assert!(!two.is_last_child());
+
+        let three = tree.get(three_id).unwrap();
+        assert!(!three.is_first_child());
+        assert!(!three.is_last_child());
+
+        let four = tree.get(four_id).unwrap();
+        assert!(!four.is_first_child());
+        assert!(four.is_last_child());
+
+        let root = tree.root().unwrap();
+        assert!(!root.is_first_child());
+        assert!(!root.is_last_child());
+    }
+
     #[test]
     fn ancestors() {
         let mut tree = Tree::new();
@@ -378,4 +1289,20 @@ mod node_ref_tests {
             assert_eq!(node_ref.data(), &values[i]);
         }
     }
+
+    #[test]
+    fn children_ids() {
+        let mut tree = Tree::new();
+        tree.set_root(1);
+
+        let mut root = tree.root_mut().expect("root doesn't exist");
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+
+        let root = root.as_ref();
+        assert_eq!(root.children_ids(), vec![two_id, three_id]);
+
+        let two = tree.get(two_id).unwrap();
+        assert!(two.children_ids().is_empty());
+    }
 }
@@ -0,0 +1,148 @@
+use std::cell::OnceCell;
+
+use crate::iter::PreOrder;
+use crate::node::NodeRef;
+use crate::tree::Tree;
+use crate::NodeId;
+
+///
+/// A borrowed view of a `Node` and all of its descendants, usable as a collection of `Node`s.
+///
+pub struct Subtree<'a, T, E = ()> {
+    node_id: NodeId,
+    tree: &'a Tree<T, E>,
+    len: OnceCell<usize>,
+}
+
+impl<'a, T, E> Subtree<'a, T, E> {
+    pub(crate) fn new(node_id: NodeId, tree: &'a Tree<T, E>) -> Subtree<'a, T, E> {
+        Subtree {
+            node_id,
+            tree,
+            len: OnceCell::new(),
+        }
+    }
+
+    ///
+    /// Returns a `NodeRef` pointing to the `Node` at the top of the sub-tree.
+    ///
+    pub fn root(&self) -> NodeRef<'a, T, E> {
+        NodeRef::new(self.node_id, self.tree)
+    }
+
+    ///
+    /// Returns the number of `Node`s in the sub-tree, its top `Node` included.  It is counted on
+    /// the first call and remembered afterwards.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let two_id = root.append2(2);
+    /// root.append2(3);
+    /// tree.get_mut(two_id).unwrap().append2(4);
+    ///
+    /// assert_eq!(tree.root().unwrap().subtree().len(), 4);
+    /// assert_eq!(tree.get(two_id).unwrap().subtree().len(), 2);
+    /// ```
+    ///
+    // a sub-tree always holds at least its top `Node`, so it is never empty
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        *self.len.get_or_init(|| self.iter().count())
+    }
+
+    ///
+    /// Returns `true` if the `Node` identified by `node_id` is in the sub-tree, i.e. if it is the
+    /// top `Node` or one of its descendants.  This walks up from `node_id`, so it takes time
+    /// proportional to its depth.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let mut root = tree.root_mut().unwrap();
+    /// let two_id = root.append2(2);
+    /// let three_id = root.append2(3);
+    /// let four_id = tree.get_mut(two_id).unwrap().append2(4);
+    ///
+    /// let two = tree.get(two_id).unwrap();
+    /// let subtree = two.subtree();
+    /// assert!(subtree.contains(two_id));
+    /// assert!(subtree.contains(four_id));
+    /// assert!(!subtree.contains(three_id));
+    /// assert!(!subtree.contains(root_id));
+    /// ```
+    ///
+    pub fn contains(&self, node_id: NodeId) -> bool {
+        let mut current = Some(node_id);
+        while let Some(id) = current {
+            if id == self.node_id {
+                return true;
+            }
+            current = self
+                .tree
+                .get_node(id)
+                .and_then(|node| node.relatives.parent);
+        }
+        false
+    }
+
+    ///
+    /// Returns an `Iterator` over the `Node`s of the sub-tree in depth-first pre-order, starting
+    /// with its top `Node`.
+    ///
+    pub fn iter(&self) -> PreOrder<'a, T, E> {
+        self.root().traverse_pre_order()
+    }
+}
+
+impl<'a, T, E> IntoIterator for &Subtree<'a, T, E> {
+    type Item = NodeRef<'a, T, E>;
+    type IntoIter = PreOrder<'a, T, E>;
+
+    fn into_iter(self) -> PreOrder<'a, T, E> {
+        self.iter()
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod subtree_tests {
+    use crate::behaviors::RemoveBehavior::OrphanChildren;
+    use crate::tree::Tree;
+
+    #[test]
+    fn subtree() {
+        let mut tree = Tree::new();
+        tree.set_root(1);
+
+        let mut root = tree.root_mut().expect("root doesn't exist");
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+        let four_id = tree.get_mut(two_id).unwrap().append2(4);
+        let five_id = tree.get_mut(four_id).unwrap().append2(5);
+
+        let two = tree.get(two_id).unwrap();
+        let subtree = two.subtree();
+        assert_eq!(subtree.root().node_id(), two_id);
+        assert_eq!(subtree.len(), 3);
+        assert_eq!(subtree.len(), 3);
+        let values: Vec<i32> = (&subtree).into_iter().map(|n| *n.data()).collect();
+        assert_eq!(values, vec![2, 4, 5]);
+        assert!(subtree.contains(five_id));
+        assert!(!subtree.contains(three_id));
+
+        let other = Tree::from_parent_array(vec![(1, None)]).unwrap();
+        assert!(!subtree.contains(other.root_id().unwrap()));
+
+        // orphaned nodes aren't in the sub-tree of their former ancestors
+        tree.remove(four_id, OrphanChildren);
+        let root = tree.root().unwrap();
+        assert!(!root.subtree().contains(five_id));
+        assert!(!root.subtree().contains(four_id));
+        assert_eq!(root.subtree().len(), 3);
+    }
+}
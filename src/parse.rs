@@ -0,0 +1,321 @@
+use std::fmt;
+
+use crate::tree::Tree;
+use crate::NodeId;
+
+///
+/// Describes what went wrong while parsing text into a `Tree`.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    ///
+    /// The line is indented by something other than a whole number of indentation units, or by
+    /// whitespace other than spaces (such as a tab), or it is more than one level deeper than the
+    /// line before it.
+    ///
+    InconsistentIndentation,
+
+    ///
+    /// The line is at the root level but the `Tree` already has a root.
+    ///
+    MultipleRoots,
+
+    ///
+    /// The line doesn't start with the box-drawing prefix `write_formatted` would have written
+    /// for it.
+    ///
+    InvalidPrefix,
+}
+
+///
+/// The error returned when text can't be parsed into a `Tree`.  It carries the (1-based) number
+/// of the offending line.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    line: usize,
+    kind: ParseErrorKind,
+}
+
+impl ParseError {
+    pub(crate) fn new(line: usize, kind: ParseErrorKind) -> ParseError {
+        ParseError { line, kind }
+    }
+
+    ///
+    /// Returns the (1-based) number of the line that couldn't be parsed.
+    ///
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    ///
+    /// Returns what went wrong on that line.
+    ///
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self.kind {
+            ParseErrorKind::InconsistentIndentation => "inconsistent indentation",
+            ParseErrorKind::MultipleRoots => "more than one root",
+            ParseErrorKind::InvalidPrefix => "invalid prefix",
+        };
+        write!(f, "{} on line {}", description, self.line)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub(crate) fn indented_text(text: &str, indent_unit: usize) -> Result<Tree<String>, ParseError> {
+    assert!(indent_unit > 0, "indent_unit must be greater than 0");
+
+    let lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            // indentation is made of spaces only; a tab can't be measured in indent units
+            let data = line.trim_start_matches(' ');
+            let indent = line.len() - data.len();
+            if indent % indent_unit != 0 || data.starts_with(char::is_whitespace) {
+                return Err(ParseError::new(
+                    index + 1,
+                    ParseErrorKind::InconsistentIndentation,
+                ));
+            }
+            Ok((index + 1, indent / indent_unit, data.trim_end().to_string()))
+        });
+    from_levels(lines)
+}
+
+pub(crate) fn formatted(text: &str) -> Result<Tree<String>, ParseError> {
+    // whether the last `Node` seen at each level (below the root) was the last of its siblings
+    let mut last: Vec<bool> = Vec::new();
+    let lines = text.lines().enumerate().map(move |(index, line)| {
+        let line_number = index + 1;
+        if index == 0 {
+            return Ok((line_number, 0, line.to_string()));
+        }
+
+        let invalid_prefix = ParseError::new(line_number, ParseErrorKind::InvalidPrefix);
+        let mut rest = line;
+        let mut level = 0;
+        loop {
+            // each ancestor below the root gets a column telling whether it has more siblings
+            let has_more_siblings = if let Some(tail) = rest.strip_prefix("│   ") {
+                rest = tail;
+                true
+            } else if let Some(tail) = rest.strip_prefix("    ") {
+                rest = tail;
+                false
+            } else {
+                break;
+            };
+            if last.get(level) != Some(&!has_more_siblings) {
+                return Err(invalid_prefix);
+            }
+            level += 1;
+        }
+
+        let is_last = if let Some(tail) = rest.strip_prefix("├── ") {
+            rest = tail;
+            false
+        } else if let Some(tail) = rest.strip_prefix("└── ") {
+            rest = tail;
+            true
+        } else {
+            return Err(invalid_prefix);
+        };
+
+        // a sibling can't follow the last child of a `Node`
+        if last.get(level) == Some(&true) {
+            return Err(invalid_prefix);
+        }
+        last.truncate(level);
+        last.push(is_last);
+
+        Ok((line_number, level + 1, rest.to_string()))
+    });
+    from_levels(lines)
+}
+
+// builds a `Tree` out of `(line, level, data)` entries given in pre-order
+fn from_levels<I>(lines: I) -> Result<Tree<String>, ParseError>
+where
+    I: IntoIterator<Item = Result<(usize, usize, String), ParseError>>,
+{
+    let mut tree = Tree::new();
+    // the ids of the last `Node` seen at each level, down to the current one
+    let mut path: Vec<NodeId> = Vec::new();
+    for entry in lines {
+        let (line, level, data) = entry?;
+        if level > path.len() {
+            return Err(ParseError::new(
+                line,
+                ParseErrorKind::InconsistentIndentation,
+            ));
+        }
+
+        path.truncate(level);
+        let node_id = match path.last() {
+            Some(&parent_id) => tree
+                .get_mut(parent_id)
+                .expect("parent must exist")
+                .append2(data),
+            None if tree.root_id().is_some() => {
+                return Err(ParseError::new(line, ParseErrorKind::MultipleRoots));
+            }
+            None => tree.set_root(data),
+        };
+        path.push(node_id);
+    }
+    Ok(tree)
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod parse_tests {
+    use super::*;
+
+    #[test]
+    fn indented_text_empty() {
+        let tree = indented_text("", 2).unwrap();
+        assert!(tree.root().is_none());
+
+        let tree = indented_text("\n  \n", 2).unwrap();
+        assert!(tree.root().is_none());
+    }
+
+    #[test]
+    fn indented_text_rejects_tabs() {
+        for text in ["root\n\ta\n", "root\n  \ta\n", "root\n  a\n\t\tb\n"] {
+            let error = indented_text(text, 2).unwrap_err();
+            assert_eq!(error.kind(), ParseErrorKind::InconsistentIndentation);
+            assert_eq!(error.line(), text.lines().count());
+        }
+
+        // tabs inside the data are kept
+        let tree = indented_text("root\n  a\tb\n", 2).unwrap();
+        assert_eq!(tree.root().unwrap().first_child().unwrap().data(), "a\tb");
+    }
+
+    #[test]
+    fn indented_text_outline() {
+        let text = "root\n  a\n    b\n\n  c  \n    d\n      e\n  f\n";
+        let tree = indented_text(text, 2).unwrap();
+
+        let mut s = String::new();
+        tree.write_formatted(&mut s).unwrap();
+        assert_eq!(
+            s,
+            "\
+root
+├── a
+│   └── b
+├── c
+│   └── d
+│       └── e
+└── f
+"
+        );
+    }
+
+    #[test]
+    fn formatted_round_trip() {
+        let text = "\
+root
+├── a
+│   ├── b
+│   └──\u{20}
+│       └── c d
+├── e
+│   └── f
+│       ├── g
+│       └── h
+└── ├── i
+";
+        let tree = formatted(text).unwrap();
+        let root = tree.root().unwrap();
+        assert_eq!(root.data(), "root");
+        assert_eq!(root.last_child().unwrap().data(), "├── i");
+        let a = root.first_child().unwrap();
+        assert_eq!(a.last_child().unwrap().data(), "");
+        assert_eq!(a.last_child().unwrap().first_child().unwrap().data(), "c d");
+
+        let mut s = String::new();
+        tree.write_formatted(&mut s).unwrap();
+        assert_eq!(s, text);
+    }
+
+    #[test]
+    fn formatted_empty_and_single() {
+        assert!(formatted("").unwrap().root().is_none());
+
+        let tree = formatted("└── root\n").unwrap();
+        assert_eq!(tree.root().unwrap().data(), "└── root");
+        assert!(tree.root().unwrap().first_child().is_none());
+    }
+
+    #[test]
+    fn formatted_errors() {
+        let error = formatted("root\n├── a\nb\n").unwrap_err();
+        assert_eq!(
+            (error.line(), error.kind()),
+            (3, ParseErrorKind::InvalidPrefix)
+        );
+
+        let error = formatted("root\n├── a\n│   b\n").unwrap_err();
+        assert_eq!(
+            (error.line(), error.kind()),
+            (3, ParseErrorKind::InvalidPrefix)
+        );
+
+        let error = formatted("root\n└── a\n│   └── b\n").unwrap_err();
+        assert_eq!(
+            (error.line(), error.kind()),
+            (3, ParseErrorKind::InvalidPrefix)
+        );
+
+        let error = formatted("root\n└── a\n└── b\n").unwrap_err();
+        assert_eq!(
+            (error.line(), error.kind()),
+            (3, ParseErrorKind::InvalidPrefix)
+        );
+
+        let error = formatted("root\n├── a\n│   │   └── b\n").unwrap_err();
+        assert_eq!(
+            (error.line(), error.kind()),
+            (3, ParseErrorKind::InvalidPrefix)
+        );
+
+        let error = formatted("root\n├─ a\n").unwrap_err();
+        assert_eq!(
+            (error.line(), error.kind()),
+            (2, ParseErrorKind::InvalidPrefix)
+        );
+    }
+
+    #[test]
+    fn indented_text_errors() {
+        let error = indented_text("root\n  a\n   b\n", 2).unwrap_err();
+        assert_eq!(error.line(), 3);
+        assert_eq!(error.kind(), ParseErrorKind::InconsistentIndentation);
+        assert_eq!(error.to_string(), "inconsistent indentation on line 3");
+
+        let error = indented_text("root\n\n    a\n", 2).unwrap_err();
+        assert_eq!(error.line(), 3);
+        assert_eq!(error.kind(), ParseErrorKind::InconsistentIndentation);
+
+        let error = indented_text("  root\n", 2).unwrap_err();
+        assert_eq!(error.line(), 1);
+        assert_eq!(error.kind(), ParseErrorKind::InconsistentIndentation);
+
+        let error = indented_text("root\n  a\nother\n", 2).unwrap_err();
+        assert_eq!(error.line(), 3);
+        assert_eq!(error.kind(), ParseErrorKind::MultipleRoots);
+    }
+}
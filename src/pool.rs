@@ -0,0 +1,232 @@
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+
+use crate::core_tree::CoreTree;
+use crate::tree::Tree;
+
+///
+/// A pool of `Tree` storage, meant for code that builds and throws away many `Tree`s.
+///
+/// `take` hands out an empty `Tree` backed by storage left behind by a previous `Tree` when
+/// there is some, so the `Node` allocations are reused instead of being made all over again.
+/// Every `Tree` handed out gets a new id: the `NodeId`s of the `Tree` that used the storage before
+/// are not valid in it.
+///
+/// ```
+/// use nary_tree::pool::TreePool;
+///
+/// let pool = TreePool::new();
+///
+/// let mut tree = pool.take();
+/// let root_id = tree.set_root(1);
+/// tree.root_mut().unwrap().append2(2);
+/// let capacity = tree.capacity();
+/// drop(tree);
+///
+/// let tree = pool.take();
+/// assert!(tree.root().is_none());
+/// assert_eq!(tree.capacity(), capacity);
+/// assert!(tree.get(root_id).is_none());
+/// ```
+///
+#[derive(Debug)]
+pub struct TreePool<T> {
+    core_trees: RefCell<Vec<CoreTree<T>>>,
+}
+
+impl<T> TreePool<T> {
+    ///
+    /// Creates a new, empty `TreePool`.
+    ///
+    /// ```
+    /// use nary_tree::pool::TreePool;
+    ///
+    /// let pool: TreePool<i32> = TreePool::new();
+    ///
+    /// # assert_eq!(pool.available(), 0);
+    /// ```
+    ///
+    pub fn new() -> TreePool<T> {
+        TreePool {
+            core_trees: RefCell::new(Vec::new()),
+        }
+    }
+
+    ///
+    /// Returns an empty `Tree`, reusing pooled storage if there is any.
+    ///
+    /// The `Tree` goes back to the pool when the returned `PooledTree` is dropped; use
+    /// `PooledTree::into_inner` to keep the `Tree` out of the pool instead.
+    ///
+    /// ```
+    /// use nary_tree::pool::TreePool;
+    ///
+    /// let pool = TreePool::new();
+    /// {
+    ///     let mut tree = pool.take();
+    ///     tree.set_root("a");
+    /// }
+    /// assert_eq!(pool.available(), 1);
+    ///
+    /// let tree = pool.take();
+    /// assert_eq!(pool.available(), 0);
+    /// assert!(tree.root().is_none());
+    /// ```
+    ///
+    pub fn take(&self) -> PooledTree<'_, T> {
+        let core_tree = self
+            .core_trees
+            .borrow_mut()
+            .pop()
+            .unwrap_or_else(|| CoreTree::new(0));
+        PooledTree {
+            tree: Some(Tree {
+                root_id: None,
+                core_tree,
+            }),
+            pool: self,
+        }
+    }
+
+    ///
+    /// Puts the storage of `tree` in the pool, dropping all of its `Node`s.
+    ///
+    /// ```
+    /// use nary_tree::pool::TreePool;
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let pool = TreePool::new();
+    /// pool.give(TreeBuilder::new().with_root(1).with_capacity(10).build());
+    ///
+    /// let tree = pool.take();
+    /// assert!(tree.root().is_none());
+    /// assert_eq!(tree.capacity(), 10);
+    /// ```
+    ///
+    pub fn give(&self, tree: Tree<T>) {
+        let mut core_tree = tree.core_tree;
+        core_tree.recycle();
+        self.core_trees.borrow_mut().push(core_tree);
+    }
+
+    ///
+    /// Returns the number of `Tree`s' worth of storage waiting in the pool.
+    ///
+    pub fn available(&self) -> usize {
+        self.core_trees.borrow().len()
+    }
+}
+
+impl<T> Default for TreePool<T> {
+    fn default() -> Self {
+        TreePool::new()
+    }
+}
+
+///
+/// A `Tree` taken from a `TreePool`.  It dereferences to the `Tree` and gives its storage back
+/// to the pool when dropped.
+///
+#[derive(Debug)]
+pub struct PooledTree<'a, T> {
+    tree: Option<Tree<T>>,
+    pool: &'a TreePool<T>,
+}
+
+impl<T> PooledTree<'_, T> {
+    ///
+    /// Takes the `Tree` out, so that its storage won't go back to the pool.
+    ///
+    /// ```
+    /// use nary_tree::pool::TreePool;
+    ///
+    /// let pool = TreePool::new();
+    /// let mut tree = pool.take().into_inner();
+    /// tree.set_root(1);
+    /// drop(tree);
+    ///
+    /// assert_eq!(pool.available(), 0);
+    /// ```
+    ///
+    pub fn into_inner(mut self) -> Tree<T> {
+        self.tree.take().expect("tree is only taken on drop")
+    }
+}
+
+impl<T> Deref for PooledTree<'_, T> {
+    type Target = Tree<T>;
+
+    fn deref(&self) -> &Tree<T> {
+        self.tree.as_ref().expect("tree is only taken on drop")
+    }
+}
+
+impl<T> DerefMut for PooledTree<'_, T> {
+    fn deref_mut(&mut self) -> &mut Tree<T> {
+        self.tree.as_mut().expect("tree is only taken on drop")
+    }
+}
+
+impl<T> Drop for PooledTree<'_, T> {
+    fn drop(&mut self) {
+        if let Some(tree) = self.tree.take() {
+            self.pool.give(tree);
+        }
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod pool_tests {
+    use super::*;
+
+    #[test]
+    fn take_reuses_storage() {
+        let pool = TreePool::new();
+        let mut tree = pool.take();
+        let root_id = tree.set_root(1);
+        for i in 2..10 {
+            tree.root_mut().unwrap().append2(i);
+        }
+        let capacity = tree.capacity();
+        assert!(capacity >= 9);
+        drop(tree);
+        assert_eq!(pool.available(), 1);
+
+        let mut tree = pool.take();
+        assert_eq!(pool.available(), 0);
+        assert!(tree.root().is_none());
+        assert_eq!(tree.capacity(), capacity);
+        assert!(tree.get(root_id).is_none());
+
+        // the slot of the old root is reused, but the old id doesn't leak into the new tree
+        let new_root_id = tree.set_root(2);
+        assert_ne!(new_root_id, root_id);
+        assert!(tree.get(root_id).is_none());
+        assert_eq!(tree.capacity(), capacity);
+    }
+
+    #[test]
+    fn take_from_empty_pool() {
+        let pool: TreePool<i32> = TreePool::default();
+        let first = pool.take();
+        let second = pool.take();
+        assert_eq!(first.capacity(), 0);
+        assert!(second.root().is_none());
+        drop(first);
+        drop(second);
+        assert_eq!(pool.available(), 2);
+    }
+
+    #[test]
+    fn into_inner_and_give() {
+        let pool = TreePool::new();
+        let mut tree = pool.take().into_inner();
+        tree.set_root(1);
+        assert_eq!(pool.available(), 0);
+
+        pool.give(tree);
+        assert_eq!(pool.available(), 1);
+        assert!(pool.take().root().is_none());
+    }
+}
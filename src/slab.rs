@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub(crate) struct Index {
     pub(crate) index: usize,
@@ -5,7 +7,7 @@ pub(crate) struct Index {
 }
 
 impl Index {
-    fn new(index: usize, generation: usize) -> Self {
+    pub(crate) fn new(index: usize, generation: usize) -> Self {
         Self { index, generation }
     }
 }
@@ -40,6 +42,20 @@ impl<T> Slab<T> {
         self.slab.capacity()
     }
 
+    // heap memory allocated for the entries: each one is either a vacant slot holding the index
+    // of the next vacant one or an occupied slot, which is laid out like this `Result`
+    pub(crate) fn memory_footprint(&self) -> usize {
+        self.capacity() * std::mem::size_of::<Result<SlabNode<T>, usize>>()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.slab.len()
+    }
+
+    pub(crate) fn reserve_exact(&mut self, additional: usize) {
+        self.slab.reserve_exact(additional);
+    }
+
     pub(crate) fn insert(&mut self, data: T) -> Index {
         Index::new(
             self.slab.insert(SlabNode::new(data, self.generation)),
@@ -81,11 +97,83 @@ impl<T> Slab<T> {
         }
     }
 
+    // replaces the value at `index` with the result of `f`, keeping the index valid; the value
+    // is taken out of its slot for `f`, so if `f` panics the slot is left vacant, and the old
+    // index can't resolve to whatever is stored there next
+    pub(crate) fn replace_with<F: FnOnce(T) -> T>(&mut self, index: Index, f: F) -> bool {
+        if self.get(index).is_none() {
+            return false;
+        }
+        let entry = self.slab.remove(index.index);
+        self.next_generation();
+        // the slot that was just vacated is the first one to be reused
+        let vacant = self.slab.vacant_entry();
+        debug_assert_eq!(vacant.key(), index.index, "vacated slot must be reused");
+        vacant.insert(SlabNode::new(f(entry.data), entry.generation));
+        true
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (Index, &T)> {
+        self.slab
+            .iter()
+            .map(|(key, node)| (Index::new(key, node.generation), &node.data))
+    }
+
+    // looks each index up directly; fails on a missing index as well as on a duplicate one
+    pub(crate) fn get_disjoint_mut<const N: usize>(
+        &mut self,
+        indices: [Index; N],
+    ) -> Option<[&mut T; N]> {
+        let nodes = self
+            .slab
+            .get_disjoint_mut(indices.map(|index| index.index))
+            .ok()?;
+        let valid = nodes
+            .iter()
+            .zip(indices)
+            .all(|(node, index)| node.generation == index.generation);
+        if !valid {
+            return None;
+        }
+        Some(nodes.map(|node| &mut node.data))
+    }
+
+    // the number of indices is only known at run time, so the slots can't be borrowed one by
+    // one: this takes a single pass over the slab, stopping once every index has been found
+    pub(crate) fn get_many_mut(&mut self, indices: &[Index]) -> Option<Vec<&mut T>> {
+        let mut sorted: Vec<usize> = indices.iter().map(|index| index.index).collect();
+        sorted.sort_unstable();
+        sorted.dedup();
+        if sorted.len() != indices.len() || indices.iter().any(|index| self.get(*index).is_none()) {
+            return None;
+        }
+
+        let mut positions: HashMap<usize, usize> = indices
+            .iter()
+            .enumerate()
+            .map(|(position, index)| (index.index, position))
+            .collect();
+        let mut found: Vec<Option<&mut T>> = indices.iter().map(|_| None).collect();
+        for (key, node) in self.slab.iter_mut() {
+            if positions.is_empty() {
+                break;
+            }
+            if let Some(position) = positions.remove(&key) {
+                found[position] = Some(&mut node.data);
+            }
+        }
+        found.into_iter().collect()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.slab.clear();
+        self.next_generation();
+    }
+
     pub(crate) fn shrink_to_fit(&mut self) {
         self.slab.shrink_to_fit();
     }
 
-    #[cfg(feature = "experimental")]
     pub(crate) fn compact<F>(&mut self, mut rekey: F)
     where
         F: FnMut(usize, Index),
@@ -329,6 +417,147 @@ mod tests {
         assert!(six_ref.is_none());
     }
 
+    #[test]
+    fn iter() {
+        let mut slab = Slab::new(5);
+
+        let six = slab.insert(6);
+        let seven = slab.insert(7);
+        let eight = slab.insert(8);
+        slab.try_remove(seven);
+        let nine = slab.insert(9);
+
+        let items: Vec<(Index, &i32)> = slab.iter().collect();
+        assert_eq!(items, vec![(six, &6), (nine, &9), (eight, &8)]);
+    }
+
+    #[test]
+    fn memory_footprint() {
+        let slab: Slab<u64> = Slab::new(0);
+        assert_eq!(slab.memory_footprint(), 0);
+
+        // every slot takes the room of an occupied or a vacant entry, whichever is larger, and
+        // the exact layout depends on the target
+        let slab: Slab<u64> = Slab::new(10);
+        let slot = std::mem::size_of::<Result<SlabNode<u64>, usize>>();
+        assert!(slot >= std::mem::size_of::<SlabNode<u64>>());
+        assert_eq!(slab.memory_footprint(), 10 * slot);
+        assert!(Slab::<u64>::new(20).memory_footprint() > slab.memory_footprint());
+    }
+
+    #[test]
+    fn replace_with() {
+        let mut slab = Slab::new(5);
+
+        let six = slab.insert(6);
+        let seven = slab.insert(7);
+        let eight = slab.insert(8);
+        slab.try_remove(eight);
+
+        assert!(slab.replace_with(six, |data| data * 10));
+        assert_eq!(slab.get(six), Some(&60));
+        assert_eq!(slab.get(seven), Some(&7));
+        assert!(!slab.replace_with(eight, |data| data * 10));
+
+        let nine = slab.insert(9);
+        assert_eq!(nine.index, eight.index);
+        assert_eq!(slab.get(nine), Some(&9));
+    }
+
+    #[test]
+    fn replace_with_panicking() {
+        let mut slab = Slab::new(5);
+
+        let six = slab.insert(6);
+        let seven = slab.insert(7);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            slab.replace_with(six, |_| panic!("merge failed"));
+        }));
+        assert!(result.is_err());
+
+        // the value is lost along with its slot, and the old index stays invalid once the slot
+        // is reused
+        assert_eq!(slab.get(six), None);
+        assert_eq!(slab.get(seven), Some(&7));
+        assert_eq!(slab.len(), 1);
+        assert_eq!(slab.iter().count(), 1);
+        let eight = slab.insert(8);
+        assert_eq!(eight.index, six.index);
+        assert_eq!(slab.get(six), None);
+        assert!(!slab.replace_with(six, |data| data * 10));
+    }
+
+    #[test]
+    fn clear() {
+        let mut slab = Slab::new(5);
+
+        let six = slab.insert(6);
+        slab.insert(7);
+        slab.clear();
+
+        assert_eq!(slab.len(), 0);
+        assert_eq!(slab.capacity(), 5);
+        assert_eq!(slab.get(six), None);
+
+        slab.insert(8);
+        assert_eq!(slab.get(six), None);
+    }
+
+    #[test]
+    fn get_disjoint_mut() {
+        let mut slab = Slab::new(5);
+
+        let six = slab.insert(6);
+        let seven = slab.insert(7);
+        let eight = slab.insert(8);
+
+        let [eight_mut, six_mut] = slab.get_disjoint_mut([eight, six]).unwrap();
+        *eight_mut = 80;
+        *six_mut = 60;
+        assert_eq!(slab.get(six), Some(&60));
+        assert_eq!(slab.get(eight), Some(&80));
+
+        assert!(slab.get_disjoint_mut([six, six]).is_none());
+        slab.try_remove(seven);
+        assert!(slab.get_disjoint_mut([six, seven]).is_none());
+        let nine = slab.insert(9);
+        assert_eq!(nine.index, seven.index);
+        assert!(slab.get_disjoint_mut([seven]).is_none());
+        assert!(slab.get_disjoint_mut([Index::new(10, 0)]).is_none());
+        assert_eq!(slab.get_disjoint_mut([]), Some([]));
+    }
+
+    #[test]
+    fn get_many_mut() {
+        let mut slab = Slab::new(5);
+
+        let six = slab.insert(6);
+        let seven = slab.insert(7);
+        let eight = slab.insert(8);
+
+        let many = slab.get_many_mut(&[eight, six]);
+        assert!(many.is_some());
+
+        let mut many = many.unwrap();
+        assert_eq!(many, vec![&mut 8, &mut 6]);
+
+        *many[0] = 80;
+        *many[1] = 60;
+        assert_eq!(slab.get(eight), Some(&80));
+        assert_eq!(slab.get(six), Some(&60));
+
+        assert!(slab.get_many_mut(&[six, six]).is_none());
+
+        slab.try_remove(seven);
+        assert!(slab.get_many_mut(&[six, seven]).is_none());
+
+        let nine = slab.insert(9);
+        assert_eq!(nine.index, seven.index);
+        assert!(slab.get_many_mut(&[seven]).is_none());
+        assert_eq!(slab.get_many_mut(&[nine]), Some(vec![&mut 9]));
+    }
+
     #[test]
     fn get_mut() {
         let mut slab = Slab::new(5);
@@ -1,8 +1,15 @@
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::Display;
+use std::hash::Hash;
+use std::panic::AssertUnwindSafe;
 
 use crate::behaviors::*;
 use crate::core_tree::CoreTree;
+use crate::diff::TreeEdit;
+use crate::iter::Paths;
 use crate::node::*;
+use crate::parse::{self, ParseError};
+use crate::view::TreeView;
 use crate::NodeId;
 
 ///
@@ -11,6 +18,7 @@ use crate::NodeId;
 pub struct TreeBuilder<T> {
     root: Option<T>,
     capacity: Option<usize>,
+    growth_policy: GrowthPolicy,
 }
 
 impl<T> Default for TreeBuilder<T> {
@@ -35,6 +43,7 @@ impl<T> TreeBuilder<T> {
         TreeBuilder {
             root: None,
             capacity: None,
+            growth_policy: GrowthPolicy::default(),
         }
     }
 
@@ -51,6 +60,7 @@ impl<T> TreeBuilder<T> {
         TreeBuilder {
             root: Some(root),
             capacity: self.capacity,
+            growth_policy: self.growth_policy,
         }
     }
 
@@ -72,6 +82,38 @@ impl<T> TreeBuilder<T> {
         TreeBuilder {
             root: self.root,
             capacity: Some(capacity),
+            growth_policy: self.growth_policy,
+        }
+    }
+
+    ///
+    /// Sets the `GrowthPolicy` of the `TreeBuilder`.
+    ///
+    /// This controls how much space the `Tree` allocates whenever it runs out of capacity.
+    /// The default is `GrowthPolicy::Double`.
+    ///
+    /// ```
+    /// use nary_tree::behaviors::GrowthPolicy;
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new()
+    ///     .with_root(1)
+    ///     .with_growth_policy(GrowthPolicy::Fixed(4))
+    ///     .build();
+    /// assert_eq!(tree.capacity(), 4);
+    ///
+    /// let mut root = tree.root_mut().unwrap();
+    /// for i in 2..=5 {
+    ///     root.append2(i);
+    /// }
+    /// assert_eq!(tree.capacity(), 8);
+    /// ```
+    ///
+    pub fn with_growth_policy(self, growth_policy: GrowthPolicy) -> TreeBuilder<T> {
+        TreeBuilder {
+            root: self.root,
+            capacity: self.capacity,
+            growth_policy,
         }
     }
 
@@ -85,21 +127,351 @@ impl<T> TreeBuilder<T> {
     /// ```
     ///
     pub fn build(self) -> Tree<T> {
+        self.build_with_edges()
+    }
+
+    ///
+    /// Build a `Tree` whose edges carry data of type `E`, based upon the current settings in the
+    /// `TreeBuilder`.  See `NodeMut::append_with_edge` and `NodeRef::parent_edge`.
+    ///
+    /// ```
+    /// use nary_tree::tree::{Tree, TreeBuilder};
+    ///
+    /// let tree: Tree<&str, &str> = TreeBuilder::new().with_root("ceo").build_with_edges();
+    /// # assert!(tree.root().unwrap().parent_edge().is_none());
+    /// ```
+    ///
+    pub fn build_with_edges<E>(self) -> Tree<T, E> {
         let capacity = self.capacity.unwrap_or(0);
-        let mut core_tree: CoreTree<T> = CoreTree::new(capacity);
+        let mut core_tree: CoreTree<T, E> = CoreTree::new(capacity);
+        core_tree.set_growth_policy(self.growth_policy);
         let root_id = self.root.map(|val| core_tree.insert(val));
 
         Tree { root_id, core_tree }
     }
+
+    ///
+    /// Turns the `TreeBuilder` into a `RootedTreeBuilder`, whose `build` fails unless a root was
+    /// set, so that the built `Tree` is known to have one.
+    ///
+    /// ```
+    /// use nary_tree::tree::{BuildError, TreeBuilder};
+    ///
+    /// let tree = TreeBuilder::new().with_root(1).require_root().build().unwrap();
+    /// assert_eq!(tree.root().unwrap().data(), &1);
+    ///
+    /// let error = TreeBuilder::<i32>::new().with_capacity(10).require_root().build();
+    /// assert_eq!(error.unwrap_err(), BuildError::NoRoot);
+    /// ```
+    ///
+    pub fn require_root(self) -> RootedTreeBuilder<T> {
+        RootedTreeBuilder { builder: self }
+    }
+}
+
+///
+/// A `TreeBuilder` that refuses to build a `Tree` without a root.  See
+/// `TreeBuilder::require_root`.
+///
+pub struct RootedTreeBuilder<T> {
+    builder: TreeBuilder<T>,
+}
+
+impl<T> RootedTreeBuilder<T> {
+    ///
+    /// Build a `Tree` based upon the settings of the `TreeBuilder`, or return
+    /// `BuildError::NoRoot` if no root was set.
+    ///
+    pub fn build(self) -> Result<Tree<T>, BuildError> {
+        if self.builder.root.is_none() {
+            return Err(BuildError::NoRoot);
+        }
+        Ok(self.builder.build())
+    }
+}
+
+///
+/// Builds a `Tree` top-down in a single pass, keeping track of a current `Node` the way a parser
+/// keeps track of the element it is in.
+///
+/// `push` adds a child to the current `Node` and descends into it, `pop` goes back up to the
+/// parent, and `sibling` adds a `Node` next to the current one and moves over to it.  The first
+/// `push` sets the root.  Going anywhere from the root other than down is refused: `pop` returns
+/// `false` and `sibling` returns a `None`-value, leaving everything as it was.
+///
+/// ```
+/// use nary_tree::tree::StackBuilder;
+///
+/// let mut builder = StackBuilder::new();
+/// builder.push("html");
+/// builder.push("head");
+/// builder.sibling("body");
+/// builder.push("p");
+/// builder.pop();
+/// builder.push("div");
+/// let tree = builder.finish();
+///
+/// let mut s = String::new();
+/// tree.write_formatted(&mut s).unwrap();
+/// assert_eq!(&s, "\
+/// html
+/// ├── head
+/// └── body
+///     ├── p
+///     └── div
+/// ");
+/// ```
+///
+pub struct StackBuilder<T> {
+    tree: Tree<T>,
+    current: Option<NodeId>,
+}
+
+impl<T> StackBuilder<T> {
+    ///
+    /// Creates a new `StackBuilder` with an empty `Tree`.
+    ///
+    pub fn new() -> StackBuilder<T> {
+        StackBuilder {
+            tree: Tree::new(),
+            current: None,
+        }
+    }
+
+    ///
+    /// Adds a `Node` holding `data` as the last child of the current `Node`, or as the root if
+    /// there is none yet, and makes it the current `Node`.  Returns its `NodeId`.
+    ///
+    pub fn push(&mut self, data: T) -> NodeId {
+        let new_id = match self.current {
+            Some(current) => self
+                .tree
+                .get_mut(current)
+                .expect("current node must exist")
+                .append2(data),
+            None => self.tree.set_root(data),
+        };
+        self.current = Some(new_id);
+        new_id
+    }
+
+    ///
+    /// Makes the parent of the current `Node` the current `Node`.  Returns `false` (and does
+    /// nothing) if the current `Node` is the root or if nothing was pushed yet.
+    ///
+    pub fn pop(&mut self) -> bool {
+        match self.parent() {
+            Some(parent) => {
+                self.current = Some(parent);
+                true
+            }
+            None => false,
+        }
+    }
+
+    ///
+    /// Adds a `Node` holding `data` as the last child of the current `Node`'s parent and makes it
+    /// the current `Node`.  Returns its `NodeId`, or a `None`-value (dropping `data`) if the
+    /// current `Node` is the root or if nothing was pushed yet.
+    ///
+    pub fn sibling(&mut self, data: T) -> Option<NodeId> {
+        let parent = self.parent()?;
+        self.current = Some(parent);
+        Some(self.push(data))
+    }
+
+    ///
+    /// Returns the `NodeId` of the current `Node`, if anything was pushed yet.
+    ///
+    pub fn current(&self) -> Option<NodeId> {
+        self.current
+    }
+
+    ///
+    /// Returns the built `Tree`.
+    ///
+    pub fn finish(self) -> Tree<T> {
+        self.tree
+    }
+
+    fn parent(&self) -> Option<NodeId> {
+        self.tree.get_node_relatives(self.current?).parent
+    }
+}
+
+impl<T> Default for StackBuilder<T> {
+    fn default() -> Self {
+        StackBuilder::new()
+    }
 }
 
 ///
 /// A tree structure containing `Node`s.
 ///
+/// `E` is the type of the data that can be attached to the edge between a `Node` and its parent;
+/// it defaults to `()` for `Tree`s that don't need any (see `TreeBuilder::build_with_edges`).
+///
 #[derive(Debug)]
-pub struct Tree<T> {
+pub struct Tree<T, E = ()> {
     pub(crate) root_id: Option<NodeId>,
-    pub(crate) core_tree: CoreTree<T>,
+    pub(crate) core_tree: CoreTree<T, E>,
+}
+
+///
+/// The error returned when `Node`s can't be moved within a `Tree`.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MoveError {
+    ///
+    /// One of the `NodeId`s points to nothing (or belongs to a different `Tree`).
+    ///
+    InvalidNodeId(NodeId),
+
+    ///
+    /// The `Node` would end up in its own sub-tree.
+    ///
+    WouldCreateCycle(NodeId),
+
+    ///
+    /// The root can't be moved under another `Node`.
+    ///
+    CannotMoveRoot,
+
+    ///
+    /// The same `NodeId` was given more than once where each `Node` can only appear once.
+    ///
+    DuplicateNodeId(NodeId),
+}
+
+impl Display for MoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MoveError::InvalidNodeId(node_id) => write!(f, "invalid node id {:?}", node_id),
+            MoveError::WouldCreateCycle(node_id) => {
+                write!(f, "moving {:?} would create a cycle", node_id)
+            }
+            MoveError::CannotMoveRoot => write!(f, "the root can't be moved"),
+            MoveError::DuplicateNodeId(node_id) => write!(f, "duplicate node id {:?}", node_id),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+///
+/// The error returned when two sub-trees can't be swapped.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SwapError {
+    ///
+    /// One of the `NodeId`s points to nothing (or belongs to a different `Tree`).
+    ///
+    InvalidNodeId(NodeId),
+
+    ///
+    /// One of the `Node`s is an ancestor of the other.
+    ///
+    Nested,
+}
+
+impl Display for SwapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SwapError::InvalidNodeId(node_id) => write!(f, "invalid node id {:?}", node_id),
+            SwapError::Nested => write!(f, "one sub-tree contains the other"),
+        }
+    }
+}
+
+impl std::error::Error for SwapError {}
+
+///
+/// The error returned when a `Tree` can't be built, either from a parent-pointer array or by a
+/// `RootedTreeBuilder`.  The variants about a specific item carry its index.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BuildError {
+    ///
+    /// There is no root: none of the items is the root, or the `RootedTreeBuilder` wasn't given
+    /// one.
+    ///
+    NoRoot,
+
+    ///
+    /// The item is a root, but an earlier item already is.
+    ///
+    MultipleRoots(usize),
+
+    ///
+    /// The item's parent index is out of bounds.
+    ///
+    InvalidParent(usize),
+
+    ///
+    /// Following the parents of the item leads back to it (or into another cycle) instead of up
+    /// to the root.
+    ///
+    Cycle(usize),
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildError::NoRoot => write!(f, "no root"),
+            BuildError::MultipleRoots(index) => write!(f, "item {} is a second root", index),
+            BuildError::InvalidParent(index) => {
+                write!(f, "item {} has an out of bounds parent", index)
+            }
+            BuildError::Cycle(index) => write!(f, "item {} is part of a cycle", index),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+///
+/// A summary of the size and shape of a `Tree`, as returned by `Tree::stats`.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TreeStats {
+    ///
+    /// The number of `Node`s stored in the `Tree`, including any that aren't reachable from the
+    /// root.
+    ///
+    pub node_count: usize,
+
+    ///
+    /// The number of `Node`s the `Tree` can hold without allocating more memory.
+    ///
+    pub capacity: usize,
+
+    ///
+    /// The number of edges on the longest path from the root down to a leaf; 0 for a `Tree`
+    /// made of its root alone or without a root.
+    ///
+    pub height: usize,
+
+    ///
+    /// The number of `Node`s reachable from the root that have no children.
+    ///
+    pub leaf_count: usize,
+
+    ///
+    /// The number of slots in the `Tree`'s storage not holding a `Node`.
+    ///
+    pub free_slots: usize,
+}
+
+impl TreeStats {
+    ///
+    /// Returns `true` if more than half of the `Tree`'s capacity is free, in which case calling
+    /// `Tree::compact_internal` and `Tree::shrink_to_fit` is likely to be worth it.  Compacting
+    /// changes the `NodeId`s of the `Node`s it moves; with the `experimental` feature,
+    /// `Tree::compact` does the same but lets `Tree::is_current` spot the `NodeId`s it made stale.
+    ///
+    pub fn recommends_compaction(&self) -> bool {
+        self.free_slots > self.node_count
+    }
 }
 
 impl<T> Tree<T> {
@@ -118,12 +490,128 @@ impl<T> Tree<T> {
         TreeBuilder::new().build()
     }
 
-    //todo: write test for this
+    ///
+    /// Builds a `Tree` out of `(data, parent)` items, where `parent` is the index of the item's
+    /// parent among `items`, or `None` for the root.  Children keep the order in which they appear
+    /// in `items`.  No items build an empty `Tree`.
+    ///
+    /// Returns a `BuildError` carrying the index of the offending item if there isn't exactly
+    /// one root, if a parent index is out of bounds, or if an item's parents never lead up to the
+    /// root.
+    ///
+    /// ```
+    /// use nary_tree::tree::{BuildError, Tree};
+    ///
+    /// let tree = Tree::from_parent_array(vec![(1, None), (2, Some(0)), (3, Some(1)), (4, Some(0))])
+    ///     .unwrap();
+    ///
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// 1
+    /// ├── 2
+    /// │   └── 3
+    /// └── 4
+    /// ");
+    ///
+    /// let error = Tree::from_parent_array(vec![(1, None), (2, Some(2)), (3, Some(1))]);
+    /// assert_eq!(error.unwrap_err(), BuildError::Cycle(1));
+    /// ```
+    ///
+    pub fn from_parent_array<I: IntoIterator<Item = (T, Option<usize>)>>(
+        items: I,
+    ) -> Result<Tree<T>, BuildError> {
+        let items: Vec<(T, Option<usize>)> = items.into_iter().collect();
+        if items.is_empty() {
+            return Ok(Tree::new());
+        }
+
+        let mut root = None;
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); items.len()];
+        for (index, (_, parent)) in items.iter().enumerate() {
+            match *parent {
+                None if root.is_some() => return Err(BuildError::MultipleRoots(index)),
+                None => root = Some(index),
+                Some(parent) if parent >= items.len() => {
+                    return Err(BuildError::InvalidParent(index))
+                }
+                Some(parent) => children[parent].push(index),
+            }
+        }
+        let root = root.ok_or(BuildError::NoRoot)?;
+
+        let mut data: Vec<Option<T>> = items.into_iter().map(|(data, _)| Some(data)).collect();
+        let mut tree = TreeBuilder::new().with_capacity(data.len()).build();
+        let root_id = tree.set_root(data[root].take().expect("each item is visited once"));
+
+        // every item reachable from the root is taken; whatever is left is cut off by a cycle
+        let mut queue = VecDeque::from([(root, root_id)]);
+        while let Some((index, node_id)) = queue.pop_front() {
+            let mut node = tree.get_mut(node_id).expect("node must exist");
+            for &child in &children[index] {
+                let child_id = node.append2(data[child].take().expect("each item is visited once"));
+                queue.push_back((child, child_id));
+            }
+        }
+        match data.iter().position(Option::is_some) {
+            Some(index) => Err(BuildError::Cycle(index)),
+            None => Ok(tree),
+        }
+    }
+
+    ///
+    /// Builds a `Tree` by starting from `root` and calling `expand` on the data of each `Node` to
+    /// get the data of its children, level by level, until `max_depth` (the root being at depth
+    /// 0).  `Node`s at `max_depth` are leaves and `expand` isn't called on them; `expand`
+    /// returning an empty `Vec` makes a leaf as well.
+    ///
+    /// `Node`s are expanded from a queue rather than recursively, so deep `Tree`s don't use up the
+    /// stack.
+    ///
+    /// ```
+    /// use nary_tree::tree::Tree;
+    ///
+    /// let tree = Tree::generate(1, |n| vec![n * 2, n * 2 + 1], 2);
+    ///
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// 1
+    /// ├── 2
+    /// │   ├── 4
+    /// │   └── 5
+    /// └── 3
+    ///     ├── 6
+    ///     └── 7
+    /// ");
+    /// ```
+    ///
+    pub fn generate<F: FnMut(&T) -> Vec<T>>(root: T, mut expand: F, max_depth: usize) -> Tree<T> {
+        let mut tree = TreeBuilder::new().with_root(root).build();
+        let root_id = tree.root_id().expect("root must exist");
+
+        let mut queue = VecDeque::from([(root_id, 0)]);
+        while let Some((node_id, depth)) = queue.pop_front() {
+            if depth == max_depth {
+                continue;
+            }
+            let mut node = tree.get_mut(node_id).expect("node must exist");
+            for data in expand(node.data()) {
+                queue.push_back((node.append2(data), depth + 1));
+            }
+        }
+        tree
+    }
+}
+
+impl<T, E> Tree<T, E> {
     ///
     /// Sets the "root" of the `Tree` to be `root`.
     ///
-    /// If there is already a "root" node in the `Tree`, that node is shifted down and the new
-    /// one takes its place.
+    /// If the `Tree` is empty, the new root has no children.  If there is already a "root" node
+    /// in the `Tree`, that node is shifted down and the new one takes its place: the old root
+    /// (with its whole sub-tree) becomes the only child of the new root and keeps its `NodeId`.
+    /// Use `replace_root_data` to change the root's data without changing the structure.
     ///
     /// ```
     /// use nary_tree::tree::Tree;
@@ -133,6 +621,14 @@ impl<T> Tree<T> {
     /// let root_id = tree.set_root(1);
     ///
     /// assert_eq!(tree.root_id().unwrap(), root_id);
+    ///
+    /// let new_root_id = tree.set_root(0);
+    /// let new_root = tree.root().unwrap();
+    ///
+    /// assert_eq!(new_root.node_id(), new_root_id);
+    /// assert_eq!(new_root.first_child().unwrap().node_id(), root_id);
+    /// assert_eq!(new_root.last_child().unwrap().node_id(), root_id);
+    /// assert_eq!(tree.get(root_id).unwrap().parent().unwrap().node_id(), new_root_id);
     /// ```
     ///
     pub fn set_root(&mut self, root: T) -> NodeId {
@@ -151,6 +647,36 @@ impl<T> Tree<T> {
         new_root_id
     }
 
+    ///
+    /// Replaces the data of the root `Node` in place and returns the old data.  Unlike
+    /// `set_root`, no `Node` is added and the `Tree`'s structure and `NodeId`s stay the same.
+    ///
+    /// Returns a `None`-value (and drops `data`) if the `Tree` has no root.
+    ///
+    /// ```
+    /// use nary_tree::tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// assert_eq!(tree.replace_root_data(0), None);
+    ///
+    /// let root_id = tree.set_root(1);
+    /// tree.root_mut().unwrap().append2(2);
+    ///
+    /// assert_eq!(tree.replace_root_data(3), Some(1));
+    /// assert_eq!(tree.root_id(), Some(root_id));
+    /// assert_eq!(tree.root().unwrap().data(), &3);
+    /// assert_eq!(tree.root().unwrap().first_child().unwrap().data(), &2);
+    /// ```
+    ///
+    pub fn replace_root_data(&mut self, data: T) -> Option<T> {
+        let root_id = self.root_id?;
+        let old_data = self
+            .get_node_mut(root_id)
+            .map(|root| std::mem::replace(&mut root.data, data))?;
+        self.core_tree.data_version += 1;
+        Some(old_data)
+    }
+
     ///
     /// Returns the `Tree`'s current capacity.  Capacity is defined as the number of times new
     /// `Node`s can be added to the `Tree` before it must allocate more memory.
@@ -168,40 +694,473 @@ impl<T> Tree<T> {
     }
 
     ///
-    /// Returns the `NodeId` of the root node of the `Tree`.
+    /// Returns the share of the `Tree`'s capacity that isn't holding a `Node`: the number of free
+    /// slots divided by `capacity()`, between 0.0 and 1.0.  Returns 0.0 for a full `Tree` and for
+    /// an empty one, whether or not it has any capacity.  A high ratio after many removals is a
+    /// sign that compacting the `Tree` would free up memory.
     ///
     /// ```
-    /// use nary_tree::tree::Tree;
-    ///
-    /// let mut tree = Tree::new();
-    /// tree.set_root(1);
+    /// use nary_tree::behaviors::RemoveBehavior::DropChildren;
+    /// use nary_tree::tree::TreeBuilder;
     ///
-    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let mut tree = TreeBuilder::new().with_root(0).with_capacity(4).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let one_id = root.append2(1);
+    /// root.append2(2);
+    /// root.append2(3);
+    /// assert_eq!(tree.fragmentation_ratio(), 0.0);
     ///
-    /// assert_eq!(tree.get(root_id).unwrap().data(), &1);
+    /// tree.remove(one_id, DropChildren);
+    /// assert_eq!(tree.fragmentation_ratio(), 0.25);
     /// ```
     ///
-    pub fn root_id(&self) -> Option<NodeId> {
-        self.root_id
+    pub fn fragmentation_ratio(&self) -> f64 {
+        let capacity = self.core_tree.capacity();
+        if self.core_tree.len() == 0 {
+            return 0.0;
+        }
+        (capacity - self.core_tree.len()) as f64 / capacity as f64
     }
 
     ///
-    /// Returns a `NodeRef` pointing to the root `Node` of the `Tree`.
+    /// Returns the `TreeStats` of the `Tree`: its node count, capacity, height, leaf count and
+    /// free slots, gathered in a single traversal.
     ///
     /// ```
-    /// use nary_tree::tree::Tree;
+    /// use nary_tree::behaviors::RemoveBehavior::DropChildren;
+    /// use nary_tree::tree::TreeBuilder;
     ///
-    /// let mut tree = Tree::new();
-    /// tree.set_root(1);
+    /// let mut tree = TreeBuilder::new().with_root(0).with_capacity(8).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let one_id = root.append2(1);
+    /// root.append(2).append2(3);
     ///
-    /// let root = tree.root().expect("root doesn't exist?");
+    /// let stats = tree.stats();
+    /// assert_eq!(stats.node_count, 4);
+    /// assert_eq!(stats.capacity, 8);
+    /// assert_eq!(stats.height, 2);
+    /// assert_eq!(stats.leaf_count, 2);
+    /// assert_eq!(stats.free_slots, 4);
+    /// assert!(!stats.recommends_compaction());
     ///
-    /// assert_eq!(root.data(), &1);
+    /// tree.remove(one_id, DropChildren);
+    /// assert!(tree.stats().recommends_compaction());
     /// ```
     ///
-    pub fn root(&self) -> Option<NodeRef<T>> {
-        self.root_id.map(|id| self.new_node_ref(id))
-    }
+    pub fn stats(&self) -> TreeStats {
+        let mut height = 0;
+        let mut leaf_count = 0;
+        if let Some(root) = self.root() {
+            for (node, depth, _) in root.traverse_pre_order_positioned() {
+                height = height.max(depth);
+                if node.first_child().is_none() {
+                    leaf_count += 1;
+                }
+            }
+        }
+
+        let node_count = self.core_tree.len();
+        let capacity = self.core_tree.capacity();
+        TreeStats {
+            node_count,
+            capacity,
+            height,
+            leaf_count,
+            free_slots: capacity - node_count,
+        }
+    }
+
+    ///
+    /// Returns an estimate of the number of bytes used by the `Tree`: the `Tree` itself plus
+    /// storage for `capacity()` `Node`s, whether they are in use or not, and the room taken by its
+    /// keys, its dirty marks and its cached pre-order (see `cached_pre_order`).
+    ///
+    /// This is an approximation meant for diagnostics.  It doesn't include memory owned by the
+    /// data itself (such as a `String`'s buffer) nor the allocator's own overhead, and the size
+    /// of a storage slot is inferred from its layout rather than measured.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let small = TreeBuilder::<u64>::new().with_capacity(10).build();
+    /// let large = TreeBuilder::<u64>::new().with_capacity(100).build();
+    ///
+    /// assert!(small.memory_footprint() >= 10 * std::mem::size_of::<u64>());
+    /// assert!(large.memory_footprint() > small.memory_footprint());
+    /// ```
+    ///
+    pub fn memory_footprint(&self) -> usize {
+        std::mem::size_of::<Tree<T, E>>() + self.core_tree.memory_footprint()
+    }
+
+    ///
+    /// Returns a counter that changes every time the structure of the `Tree` changes: whenever a
+    /// `Node` is inserted, removed or moved, or the `Tree` is compacted.  Comparing it against a
+    /// previously seen value is a cheap way to tell whether anything derived from the shape of the
+    /// `Tree` needs to be recomputed.
+    ///
+    /// Changes to the data alone don't affect it; see `data_version` for those.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let version = tree.version();
+    ///
+    /// *tree.root_mut().unwrap().data() = 2;
+    /// assert_eq!(tree.version(), version);
+    ///
+    /// tree.root_mut().unwrap().append2(3);
+    /// assert_ne!(tree.version(), version);
+    /// ```
+    ///
+    pub fn version(&self) -> u64 {
+        self.core_tree.version()
+    }
+
+    ///
+    /// Returns a counter that changes every time mutable access to the data of any `Node` is
+    /// handed out, e.g. through `NodeMut::data`, `get_many_mut` or `replace_root_data`.  Since
+    /// the `Tree` can't tell whether the data was actually written to, it changes even if it
+    /// wasn't.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let data_version = tree.data_version();
+    ///
+    /// tree.root_mut().unwrap().append2(2);
+    /// assert_eq!(tree.data_version(), data_version);
+    ///
+    /// *tree.root_mut().unwrap().data() = 3;
+    /// assert_ne!(tree.data_version(), data_version);
+    /// ```
+    ///
+    pub fn data_version(&self) -> u64 {
+        self.core_tree.data_version
+    }
+
+    ///
+    /// Returns a counter that goes up every time a compaction (`compact_internal`, or `compact`)
+    /// moves `Node`s around, and so changes some of their `NodeId`s.  It starts at 0 and stays
+    /// put through every other change.
+    ///
+    /// ```
+    /// use nary_tree::behaviors::RemoveBehavior::DropChildren;
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let one_id = tree.root_mut().unwrap().append2(1);
+    /// tree.root_mut().unwrap().append2(2);
+    /// assert_eq!(tree.compaction_generation(), 0);
+    ///
+    /// // nothing to move yet
+    /// tree.compact_internal();
+    /// assert_eq!(tree.compaction_generation(), 0);
+    ///
+    /// tree.remove(one_id, DropChildren);
+    /// tree.compact_internal();
+    /// assert_eq!(tree.compaction_generation(), 1);
+    /// ```
+    ///
+    pub fn compaction_generation(&self) -> u64 {
+        self.core_tree.compaction_generation()
+    }
+
+    ///
+    /// Returns `true` if `node_id` points to a `Node` of this `Tree` and was handed out since the
+    /// last compaction that moved `Node`s around (see `compaction_generation`).  `NodeId`s kept
+    /// from before a compaction get `false` even if their `Node` happened to stay in place, and
+    /// should be looked up again (e.g. by key or by traversal) before being used.
+    ///
+    /// Every `NodeId` handed out by the `Tree` after the compaction is current, including the
+    /// ones read from a `Node`'s relatives.
+    ///
+    /// Only available with the `experimental` feature, which is what makes `NodeId`s carry the
+    /// generation they were handed out under.
+    ///
+    /// ```
+    /// use nary_tree::behaviors::RemoveBehavior::DropChildren;
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let root_id = tree.root_id().unwrap();
+    /// let one_id = tree.root_mut().unwrap().append2(1);
+    /// let two_id = tree.root_mut().unwrap().append2(2);
+    /// assert!(tree.is_current(root_id) && tree.is_current(two_id));
+    ///
+    /// tree.remove(one_id, DropChildren);
+    /// assert!(!tree.is_current(one_id));
+    ///
+    /// tree.compact_internal();
+    /// assert!(!tree.is_current(root_id));
+    /// assert!(!tree.is_current(two_id));
+    ///
+    /// let two_id = tree.root().unwrap().first_child().unwrap().node_id();
+    /// assert!(tree.is_current(two_id));
+    /// assert!(tree.is_current(tree.root_id().unwrap()));
+    /// ```
+    ///
+    #[cfg(feature = "experimental")]
+    pub fn is_current(&self, node_id: NodeId) -> bool {
+        node_id.compaction == self.core_tree.compaction_generation() && self.get(node_id).is_some()
+    }
+
+    ///
+    /// Returns the `NodeId`s of the `Node`s marked dirty by `NodeMut::data_mut_tracked` since the
+    /// last call to `clear_dirty`, in no particular order.  A `Node` is marked when its own data
+    /// is accessed that way or when one of its descendants' is.  Removed `Node`s are dropped from
+    /// the list.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().unwrap().append2(2);
+    /// let three_id = tree.get_mut(two_id).unwrap().append2(3);
+    /// assert!(tree.dirty_nodes().is_empty());
+    ///
+    /// *tree.get_mut(two_id).unwrap().data_mut_tracked() = 20;
+    /// assert_eq!(tree.dirty_nodes().len(), 2);
+    /// assert!(!tree.dirty_nodes().contains(&three_id));
+    /// ```
+    ///
+    pub fn dirty_nodes(&self) -> Vec<NodeId> {
+        self.core_tree.dirty.iter().copied().collect()
+    }
+
+    ///
+    /// Unmarks every dirty `Node`.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// *tree.root_mut().unwrap().data_mut_tracked() = 2;
+    /// assert_eq!(tree.dirty_nodes().len(), 1);
+    ///
+    /// tree.clear_dirty();
+    /// assert!(tree.dirty_nodes().is_empty());
+    /// ```
+    ///
+    pub fn clear_dirty(&mut self) {
+        self.core_tree.dirty.clear();
+    }
+
+    ///
+    /// Returns the `NodeId`s of the `Node`s reachable from the root in depth-first pre-order.
+    ///
+    /// The order is computed on the first call and cached until the structure of the `Tree`
+    /// changes (see `version`), so that calling it over and over on an unchanged `Tree` is cheap.
+    /// Changes to the data alone keep the cache.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let two_id = tree.root_mut().unwrap().append2(2);
+    /// assert_eq!(tree.cached_pre_order(), &[root_id, two_id]);
+    ///
+    /// let three_id = tree.get_mut(two_id).unwrap().append2(3);
+    /// assert_eq!(tree.cached_pre_order(), &[root_id, two_id, three_id]);
+    /// ```
+    ///
+    pub fn cached_pre_order(&self) -> &[NodeId] {
+        self.core_tree.pre_order.get_or_init(|| {
+            self.root()
+                .into_iter()
+                .flat_map(|root| root.traverse_pre_order())
+                .map(|node| node.node_id())
+                .collect()
+        })
+    }
+
+    ///
+    /// Clears `buf` and fills it with the `NodeId`s of the `Node`s reachable from the root, in
+    /// depth-first pre-order.  The traversal follows the links between `Node`s and uses no other
+    /// storage, so reusing the same `buf` across calls avoids allocating once it is big enough.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let mut root = tree.root_mut().unwrap();
+    /// let two_id = root.append2(2);
+    /// let four_id = root.append2(4);
+    /// let three_id = tree.get_mut(two_id).unwrap().append2(3);
+    ///
+    /// let mut buf = Vec::new();
+    /// tree.traverse_pre_order_into(&mut buf);
+    /// assert_eq!(buf, vec![root_id, two_id, three_id, four_id]);
+    /// ```
+    ///
+    pub fn traverse_pre_order_into(&self, buf: &mut Vec<NodeId>) {
+        buf.clear();
+        let mut next = self.root_id;
+        while let Some(node_id) = next {
+            buf.push(node_id);
+            let relatives = self.get_node_relatives(node_id);
+            next = relatives.first_child.or_else(|| {
+                // climb to the closest node with a next sibling
+                let mut current = Some(node_id);
+                while let Some(id) = current {
+                    let relatives = self.get_node_relatives(id);
+                    if relatives.next_sibling.is_some() {
+                        return relatives.next_sibling;
+                    }
+                    current = relatives.parent;
+                }
+                None
+            });
+        }
+    }
+
+    ///
+    /// Returns an `Iterator` over the `Tree` in depth-first pre-order, yielding each `Node` along
+    /// with the range of pre-order indices its sub-tree covers: `start` is the `Node`'s own index
+    /// and `end` is one past the index of its last descendant, so `end - start` is the size of
+    /// its sub-tree.  The ranges are worked out in a single traversal before the first item is
+    /// yielded.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let two_id = root.append2(2);
+    /// root.append2(5);
+    /// let mut two = tree.get_mut(two_id).unwrap();
+    /// two.append2(3);
+    /// two.append2(4);
+    ///
+    /// let ranges = tree.traverse_with_ranges()
+    ///     .map(|(node, start, end)| (*node.data(), start, end))
+    ///     .collect::<Vec<(i32, usize, usize)>>();
+    /// assert_eq!(ranges, vec![(1, 0, 5), (2, 1, 4), (3, 2, 3), (4, 3, 4), (5, 4, 5)]);
+    /// ```
+    ///
+    pub fn traverse_with_ranges(&self) -> impl Iterator<Item = (NodeRef<'_, T, E>, usize, usize)> {
+        let mut ranges: Vec<(NodeId, usize, usize)> = Vec::new();
+        // the positions in `ranges` of the nodes whose sub-trees are still being walked, with
+        // their depths
+        let mut open: Vec<(usize, usize)> = Vec::new();
+        if let Some(root) = self.root() {
+            for (node, depth, _) in root.traverse_pre_order_positioned() {
+                let start = ranges.len();
+                while let Some(&(position, open_depth)) = open.last() {
+                    if open_depth < depth {
+                        break;
+                    }
+                    ranges[position].2 = start;
+                    open.pop();
+                }
+                open.push((start, depth));
+                ranges.push((node.node_id(), start, start));
+            }
+        }
+        let len = ranges.len();
+        for (position, _) in open {
+            ranges[position].2 = len;
+        }
+
+        ranges
+            .into_iter()
+            .map(move |(node_id, start, end)| (self.new_node_ref(node_id), start, end))
+    }
+
+    ///
+    /// Returns the `NodeId` of the root node of the `Tree`.
+    ///
+    /// ```
+    /// use nary_tree::tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// tree.set_root(1);
+    ///
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    ///
+    /// assert_eq!(tree.get(root_id).unwrap().data(), &1);
+    /// ```
+    ///
+    pub fn root_id(&self) -> Option<NodeId> {
+        self.root_id
+    }
+
+    ///
+    /// Returns a `TreeView` of the `Tree`, a handle that can only read and traverse it.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let tree = TreeBuilder::new().with_root(1).build();
+    /// let view = tree.as_view();
+    ///
+    /// assert_eq!(view.root().unwrap().data(), &1);
+    /// ```
+    ///
+    pub fn as_view(&self) -> TreeView<'_, T, E> {
+        TreeView::new(self)
+    }
+
+    ///
+    /// Returns `true` if the `Tree` has no root.  `Node`s orphaned by removing the root with
+    /// `OrphanChildren` don't count.
+    ///
+    /// ```
+    /// use nary_tree::tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// assert!(tree.is_empty());
+    ///
+    /// tree.set_root(1);
+    /// assert!(!tree.is_empty());
+    /// ```
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.root_id.is_none()
+    }
+
+    ///
+    /// Returns `true` if the `Tree` is made of its root alone.
+    ///
+    /// ```
+    /// use nary_tree::tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// assert!(!tree.is_single_node());
+    ///
+    /// tree.set_root(1);
+    /// assert!(tree.is_single_node());
+    ///
+    /// tree.root_mut().unwrap().append2(2);
+    /// assert!(!tree.is_single_node());
+    /// ```
+    ///
+    pub fn is_single_node(&self) -> bool {
+        self.root_id
+            .is_some_and(|root_id| self.get_node_relatives(root_id).first_child.is_none())
+    }
+
+    ///
+    /// Returns a `NodeRef` pointing to the root `Node` of the `Tree`.
+    ///
+    /// ```
+    /// use nary_tree::tree::Tree;
+    ///
+    /// let mut tree = Tree::new();
+    /// tree.set_root(1);
+    ///
+    /// let root = tree.root().expect("root doesn't exist?");
+    ///
+    /// assert_eq!(root.data(), &1);
+    /// ```
+    ///
+    pub fn root(&self) -> Option<NodeRef<'_, T, E>> {
+        self.root_id.map(|id| self.new_node_ref(id))
+    }
 
     ///
     /// Returns a `NodeMut` pointing to the root `Node` of the `Tree`.
@@ -219,7 +1178,7 @@ impl<T> Tree<T> {
     /// assert_eq!(root.data(), &mut 2);
     /// ```
     ///
-    pub fn root_mut(&mut self) -> Option<NodeMut<T>> {
+    pub fn root_mut(&mut self) -> Option<NodeMut<'_, T, E>> {
         self.root_id.map(move |id| self.new_node_mut(id))
     }
 
@@ -242,7 +1201,7 @@ impl<T> Tree<T> {
     /// assert_eq!(root.data(), &1);
     /// ```
     ///
-    pub fn get(&self, node_id: NodeId) -> Option<NodeRef<T>> {
+    pub fn get(&self, node_id: NodeId) -> Option<NodeRef<'_, T, E>> {
         let _ = self.core_tree.get(node_id)?;
         Some(self.new_node_ref(node_id))
     }
@@ -268,724 +1227,5789 @@ impl<T> Tree<T> {
     /// assert_eq!(root.data(), &mut 2);
     /// ```
     ///
-    pub fn get_mut(&mut self, node_id: NodeId) -> Option<NodeMut<T>> {
+    pub fn get_mut(&mut self, node_id: NodeId) -> Option<NodeMut<'_, T, E>> {
         let _ = self.core_tree.get_mut(node_id)?;
         Some(self.new_node_mut(node_id))
     }
 
     ///
-    /// Remove a `Node` by its `NodeId` and return the data that it contained.
-    /// Returns a `Some`-value if the `Node` exists; returns a `None`-value otherwise.
+    /// Returns mutable references to the data of every `Node` on the path from `ancestor` down
+    /// to `descendant` (both included), ordered from `ancestor` to `descendant`.
     ///
-    /// Children of the removed `Node` can either be dropped with `DropChildren` or orphaned with
-    /// `OrphanChildren`.
+    /// Returns a `None`-value if either `NodeId` points to nothing (or belongs to a different
+    /// `Tree`) or if `ancestor` is not an ancestor of `descendant`.  If both `NodeId`s are the
+    /// same, the returned `Vec` contains only that `Node`'s data.
     ///
     /// ```
     /// use nary_tree::tree::TreeBuilder;
-    /// use nary_tree::behaviors::RemoveBehavior::*;
     ///
     /// let mut tree = TreeBuilder::new().with_root(1).build();
-    /// let two_id = {
-    ///     let mut root = tree.root_mut().expect("root doesn't exist?");
-    ///     let two_id = root.append2(2);
-    ///     root.append2(3);
-    ///     two_id
-    /// };
-    ///
-    /// let two = tree.remove(two_id, DropChildren);
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let leaf_id = tree.root_mut().unwrap()
+    ///     .append(2)
+    ///     .append(3)
+    ///     .append(4)
+    ///     .node_id();
     ///
-    /// assert!(two.is_some());
-    /// assert_eq!(two.unwrap(), 2);
+    /// let mut total = 0;
+    /// for data in tree.get_path_data_mut(root_id, leaf_id).unwrap() {
+    ///     total += *data;
+    ///     *data = total;
+    /// }
     ///
-    /// let root = tree.root().expect("root doesn't exist?");
-    /// assert!(root.first_child().is_some());
-    /// assert_eq!(root.first_child().unwrap().data(), &mut 3);
+    /// let values = tree.root().unwrap().traverse_pre_order()
+    ///     .map(|node_ref| *node_ref.data()).collect::<Vec<i32>>();
+    /// assert_eq!(values, vec![1, 3, 6, 10]);
     ///
-    /// assert!(root.last_child().is_some());
-    /// assert_eq!(root.last_child().unwrap().data(), &mut 3);
+    /// assert!(tree.get_path_data_mut(leaf_id, root_id).is_none());
     /// ```
     ///
-    pub fn remove(&mut self, node_id: NodeId, behavior: RemoveBehavior) -> Option<T> {
-        if let Some(node) = self.get_node(node_id) {
-            let Relatives {
-                parent,
-                prev_sibling,
-                next_sibling,
-                ..
-            } = node.relatives;
-
-            let (is_first_child, is_last_child) = self.is_node_first_last_child(node_id);
-
-            if is_first_child {
-                // parent first child = my next sibling
-                self.set_first_child(parent.expect("parent must exist"), next_sibling);
-            }
-            if is_last_child {
-                // parent last child = my prev sibling
-                self.set_last_child(parent.expect("parent must exist"), prev_sibling);
-            }
-            if let Some(prev) = prev_sibling {
-                self.set_next_sibling(prev, next_sibling);
-            }
-            if let Some(next) = next_sibling {
-                self.set_prev_sibling(next, prev_sibling);
-            }
-
-            match behavior {
-                RemoveBehavior::DropChildren => self.drop_children(node_id),
-                RemoveBehavior::OrphanChildren => self.orphan_children(node_id),
-            };
-            if self.root_id == Some(node_id) {
-                self.root_id = None;
-            }
-            self.core_tree.remove(node_id)
-        } else {
-            None
+    pub fn get_path_data_mut(
+        &mut self,
+        ancestor: NodeId,
+        descendant: NodeId,
+    ) -> Option<Vec<&mut T>> {
+        let _ = self.get_node(ancestor)?;
+        let mut path = vec![descendant];
+        let mut ancestors = self.get(descendant)?.ancestors();
+        while path.last() != Some(&ancestor) {
+            path.push(ancestors.next()?.node_id());
         }
+        path.reverse();
+
+        let nodes = self.core_tree.get_many_mut(&path)?;
+        Some(nodes.into_iter().map(|node| &mut node.data).collect())
     }
 
-    /// Shrink the capacity of the nary_tree as much as possible without invalidating
-    /// keys.
     ///
-    /// Because values cannot be moved to a different index, the slab cannot
-    /// shrink past any stored values.
-    /// It will drop down as close as possible to the length but the allocator may
-    /// still inform the underlying vector that there is space for a few more elements.
-    ///
-    /// This function can take O(n) time even when the capacity cannot be reduced
-    /// or the allocation is shrunk in place. Repeated calls run in O(1) though.
+    /// Returns mutable references to the data of every ancestor of the `Node` identified by
+    /// `node_id`, ordered from its parent up to the root (the `Node` itself is not included).
+    /// The root, or an orphaned `Node`, gets an empty `Vec`.
     ///
-    /// # Examples
+    /// Returns a `None`-value if `node_id` points to nothing (or belongs to a different `Tree`).
     ///
     /// ```
-    /// # use nary_tree::*;
-    /// let mut tree = TreeBuilder::new().with_root(0).with_capacity(10).build();
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root((1, false)).build();
     /// let mut root = tree.root_mut().unwrap();
+    /// let two_id = root.append2((2, false));
+    /// root.append2((3, false));
+    /// let four_id = tree.get_mut(two_id).unwrap().append2((4, false));
     ///
-    /// for i in 1..4 {
-    ///     root.append2(i);
+    /// for (_, active) in tree.ancestors_data_mut(four_id).unwrap() {
+    ///     *active = true;
     /// }
     ///
-    /// tree.shrink_to_fit();
-    /// assert!(tree.capacity() >= 4 && tree.capacity() < 10);
+    /// let active = tree.root().unwrap().traverse_pre_order()
+    ///     .filter(|node_ref| node_ref.data().1)
+    ///     .map(|node_ref| node_ref.data().0).collect::<Vec<i32>>();
+    /// assert_eq!(active, vec![1, 2]);
     /// ```
     ///
-    /// The nary_tree cannot shrink past the last present value even if previous
-    /// values are removed:
+    pub fn ancestors_data_mut(&mut self, node_id: NodeId) -> Option<Vec<&mut T>> {
+        let ancestors: Vec<NodeId> = self
+            .get(node_id)?
+            .ancestors()
+            .map(|node| node.node_id())
+            .collect();
+
+        let nodes = self.core_tree.get_many_mut(&ancestors)?;
+        Some(nodes.into_iter().map(|node| &mut node.data).collect())
+    }
+
+    ///
+    /// Walks from the `Node` identified by `from` up to the root, calling
+    /// `update(parent_data, child_data)` for each `Node` on the way and its parent, so that a
+    /// change can be propagated upwards.  Each call sees the data as left by the previous one.
+    ///
+    /// Does nothing if `from` points to nothing (or belongs to a different `Tree`) or has no
+    /// parent.  For an orphaned `Node`, the walk stops at the top of its sub-tree.
     ///
     /// ```
-    /// # use nary_tree::*;
-    /// let mut tree = TreeBuilder::new().with_root(0).with_capacity(10).build();
-    /// let mut root = tree.root_mut().unwrap();
+    /// use nary_tree::tree::TreeBuilder;
     ///
-    /// let mut node_ids = vec![root.node_id()];
-    /// for i in 1..4 {
-    ///     node_ids.push(root.append2(i));
-    /// }
+    /// // each node caches the sum of its sub-tree
+    /// let mut tree = TreeBuilder::new().with_root(3).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// root.append2(1);
+    /// let two_id = root.append2(2);
     ///
-    /// tree.remove(node_ids[1], nary_tree::RemoveBehavior::OrphanChildren);
-    /// tree.remove(node_ids[3], nary_tree::RemoveBehavior::OrphanChildren);
+    /// let three_id = tree.get_mut(two_id).unwrap().append2(3);
+    /// tree.bubble_up(three_id, |parent_sum, _| *parent_sum += 3);
     ///
-    /// tree.shrink_to_fit();
-    /// assert!(tree.capacity() >= 3 && tree.capacity() < 10);
+    /// assert_eq!(tree.get(two_id).unwrap().data(), &5);
+    /// assert_eq!(tree.root().unwrap().data(), &6);
     /// ```
-    pub fn shrink_to_fit(&mut self) {
-        self.core_tree.shrink_to_fit();
+    ///
+    pub fn bubble_up<F: FnMut(&mut T, &T)>(&mut self, from: NodeId, mut update: F) {
+        let path: Vec<NodeId> = match self.get(from) {
+            Some(node) => node
+                .ancestors()
+                .map(|ancestor| ancestor.node_id())
+                .collect(),
+            None => return,
+        };
+
+        let mut child_id = from;
+        for parent_id in path {
+            let [parent, child] = self
+                .get_many_mut([parent_id, child_id])
+                .expect("ancestors must exist");
+            update(parent, child);
+            child_id = parent_id;
+        }
     }
 
-    #[cfg(feature = "experimental")]
-    /// Reduce the capacity as much as possible by moving `Node`s from the back of the slab to
-    /// empty slots, updating the index for elements when necessary.
-    /// This will increase the generation of all moved `Node`s, making obsolete the `NodeId`s
-    /// pointing to the old index.
     ///
-    /// ```
-    /// # use nary_tree::*;
-    /// let mut tree = TreeBuilder::new().with_root(0).build();
-    /// let mut root = tree.root_mut().unwrap();
-    /// {
-    ///     let mut one = root.append(1);
-    ///     let mut two = one.append(2);
-    ///     two.append(3);
-    ///     two.append(4);
-    /// }
-    /// {
-    ///     let mut five = root.append(5);
-    ///     five.append(6).append(7);
-    ///     five.append(8);
-    /// }
+    /// Returns mutable references to the data of the `Node`s identified by each of `node_ids`,
+    /// in the same order, so that several `Node`s can be updated at once.
     ///
-    /// // 0
-    /// // ├── 1
-    /// // │   └── 2
-    /// // │       ├── 3
-    /// // │       └── 4
-    /// // ├── 5
-    /// // │   ├── 6
-    /// // │   │   └── 7
-    /// // │   └── 8
+    /// Returns a `None`-value if any of the `NodeId`s points to nothing (or belongs to a different
+    /// `Tree`), or if the same `NodeId` is given more than once.  Every `NodeId` is checked before
+    /// any reference is handed out.
     ///
-    /// let three_id = tree.find(&3).unwrap()[0];
-    /// let five_id = tree.find(&5).unwrap()[0];
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
     ///
-    /// tree.remove(three_id, RemoveBehavior::DropChildren);
-    /// tree.remove(five_id, RemoveBehavior::DropChildren);
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let two_id = tree.root_mut().unwrap().append2(2);
     ///
-    /// // 0
-    /// // └── 1
-    /// //     └── 2
-    /// //         └── 4
+    /// let [root, two] = tree.get_many_mut([root_id, two_id]).unwrap();
+    /// std::mem::swap(root, two);
+    /// assert_eq!(tree.root().unwrap().data(), &2);
     ///
-    /// let two = tree.get(tree.find(&2).unwrap()[0]).unwrap();
-    /// assert_eq!(two.first_child().unwrap().data(), &4);
+    /// assert!(tree.get_many_mut([two_id, two_id]).is_none());
+    /// ```
     ///
-    /// let four = tree.get(tree.find(&4).unwrap()[0]).unwrap();
-    /// assert!(four.prev_sibling().is_none());
-    /// assert_eq!(tree.root().unwrap().last_child().unwrap().data(), &1);
+    pub fn get_many_mut<const N: usize>(&mut self, node_ids: [NodeId; N]) -> Option<[&mut T; N]> {
+        let nodes = self.core_tree.get_disjoint_mut(node_ids)?;
+        Some(nodes.map(|node| &mut node.data))
+    }
+
     ///
-    /// assert!(tree.capacity() >= 9);
+    /// Inserts a new `Node` as the next sibling of the `Node` that `node_id` identifies and
+    /// returns the new `NodeId`.  Returns a `None`-value if `node_id` points to nothing (or
+    /// belongs to a different `Tree`) or if its `Node` has no parent (such as the root).
     ///
-    /// tree.compact();
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
     ///
-    /// let two = tree.get(tree.find(&2).unwrap()[0]).unwrap();
-    /// assert_eq!(two.first_child().unwrap().data(), &4);
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let two_id = tree.root_mut().unwrap().append2(2);
+    /// tree.root_mut().unwrap().append2(4);
     ///
-    /// let four = tree.get(tree.find(&4).unwrap()[0]).unwrap();
-    /// assert!(four.prev_sibling().is_none());
-    /// assert_eq!(tree.root().unwrap().last_child().unwrap().data(), &1);
+    /// let three_id = tree.insert_after(two_id, 3).unwrap();
     ///
-    /// assert!(tree.capacity() == 4);
+    /// assert_eq!(tree.get(three_id).unwrap().data(), &3);
+    /// assert_eq!(
+    ///     tree.root().unwrap().children().map(|child_ref| *child_ref.data())
+    ///         .collect::<Vec<i32>>(),
+    ///     vec![2, 3, 4]);
+    /// assert!(tree.insert_after(root_id, 5).is_none());
     /// ```
-    pub fn compact(&mut self) -> usize {
-        self.core_tree.compact()
-    }
-
-    pub(crate) fn get_node(&self, node_id: NodeId) -> Option<&Node<T>> {
-        self.core_tree.get(node_id)
-    }
+    ///
+    pub fn insert_after(&mut self, node_id: NodeId, data: T) -> Option<NodeId> {
+        let Relatives {
+            parent,
+            next_sibling,
+            ..
+        } = self.get_node(node_id)?.relatives;
+        let parent_id = parent?;
 
-    pub(crate) fn get_node_mut(&mut self, node_id: NodeId) -> Option<&mut Node<T>> {
-        self.core_tree.get_mut(node_id)
-    }
+        let new_id = self.core_tree.insert(data);
+        self.set_parent(new_id, Some(parent_id));
+        self.set_prev_sibling(new_id, Some(node_id));
+        self.set_next_sibling(new_id, next_sibling);
+        self.set_next_sibling(node_id, Some(new_id));
 
-    pub(crate) fn set_prev_siblings_next_sibling(
-        &mut self,
-        current_id: NodeId,
-        next_sibling: Option<NodeId>,
-    ) {
-        if let Some(prev_sibling_id) = self.get_node_prev_sibling_id(current_id) {
-            self.set_next_sibling(prev_sibling_id, next_sibling);
+        if let Some(next_id) = next_sibling {
+            self.set_prev_sibling(next_id, Some(new_id));
+        } else {
+            self.set_last_child(parent_id, Some(new_id));
         }
-    }
 
-    pub(crate) fn set_next_siblings_prev_sibling(
-        &mut self,
-        current_id: NodeId,
-        prev_sibling: Option<NodeId>,
-    ) {
-        if let Some(next_sibling_id) = self.get_node_next_sibling_id(current_id) {
-            self.set_prev_sibling(next_sibling_id, prev_sibling);
-        }
+        Some(new_id)
     }
 
-    pub(crate) fn set_parent(&mut self, node_id: NodeId, parent_id: Option<NodeId>) {
-        if let Some(node) = self.get_node_mut(node_id) {
-            node.relatives.parent = parent_id;
-        } else {
-            unreachable!()
-        }
-    }
+    ///
+    /// Inserts a new `Node` as the previous sibling of the `Node` that `node_id` identifies and
+    /// returns the new `NodeId`.  Returns a `None`-value if `node_id` points to nothing (or
+    /// belongs to a different `Tree`) or if its `Node` has no parent (such as the root).
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let three_id = tree.root_mut().unwrap().append2(3);
+    ///
+    /// let two_id = tree.insert_before(three_id, 2).unwrap();
+    ///
+    /// assert_eq!(tree.get(two_id).unwrap().data(), &2);
+    /// assert_eq!(
+    ///     tree.root().unwrap().children().map(|child_ref| *child_ref.data())
+    ///         .collect::<Vec<i32>>(),
+    ///     vec![2, 3]);
+    /// assert!(tree.insert_before(root_id, 0).is_none());
+    /// ```
+    ///
+    pub fn insert_before(&mut self, node_id: NodeId, data: T) -> Option<NodeId> {
+        let Relatives {
+            parent,
+            prev_sibling,
+            ..
+        } = self.get_node(node_id)?.relatives;
+        let parent_id = parent?;
 
-    pub(crate) fn set_prev_sibling(&mut self, node_id: NodeId, prev_sibling: Option<NodeId>) {
-        if let Some(node) = self.get_node_mut(node_id) {
-            node.relatives.prev_sibling = prev_sibling;
-        } else {
-            unreachable!()
-        }
-    }
+        let new_id = self.core_tree.insert(data);
+        self.set_parent(new_id, Some(parent_id));
+        self.set_prev_sibling(new_id, prev_sibling);
+        self.set_next_sibling(new_id, Some(node_id));
+        self.set_prev_sibling(node_id, Some(new_id));
 
-    pub(crate) fn set_next_sibling(&mut self, node_id: NodeId, next_sibling: Option<NodeId>) {
-        if let Some(node) = self.get_node_mut(node_id) {
-            node.relatives.next_sibling = next_sibling;
+        if let Some(prev_id) = prev_sibling {
+            self.set_next_sibling(prev_id, Some(new_id));
         } else {
-            unreachable!()
+            self.set_first_child(parent_id, Some(new_id));
         }
-    }
 
-    pub(crate) fn set_first_child(&mut self, node_id: NodeId, first_child: Option<NodeId>) {
-        if let Some(node) = self.get_node_mut(node_id) {
-            node.relatives.first_child = first_child;
-        } else {
-            unreachable!()
-        }
+        Some(new_id)
     }
 
-    pub(crate) fn set_last_child(&mut self, node_id: NodeId, last_child: Option<NodeId>) {
-        if let Some(node) = self.get_node_mut(node_id) {
-            node.relatives.last_child = last_child;
-        } else {
-            unreachable!()
+    ///
+    /// Inserts a new `Node` on the edge between `parent` and its child `child`: the new `Node`
+    /// takes `child`'s place among the children of `parent`, and `child` (along with its
+    /// sub-tree) becomes the new `Node`'s only child.  Returns the new `NodeId`.
+    ///
+    /// Returns a `None`-value if either `NodeId` points to nothing (or belongs to a different
+    /// `Tree`) or if `child` isn't a child of `parent`.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append2(2);
+    /// let four_id = root.append2(4);
+    /// root.append2(5);
+    ///
+    /// let three_id = tree.insert_between(root_id, four_id, 3).unwrap();
+    ///
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// 1
+    /// ├── 2
+    /// ├── 3
+    /// │   └── 4
+    /// └── 5
+    /// ");
+    /// assert!(tree.insert_between(root_id, four_id, 6).is_none());
+    /// ```
+    ///
+    pub fn insert_between(&mut self, parent: NodeId, child: NodeId, data: T) -> Option<NodeId> {
+        self.get_node(parent)?;
+        let relatives = self.get_node(child)?.relatives;
+        if relatives.parent != Some(parent) {
+            return None;
         }
-    }
 
-    pub(crate) fn get_node_prev_sibling_id(&self, node_id: NodeId) -> Option<NodeId> {
-        if let Some(node) = self.get_node(node_id) {
-            node.relatives.prev_sibling
-        } else {
-            unreachable!()
-        }
+        self.unlink(child);
+        let new_id = self.core_tree.insert(data);
+        self.link_child(new_id, parent, relatives.prev_sibling);
+        self.link_child(child, new_id, None);
+        Some(new_id)
     }
 
-    pub(crate) fn get_node_next_sibling_id(&self, node_id: NodeId) -> Option<NodeId> {
-        if let Some(node) = self.get_node(node_id) {
-            node.relatives.next_sibling
-        } else {
-            unreachable!()
+    ///
+    /// Inserts a new `Node` above the `Node` that `node_id` identifies: the new `Node` takes its
+    /// place among its parent's children, and the `Node` (along with its sub-tree) becomes the
+    /// new `Node`'s only child.  If the `Node` is the root, the new `Node` becomes the root (as
+    /// with `set_root`).  Returns the new `NodeId`.
+    ///
+    /// Returns a `None`-value if `node_id` points to nothing (or belongs to a different `Tree`).
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append2(2);
+    /// let four_id = root.append2(4);
+    /// root.append2(5);
+    ///
+    /// tree.insert_parent(four_id, 3).unwrap();
+    /// let zero_id = tree.insert_parent(root_id, 0).unwrap();
+    ///
+    /// assert_eq!(tree.root_id(), Some(zero_id));
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// 0
+    /// └── 1
+    ///     ├── 2
+    ///     ├── 3
+    ///     │   └── 4
+    ///     └── 5
+    /// ");
+    /// ```
+    ///
+    pub fn insert_parent(&mut self, node_id: NodeId, data: T) -> Option<NodeId> {
+        let parent = self.get_node(node_id)?.relatives.parent;
+        match parent {
+            Some(parent_id) => self.insert_between(parent_id, node_id, data),
+            None if self.root_id == Some(node_id) => Some(self.set_root(data)),
+            None => {
+                let new_id = self.core_tree.insert(data);
+                self.link_child(node_id, new_id, None);
+                Some(new_id)
+            }
         }
     }
 
-    pub(crate) fn get_node_relatives(&self, node_id: NodeId) -> Relatives {
-        if let Some(node) = self.get_node(node_id) {
-            node.relatives
-        } else {
-            unreachable!()
+    ///
+    /// Takes the `Node` identified by `node_id` and its sub-tree out of the `Tree` and puts the
+    /// `Node`s of `replacement` in their place: the root of `replacement` takes the `Node`'s spot
+    /// among its parent's children (or becomes the root, if the `Node` was the root).  Returns
+    /// the `Node`s taken out as a `Tree` of their own, rooted at the `Node`.
+    ///
+    /// Both sets of `Node`s are moved, not copied, and get new `NodeId`s: the grafted ones from
+    /// this `Tree`, and the returned ones from the new `Tree`.  If `replacement` has no root, the
+    /// sub-tree is taken out with nothing in its place.
+    ///
+    /// Returns a `None`-value (and changes nothing but dropping `replacement`) if `node_id`
+    /// points to nothing (or belongs to a different `Tree`), or if its `Node` is an orphan:
+    /// neither the root nor anyone's child, so there is no place to put `replacement` in.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("root").build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append2("a");
+    /// let branch_id = root.append2("branch");
+    /// root.append2("z");
+    /// tree.get_mut(branch_id).unwrap().append2("leaf");
+    ///
+    /// let collapsed = TreeBuilder::new().with_root("branch (1 hidden)").build();
+    /// let branch = tree.replace_subtree(branch_id, collapsed).unwrap();
+    ///
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// root
+    /// ├── a
+    /// ├── branch (1 hidden)
+    /// └── z
+    /// ");
+    ///
+    /// let mut s = String::new();
+    /// branch.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// branch
+    /// └── leaf
+    /// ");
+    /// ```
+    ///
+    pub fn replace_subtree(
+        &mut self,
+        node_id: NodeId,
+        replacement: Tree<T, E>,
+    ) -> Option<Tree<T, E>> {
+        let Relatives {
+            parent,
+            prev_sibling,
+            ..
+        } = self.get_node(node_id)?.relatives;
+        let was_root = self.root_id == Some(node_id);
+        if parent.is_none() && !was_root {
+            return None;
         }
-    }
-
-    fn drop_children(&mut self, node_id: NodeId) {
-        let sub_tree_ids: Vec<NodeId> = self
-            .get(node_id)
-            .expect("node must exist")
-            .traverse_level_order()
-            .skip(1) // skip the "root" of the sub-tree, which is the "current" node
-            .map(|node_ref| node_ref.node_id())
-            .collect();
+        let edge = self.get_node_mut(node_id).and_then(|node| node.edge.take());
 
-        for id in sub_tree_ids {
-            self.core_tree.remove(id);
+        self.unlink(node_id);
+        if was_root {
+            self.root_id = None;
         }
-    }
-
-    fn orphan_children(&mut self, node_id: NodeId) {
-        let child_ids: Vec<NodeId> = self
-            .get(node_id)
-            .expect("node must exist")
-            .children()
-            .map(|node_ref| node_ref.node_id())
-            .collect();
+        let mut removed = TreeBuilder::new().build_with_edges();
+        removed.root_id = Some(removed.adopt_from(self, node_id));
 
-        for id in child_ids {
-            self.set_parent(id, None);
+        if let Some(new_id) = self.adopt(replacement) {
+            self.get_node_mut(new_id).expect("node must exist").edge = edge;
+            match parent {
+                Some(parent_id) => self.link_child(new_id, parent_id, prev_sibling),
+                None => self.root_id = Some(new_id),
+            }
         }
+        Some(removed)
     }
 
-    fn new_node_ref(&self, node_id: NodeId) -> NodeRef<T> {
-        NodeRef::new(node_id, self)
-    }
-
-    fn new_node_mut(&mut self, node_id: NodeId) -> NodeMut<T> {
-        NodeMut::new(node_id, self)
-    }
-
-    fn is_node_first_last_child(&self, node_id: NodeId) -> (bool, bool) {
-        if let Some(node) = self.get_node(node_id) {
-            node.relatives
-                .parent
-                .and_then(|parent_id| self.get_node(parent_id))
-                .map(|parent| {
-                    let Relatives {
-                        first_child: first,
-                        last_child: last,
-                        ..
-                    } = parent.relatives;
-                    (
-                        first.map(|child_id| child_id == node_id).unwrap_or(false),
-                        last.map(|child_id| child_id == node_id).unwrap_or(false),
-                    )
-                })
-                .unwrap_or((false, false))
-        } else {
-            (false, false)
-        }
-    }
-}
-
-impl<T: PartialEq> Tree<T> {
-    /// Find all the `Node`s that contain data and return a `Some`-Vec of their `NodeId`s
-    /// or `None` if none found.
+    ///
+    /// Moves each of the `Node`s identified by `node_ids` (along with its sub-tree) under the
+    /// `Node` identified by `new_parent`, appending them as its last children in the given order.
+    ///
+    /// Nothing is moved if any of the moves is invalid: a `NodeId` that points to nothing (or
+    /// belongs to a different `Tree`), the root, or a `Node` that `new_parent` is part of the
+    /// sub-tree of (including `new_parent` itself), as moving it would create a cycle.
+    ///
     /// ```
-    /// # use nary_tree::*;
+    /// use nary_tree::tree::{MoveError, TreeBuilder};
+    ///
     /// let mut tree = TreeBuilder::new().with_root(0).build();
-    /// let mut root = tree.root_mut().unwrap();
-    /// let mut root = {
-    ///     let one = root.append(1);
-    ///     let mut two = one.append(2);
-    ///     two.append2(3);
-    ///     two.append2(4);
-    ///     two.parent().unwrap().parent().unwrap()
-    /// };
-    /// let root = {
-    ///     let five = root.append(5);
-    ///     let mut five = five.append(6).append(7).parent().unwrap().parent().unwrap();
-    ///     five.append2(8);
-    ///     five.parent().unwrap()
-    /// };
-    /// root.append(9);
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let one_id = root.append2(1);
+    /// let two_id = root.append2(2);
+    /// let three_id = root.append2(3);
     ///
-    /// // 0
-    /// // ├── 1
-    /// // │   └── 2
-    /// // │       ├── 3
-    /// // │       └── 4
-    /// // ├── 5
-    /// // │   ├── 6
-    /// // │   │   └── 7
-    /// // │   └── 8
-    /// // └── 9
+    /// assert_eq!(tree.reparent_many(&[three_id, two_id], one_id), Ok(()));
     ///
-    /// let matches = tree.find(&6).unwrap();
-    /// assert_eq!(matches.len(), 1);
-    /// assert_eq!(tree.get(matches[0]).unwrap().data(), &6);
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// 0
+    /// └── 1
+    ///     ├── 3
+    ///     └── 2
+    /// ");
+    ///
+    /// assert_eq!(
+    ///     tree.reparent_many(&[one_id], two_id),
+    ///     Err(MoveError::WouldCreateCycle(one_id))
+    /// );
     /// ```
-    pub fn find(&self, data: &T) -> Option<Vec<NodeId>> {
-        let mut matches = Vec::with_capacity(1);
-        if let Some(root) = self.root() {
-            for node in root.traverse_level_order() {
-                if node.data() == data {
-                    matches.push(node.node_id());
-                }
+    ///
+    pub fn reparent_many(
+        &mut self,
+        node_ids: &[NodeId],
+        new_parent: NodeId,
+    ) -> Result<(), MoveError> {
+        let parent = self
+            .get(new_parent)
+            .ok_or(MoveError::InvalidNodeId(new_parent))?;
+        let mut ancestors: Vec<NodeId> = parent.ancestors().map(|node| node.node_id()).collect();
+        ancestors.push(new_parent);
+
+        for &node_id in node_ids {
+            if self.get_node(node_id).is_none() {
+                return Err(MoveError::InvalidNodeId(node_id));
+            }
+            if self.root_id == Some(node_id) {
+                return Err(MoveError::CannotMoveRoot);
+            }
+            if ancestors.contains(&node_id) {
+                return Err(MoveError::WouldCreateCycle(node_id));
             }
         }
-        if matches.is_empty() {
-            None
-        } else {
-            Some(matches)
+
+        for &node_id in node_ids {
+            self.unlink(node_id);
+            let last_child = self.get_node_relatives(new_parent).last_child;
+            self.link_child(node_id, new_parent, last_child);
         }
+        Ok(())
     }
-}
 
-impl<T> Default for Tree<T> {
-    fn default() -> Self {
-        TreeBuilder::new().build()
+    ///
+    /// Moves the `Node` identified by `node_id` one position up among its siblings, swapping it
+    /// with its previous sibling.  Returns `false` (and changes nothing) if it is already the
+    /// first child, has no parent, or if `node_id` points to nothing (or belongs to a different
+    /// `Tree`).
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let one_id = root.append2(1);
+    /// let two_id = root.append2(2);
+    ///
+    /// assert!(tree.move_up(two_id));
+    /// assert!(!tree.move_up(two_id));
+    /// assert_eq!(tree.root().unwrap().first_child().unwrap().node_id(), two_id);
+    /// assert_eq!(tree.root().unwrap().last_child().unwrap().node_id(), one_id);
+    /// ```
+    ///
+    pub fn move_up(&mut self, node_id: NodeId) -> bool {
+        self.get_mut(node_id)
+            .is_some_and(|mut node| node.swap_prev_sibling())
     }
-}
 
-impl<T: std::fmt::Display> Tree<T> {
-    /// Write formatted tree representation and nodes with debug formatting.
     ///
-    /// Example:
+    /// Moves the `Node` identified by `node_id` one position down among its siblings, swapping
+    /// it with its next sibling.  Returns `false` (and changes nothing) if it is already the last
+    /// child, has no parent, or if `node_id` points to nothing (or belongs to a different
+    /// `Tree`).
     ///
     /// ```
     /// use nary_tree::tree::TreeBuilder;
     ///
     /// let mut tree = TreeBuilder::new().with_root(0).build();
-    /// let root = tree.root_mut().unwrap();
-    /// let mut root = root.append(1).append(2).parent().unwrap().parent().unwrap();
-    /// root.append2(3);
+    /// let mut root = tree.root_mut().unwrap();
+    /// let one_id = root.append2(1);
+    /// let two_id = root.append2(2);
+    ///
+    /// assert!(tree.move_down(one_id));
+    /// assert!(!tree.move_down(one_id));
+    /// assert_eq!(tree.root().unwrap().first_child().unwrap().node_id(), two_id);
+    /// assert_eq!(tree.root().unwrap().last_child().unwrap().node_id(), one_id);
+    /// ```
+    ///
+    pub fn move_down(&mut self, node_id: NodeId) -> bool {
+        self.get_mut(node_id)
+            .is_some_and(|mut node| node.swap_next_sibling())
+    }
+
+    ///
+    /// Swaps the positions of the `Node`s identified by `a` and `b` in the `Tree`, each taking
+    /// its sub-tree along: `a` ends up with `b`'s parent and siblings and the other way around.
+    /// Only links are rewired, so every `NodeId` stays valid.
+    ///
+    /// Returns a `SwapError` (and changes nothing) if either `NodeId` points to nothing (or
+    /// belongs to a different `Tree`), or if one of the `Node`s is an ancestor of the other.
+    /// Swapping a `Node` with itself does nothing.
+    ///
+    /// ```
+    /// use nary_tree::tree::{SwapError, TreeBuilder};
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let mut root = tree.root_mut().unwrap();
+    /// let two_id = root.append2(2);
+    /// let three_id = root.append2(3);
+    /// tree.get_mut(two_id).unwrap().append2(4);
+    /// let five_id = tree.get_mut(three_id).unwrap().append2(5);
+    ///
+    /// tree.swap_subtrees(two_id, five_id).unwrap();
+    ///
     /// let mut s = String::new();
     /// tree.write_formatted(&mut s).unwrap();
     /// assert_eq!(&s, "\
-    /// 0
-    /// ├── 1
-    /// │   └── 2
+    /// 1
+    /// ├── 5
     /// └── 3
+    ///     └── 2
+    ///         └── 4
     /// ");
+    /// assert_eq!(tree.swap_subtrees(root_id, three_id), Err(SwapError::Nested));
     /// ```
     ///
-    /// Writes nothing if the tree is empty.
+    pub fn swap_subtrees(&mut self, a: NodeId, b: NodeId) -> Result<(), SwapError> {
+        let a_relatives = self
+            .get_node(a)
+            .ok_or(SwapError::InvalidNodeId(a))?
+            .relatives;
+        let b_relatives = self
+            .get_node(b)
+            .ok_or(SwapError::InvalidNodeId(b))?
+            .relatives;
+        if a == b {
+            return Ok(());
+        }
+        let is_ancestor = |ancestor: NodeId, node_id: NodeId| {
+            self.get(node_id)
+                .expect("node must exist")
+                .ancestors()
+                .any(|node| node.node_id() == ancestor)
+        };
+        if is_ancestor(a, b) || is_ancestor(b, a) {
+            return Err(SwapError::Nested);
+        }
+
+        if a_relatives.next_sibling == Some(b) {
+            // adjacent siblings only need the first one moved after the second
+            self.unlink(a);
+            self.link_child(a, a_relatives.parent.expect("parent must exist"), Some(b));
+        } else if b_relatives.next_sibling == Some(a) {
+            self.unlink(b);
+            self.link_child(b, b_relatives.parent.expect("parent must exist"), Some(a));
+        } else {
+            self.unlink(a);
+            self.unlink(b);
+            if let Some(parent_id) = b_relatives.parent {
+                self.link_child(a, parent_id, b_relatives.prev_sibling);
+            }
+            if let Some(parent_id) = a_relatives.parent {
+                self.link_child(b, parent_id, a_relatives.prev_sibling);
+            }
+        }
+
+        // an orphaned `Node` can take the place of the root
+        if self.root_id == Some(a) {
+            self.root_id = Some(self.core_tree.stamp(b));
+        } else if self.root_id == Some(b) {
+            self.root_id = Some(self.core_tree.stamp(a));
+        }
+        Ok(())
+    }
+
+    ///
+    /// Repairs the links between the children of the `Node` identified by `parent`, trusting
+    /// only the chain that starts at its first child and follows each child's next sibling.
+    ///
+    /// Every `Node` on that chain gets `parent` as its parent and its previous `Node` on the
+    /// chain as its previous sibling, and the last `Node` on the chain becomes the last child.
+    /// The chain stops early at a `NodeId` that points to nothing or at a `Node` seen before.
+    /// This is meant to restore a consistent state after low-level surgery on a `Tree`.
+    ///
+    /// Returns `false` (and does nothing) if `parent` points to nothing (or belongs to a
+    /// different `Tree`).
     ///
     /// ```
     /// use nary_tree::tree::TreeBuilder;
     ///
-    /// let tree = TreeBuilder::<i32>::new().build();
-    /// let mut s = String::new();
-    /// tree.write_formatted(&mut s).unwrap();
-    /// assert_eq!(&s, "");
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append2(2);
+    /// root.append2(3);
+    ///
+    /// assert!(tree.rebuild_links_under(root_id));
+    /// assert_eq!(tree.root().unwrap().last_child().unwrap().data(), &3);
     /// ```
-    pub fn write_formatted<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
-        if let Some(root) = self.root() {
-            let node_id = root.node_id();
-            let childn = 0;
-            let level = 0;
-            let last = vec![];
-            let mut stack = vec![(node_id, childn, level, last)];
-            while let Some((node_id, childn, level, last)) = stack.pop() {
-                debug_assert_eq!(
-                    last.len(),
-                    level,
-                    "each previous level should indicate whether it has reached the last node"
-                );
-                let node = self
-                    .get(node_id)
-                    .expect("getting node of existing node ref id");
-                if childn == 0 {
-                    for i in 1..level {
-                        if last[i - 1] {
-                            write!(w, "    ")?;
-                        } else {
-                            write!(w, "│   ")?;
-                        }
-                    }
-                    if level > 0 {
-                        if last[level - 1] {
-                            write!(w, "└── ")?;
-                        } else {
-                            write!(w, "├── ")?;
-                        }
-                    }
-                    writeln!(w, "{}", node.data())?;
-                }
-                let mut children = node.children().skip(childn);
-                if let Some(child) = children.next() {
-                    let mut next_last = last.clone();
-                    if children.next().is_some() {
-                        stack.push((node_id, childn + 1, level, last));
-                        next_last.push(false);
-                    } else {
-                        next_last.push(true);
-                    }
-                    stack.push((child.node_id(), 0, level + 1, next_last));
-                }
+    ///
+    pub fn rebuild_links_under(&mut self, parent: NodeId) -> bool {
+        let first_child = match self.get_node(parent) {
+            Some(node) => node.relatives.first_child,
+            None => return false,
+        };
+
+        let mut visited = HashSet::new();
+        let mut prev_sibling = None;
+        let mut next = first_child;
+        while let Some(node_id) = next {
+            if self.get_node(node_id).is_none() || !visited.insert(node_id) {
+                break;
             }
+            self.set_parent(node_id, Some(parent));
+            self.set_prev_sibling(node_id, prev_sibling);
+            prev_sibling = Some(node_id);
+            next = self.get_node_next_sibling_id(node_id);
         }
-        Ok(())
-    }
-}
 
-impl<T: Display> Display for Tree<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.write_formatted(f)
+        if let Some(last_id) = prev_sibling {
+            self.set_next_sibling(last_id, None);
+            self.set_first_child(parent, first_child);
+        } else {
+            self.set_first_child(parent, None);
+        }
+        self.set_last_child(parent, prev_sibling);
+        true
     }
-}
 
-#[cfg_attr(tarpaulin, skip)]
-#[cfg(test)]
-mod tree_tests {
-    use super::*;
-    use crate::behaviors::RemoveBehavior::{DropChildren, OrphanChildren};
+    ///
+    /// Makes the `Node`s identified by `children` the children of the `Node` identified by
+    /// `parent`, in that order, rewriting all the links between them.
+    ///
+    /// A listed `Node` that currently has another parent is first detached from it (along with
+    /// its sub-tree).  The current children of `parent` that aren't listed are orphaned: they
+    /// stay in the `Tree` with their sub-trees but are no longer connected to the rest of it.
+    ///
+    /// Nothing is changed if a `NodeId` points to nothing (or belongs to a different `Tree`), is
+    /// listed twice, or identifies the root, `parent` or one of its ancestors.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let mut root = tree.root_mut().unwrap();
+    /// let one_id = root.append2(1);
+    /// let two_id = root.append2(2);
+    /// let three_id = root.append2(3);
+    ///
+    /// assert_eq!(tree.relink_children(root_id, &[three_id, one_id]), Ok(()));
+    ///
+    /// let root = tree.root().unwrap();
+    /// assert_eq!(root.children_ids(), vec![three_id, one_id]);
+    /// assert!(tree.get(two_id).unwrap().parent().is_none());
+    /// ```
+    ///
+    pub fn relink_children(
+        &mut self,
+        parent: NodeId,
+        children: &[NodeId],
+    ) -> Result<(), MoveError> {
+        let parent_ref = self.get(parent).ok_or(MoveError::InvalidNodeId(parent))?;
+        let mut ancestors: Vec<NodeId> =
+            parent_ref.ancestors().map(|node| node.node_id()).collect();
+        ancestors.push(parent);
 
-    #[test]
-    fn capacity() {
-        let tree = TreeBuilder::new().with_root(1).with_capacity(5).build();
-        assert_eq!(tree.capacity(), 5);
+        let mut listed = HashSet::new();
+        for &node_id in children {
+            if self.get_node(node_id).is_none() {
+                return Err(MoveError::InvalidNodeId(node_id));
+            }
+            if self.root_id == Some(node_id) {
+                return Err(MoveError::CannotMoveRoot);
+            }
+            if ancestors.contains(&node_id) {
+                return Err(MoveError::WouldCreateCycle(node_id));
+            }
+            if !listed.insert(node_id) {
+                return Err(MoveError::DuplicateNodeId(node_id));
+            }
+        }
+
+        self.rebuild_links_under(parent);
+        for old_child_id in self.new_node_ref(parent).children_ids() {
+            if !listed.contains(&old_child_id) {
+                self.unlink(old_child_id);
+            }
+        }
+        for &node_id in children {
+            if self
+                .get_node_relatives(node_id)
+                .parent
+                .is_some_and(|id| id != parent)
+            {
+                self.unlink(node_id);
+            }
+        }
+
+        self.link_children(parent, children);
+        Ok(())
     }
 
-    #[test]
-    fn root_id() {
-        let tree = TreeBuilder::new().with_root(1).build();
-        let root_id = tree.root_id().expect("root doesn't exist?");
-        let root = tree.get(root_id).unwrap();
-        assert_eq!(root.data(), &1);
+    ///
+    /// Remove a `Node` by its `NodeId` and return the data that it contained.
+    /// Returns a `Some`-value if the `Node` exists; returns a `None`-value otherwise.
+    ///
+    /// Children of the removed `Node` can either be dropped with `DropChildren` or orphaned with
+    /// `OrphanChildren`.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    /// use nary_tree::behaviors::RemoveBehavior::*;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = {
+    ///     let mut root = tree.root_mut().expect("root doesn't exist?");
+    ///     let two_id = root.append2(2);
+    ///     root.append2(3);
+    ///     two_id
+    /// };
+    ///
+    /// let two = tree.remove(two_id, DropChildren);
+    ///
+    /// assert!(two.is_some());
+    /// assert_eq!(two.unwrap(), 2);
+    ///
+    /// let root = tree.root().expect("root doesn't exist?");
+    /// assert!(root.first_child().is_some());
+    /// assert_eq!(root.first_child().unwrap().data(), &mut 3);
+    ///
+    /// assert!(root.last_child().is_some());
+    /// assert_eq!(root.last_child().unwrap().data(), &mut 3);
+    /// ```
+    ///
+    pub fn remove(&mut self, node_id: NodeId, behavior: RemoveBehavior) -> Option<T> {
+        self.remove_node_with(node_id, behavior, drop)
+    }
+
+    ///
+    /// Removes the `Node` identified by `node_id` and returns its data, moving its children up
+    /// into its place: they take its spot among its parent's children, in order, between its
+    /// previous and next siblings.  The children keep their own sub-trees and `NodeId`s.
+    ///
+    /// Returns a `None`-value (and changes nothing) if `node_id` points to nothing (or belongs to
+    /// a different `Tree`) or if its `Node` has no parent (such as the root), as there is no
+    /// place to move the children to.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// root.append2(2);
+    /// let group_id = root.append2(0);
+    /// root.append2(5);
+    /// let mut group = tree.get_mut(group_id).unwrap();
+    /// group.append2(3);
+    /// group.append2(4);
+    ///
+    /// assert_eq!(tree.flatten_node(group_id), Some(0));
+    ///
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// 1
+    /// ├── 2
+    /// ├── 3
+    /// ├── 4
+    /// └── 5
+    /// ");
+    /// ```
+    ///
+    pub fn flatten_node(&mut self, node_id: NodeId) -> Option<T> {
+        let Relatives {
+            parent,
+            prev_sibling,
+            ..
+        } = self.get_node(node_id)?.relatives;
+        let parent_id = parent?;
+        let children = self.get(node_id)?.children_ids();
+
+        self.unlink(node_id);
+        let mut prev_sibling = prev_sibling;
+        for child_id in children {
+            self.link_child(child_id, parent_id, prev_sibling);
+            prev_sibling = Some(child_id);
+        }
+        self.set_first_child(node_id, None);
+        self.set_last_child(node_id, None);
+        self.remove(node_id, RemoveBehavior::DropChildren)
+    }
+
+    ///
+    /// Remove a `Node` by its `NodeId` like `remove`, but push the removed data into `sink`
+    /// instead of returning (or dropping) it.  Returns the number of values pushed, which is 0
+    /// if the `Node` doesn't exist.
+    ///
+    /// The removed `Node`'s data is pushed first.  With `DropChildren` it is followed by the data
+    /// of every dropped descendant in level-order (children before grandchildren, siblings in
+    /// order); with `OrphanChildren` only the removed `Node`'s data is pushed.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    /// use nary_tree::behaviors::RemoveBehavior::*;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().expect("root doesn't exist?").append2(2);
+    /// let mut two = tree.get_mut(two_id).unwrap();
+    /// let three_id = two.append2(3);
+    /// two.append2(4);
+    /// tree.get_mut(three_id).unwrap().append(5);
+    ///
+    /// let mut pool = vec![0];
+    /// assert_eq!(tree.remove_into(two_id, DropChildren, &mut pool), 4);
+    /// assert_eq!(pool, vec![0, 2, 3, 4, 5]);
+    ///
+    /// assert!(tree.root().unwrap().first_child().is_none());
+    /// ```
+    ///
+    pub fn remove_into(
+        &mut self,
+        node_id: NodeId,
+        behavior: RemoveBehavior,
+        sink: &mut Vec<T>,
+    ) -> usize {
+        let start = sink.len();
+        if let Some(data) = self.remove_node_with(node_id, behavior, |dropped| sink.push(dropped)) {
+            sink.insert(start, data);
+        }
+        sink.len() - start
+    }
+
+    ///
+    /// Remove a `Node` by its `NodeId` along with all of its descendants, like `remove` with
+    /// `DropChildren`, but hand the data of each descendant to `on_drop` instead of dropping it.
+    /// Returns the removed `Node`'s own data, or a `None`-value (without calling `on_drop`) if the
+    /// `Node` doesn't exist.
+    ///
+    /// `on_drop` is called on the descendants in level-order (children before grandchildren,
+    /// siblings in order).
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().expect("root doesn't exist?").append2(2);
+    /// let mut two = tree.get_mut(two_id).unwrap();
+    /// let three_id = two.append2(3);
+    /// two.append2(4);
+    /// tree.get_mut(three_id).unwrap().append(5);
+    ///
+    /// let mut closed = Vec::new();
+    /// assert_eq!(tree.remove_with(two_id, |data| closed.push(data)), Some(2));
+    /// assert_eq!(closed, vec![3, 4, 5]);
+    ///
+    /// assert!(tree.root().unwrap().first_child().is_none());
+    /// ```
+    ///
+    pub fn remove_with<F: FnMut(T)>(&mut self, node_id: NodeId, on_drop: F) -> Option<T> {
+        self.remove_node_with(node_id, RemoveBehavior::DropChildren, on_drop)
+    }
+
+    ///
+    /// Replaces the `Node` identified by `node_id` with a new `Node` holding `data`, and returns
+    /// the data of the replaced `Node`.  The new `Node` takes the exact place of the old one (its
+    /// parent, its siblings and its children, or its place as the root) along with its edge data,
+    /// but gets a new `NodeId`; the old `NodeId` is no longer valid.
+    ///
+    /// Returns a `None`-value if `node_id` points to nothing (or belongs to a different `Tree`).
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let two_id = root.append2(2);
+    /// root.append2(4);
+    /// tree.get_mut(two_id).unwrap().append2(3);
+    ///
+    /// assert_eq!(tree.replace_node(two_id, 5), Some(2));
+    ///
+    /// assert!(tree.get(two_id).is_none());
+    /// let root = tree.root().unwrap();
+    /// let five = root.first_child().unwrap();
+    /// assert_eq!(five.data(), &5);
+    /// assert_eq!(five.first_child().unwrap().data(), &3);
+    /// assert_eq!(five.next_sibling().unwrap().data(), &4);
+    /// ```
+    ///
+    pub fn replace_node(&mut self, node_id: NodeId, data: T) -> Option<T> {
+        let relatives = self.get_node(node_id)?.relatives;
+        let children = self.get(node_id)?.children_ids();
+
+        let new_id = self.core_tree.insert(data);
+        let edge = self.get_node_mut(node_id).and_then(|node| node.edge.take());
+        self.get_node_mut(new_id).expect("node must exist").edge = edge;
+        self.unlink(node_id);
+        if let Some(parent_id) = relatives.parent {
+            self.link_child(new_id, parent_id, relatives.prev_sibling);
+        }
+        if self.root_id == Some(node_id) {
+            self.root_id = Some(new_id);
+        }
+        self.link_children(new_id, &children);
+
+        self.set_first_child(node_id, None);
+        self.set_last_child(node_id, None);
+        self.core_tree.remove(node_id)
+    }
+
+    ///
+    /// Removes every `Node` reachable from the root whose data satisfies `pred`, and returns the
+    /// removed data in pre-order.  The children of a removed `Node` are not removed with it:
+    /// they take its place among its parent's children, in order, and are tested themselves.
+    ///
+    /// The root is never removed (nor passed to `pred`), since its children would have no parent
+    /// to go to.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let two_id = root.append2(2);
+    /// root.append2(5);
+    /// let mut two = tree.get_mut(two_id).unwrap();
+    /// two.append2(3);
+    /// two.append2(4);
+    ///
+    /// assert_eq!(tree.drain_filter(|data| data % 2 == 0), vec![2, 4]);
+    ///
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// 1
+    /// ├── 3
+    /// └── 5
+    /// ");
+    /// ```
+    ///
+    pub fn drain_filter<F: FnMut(&T) -> bool>(&mut self, mut pred: F) -> Vec<T> {
+        let node_ids: Vec<NodeId> = match self.root() {
+            Some(root) => root
+                .traverse_pre_order()
+                .skip(1)
+                .map(|node| node.node_id())
+                .collect(),
+            None => return Vec::new(),
+        };
+
+        let mut drained = Vec::new();
+        for node_id in node_ids {
+            if !pred(&self.get_node(node_id).expect("node must exist").data) {
+                continue;
+            }
+
+            // the children of a removed node keep their order, right where it was
+            let parent_id = self
+                .get_node_relatives(node_id)
+                .parent
+                .expect("parent must exist");
+            let mut prev_sibling = node_id;
+            for child_id in self.get(node_id).expect("node must exist").children_ids() {
+                self.unlink(child_id);
+                self.link_child(child_id, parent_id, Some(prev_sibling));
+                prev_sibling = child_id;
+            }
+            self.unlink(node_id);
+            drained.push(self.core_tree.remove(node_id).expect("node must exist"));
+        }
+        drained
+    }
+
+    ///
+    /// Removes every leaf `Node` of the `Tree` and returns their data in pre-order.  The leaves
+    /// are picked before anything is removed, so `Node`s that become leaves along the way are
+    /// kept until the next call.  A `Tree` made of its root alone loses its root, so repeated
+    /// calls eventually empty the `Tree`.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let two_id = root.append2(2);
+    /// root.append2(3);
+    /// tree.get_mut(two_id).unwrap().append2(4);
+    ///
+    /// assert_eq!(tree.trim_leaves(), vec![4, 3]);
+    /// assert_eq!(tree.trim_leaves(), vec![2]);
+    /// assert_eq!(tree.trim_leaves(), vec![1]);
+    /// assert!(tree.root().is_none());
+    /// assert!(tree.trim_leaves().is_empty());
+    /// ```
+    ///
+    pub fn trim_leaves(&mut self) -> Vec<T> {
+        let leaf_ids: Vec<NodeId> = match self.root() {
+            Some(root) => root
+                .traverse_pre_order()
+                .filter(|node| node.first_child().is_none())
+                .map(|node| node.node_id())
+                .collect(),
+            None => return Vec::new(),
+        };
+
+        let mut trimmed = Vec::with_capacity(leaf_ids.len());
+        for leaf_id in leaf_ids {
+            self.unlink(leaf_id);
+            if self.root_id == Some(leaf_id) {
+                self.root_id = None;
+            }
+            trimmed.push(self.core_tree.remove(leaf_id).expect("node must exist"));
+        }
+        trimmed
+    }
+
+    ///
+    /// Takes the `Tree` apart into the data of its `Node`s in pre-order, along with a parallel
+    /// `Vec` holding the index of each `Node`'s parent in the returned data (`None` for the root).
+    /// This is the inverse of `Tree::from_parent_array`.  Edge data is dropped.
+    ///
+    /// ```
+    /// use nary_tree::tree::{Tree, TreeBuilder};
+    ///
+    /// let mut tree = TreeBuilder::new().with_root('a').build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let b_id = root.append2('b');
+    /// root.append2('d');
+    /// tree.get_mut(b_id).unwrap().append2('c');
+    ///
+    /// let (data, parents) = tree.into_flat();
+    /// assert_eq!(data, vec!['a', 'b', 'c', 'd']);
+    /// assert_eq!(parents, vec![None, Some(0), Some(1), Some(0)]);
+    ///
+    /// let tree = Tree::from_parent_array(data.into_iter().zip(parents)).unwrap();
+    /// assert_eq!(tree.root().unwrap().first_child_data(), Some(&'b'));
+    /// ```
+    ///
+    pub fn into_flat(mut self) -> (Vec<T>, Vec<Option<usize>>) {
+        let mut node_ids = Vec::new();
+        let mut parents = Vec::new();
+        let mut stack: Vec<(NodeId, Option<usize>)> = self
+            .root_id
+            .map(|root_id| (root_id, None))
+            .into_iter()
+            .collect();
+        while let Some((node_id, parent)) = stack.pop() {
+            let index = node_ids.len();
+            node_ids.push(node_id);
+            parents.push(parent);
+            let children = self.get(node_id).expect("node must exist").children_ids();
+            stack.extend(children.into_iter().rev().map(|child| (child, Some(index))));
+        }
+
+        let data = node_ids
+            .into_iter()
+            .map(|node_id| self.core_tree.remove(node_id).expect("node must exist"))
+            .collect();
+        (data, parents)
+    }
+
+    ///
+    /// Returns the `NodeId`s of every `Node` that is still stored in the `Tree` but can't be
+    /// reached from the root, such as the children of a `Node` removed with `OrphanChildren` and
+    /// their descendants.  The `NodeId`s come in storage order.
+    ///
+    /// ```
+    /// use nary_tree::behaviors::RemoveBehavior::OrphanChildren;
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().unwrap().append2(2);
+    /// let three_id = tree.get_mut(two_id).unwrap().append2(3);
+    /// assert!(tree.orphans().is_empty());
+    ///
+    /// tree.remove(two_id, OrphanChildren);
+    /// assert_eq!(tree.orphans(), vec![three_id]);
+    /// ```
+    ///
+    pub fn orphans(&self) -> Vec<NodeId> {
+        let reachable: HashSet<NodeId> = self.cached_pre_order().iter().copied().collect();
+        self.core_tree
+            .iter()
+            .map(|(node_id, _)| node_id)
+            .filter(|node_id| !reachable.contains(node_id))
+            .collect()
+    }
+
+    ///
+    /// Drops every `Node` returned by `orphans`, and returns how many were dropped.
+    ///
+    /// ```
+    /// use nary_tree::behaviors::RemoveBehavior::OrphanChildren;
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().unwrap().append2(2);
+    /// let three_id = tree.get_mut(two_id).unwrap().append2(3);
+    /// tree.get_mut(three_id).unwrap().append2(4);
+    ///
+    /// tree.remove(two_id, OrphanChildren);
+    /// assert_eq!(tree.collect_orphans(), 2);
+    /// assert!(tree.get(three_id).is_none());
+    /// assert!(tree.orphans().is_empty());
+    /// ```
+    ///
+    pub fn collect_orphans(&mut self) -> usize {
+        let orphans = self.orphans();
+        for &node_id in &orphans {
+            // orphans only link to other orphans, so they can go without unlinking
+            self.core_tree.remove(node_id);
+        }
+        orphans.len()
+    }
+
+    fn remove_node_with<F: FnMut(T)>(
+        &mut self,
+        node_id: NodeId,
+        behavior: RemoveBehavior,
+        on_dropped: F,
+    ) -> Option<T> {
+        if self.get_node(node_id).is_some() {
+            self.unlink(node_id);
+
+            match behavior {
+                RemoveBehavior::DropChildren => self.drop_children(node_id, on_dropped),
+                RemoveBehavior::OrphanChildren => self.orphan_children(node_id),
+            };
+            if self.root_id == Some(node_id) {
+                self.root_id = None;
+            }
+            self.core_tree.remove(node_id)
+        } else {
+            None
+        }
+    }
+
+    /// Shrink the capacity of the nary_tree as much as possible without invalidating
+    /// keys.
+    ///
+    /// Because values cannot be moved to a different index, the slab cannot
+    /// shrink past any stored values.
+    /// It will drop down as close as possible to the length but the allocator may
+    /// still inform the underlying vector that there is space for a few more elements.
+    ///
+    /// This function can take O(n) time even when the capacity cannot be reduced
+    /// or the allocation is shrunk in place. Repeated calls run in O(1) though.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use nary_tree::*;
+    /// let mut tree = TreeBuilder::new().with_root(0).with_capacity(10).build();
+    /// let mut root = tree.root_mut().unwrap();
+    ///
+    /// for i in 1..4 {
+    ///     root.append2(i);
+    /// }
+    ///
+    /// tree.shrink_to_fit();
+    /// assert!(tree.capacity() >= 4 && tree.capacity() < 10);
+    /// ```
+    ///
+    /// The nary_tree cannot shrink past the last present value even if previous
+    /// values are removed:
+    ///
+    /// ```
+    /// # use nary_tree::*;
+    /// let mut tree = TreeBuilder::new().with_root(0).with_capacity(10).build();
+    /// let mut root = tree.root_mut().unwrap();
+    ///
+    /// let mut node_ids = vec![root.node_id()];
+    /// for i in 1..4 {
+    ///     node_ids.push(root.append2(i));
+    /// }
+    ///
+    /// tree.remove(node_ids[1], nary_tree::RemoveBehavior::OrphanChildren);
+    /// tree.remove(node_ids[3], nary_tree::RemoveBehavior::OrphanChildren);
+    ///
+    /// tree.shrink_to_fit();
+    /// assert!(tree.capacity() >= 3 && tree.capacity() < 10);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.core_tree.shrink_to_fit();
+    }
+
+    /// Reduce the capacity as much as possible by moving `Node`s from the back of the slab to
+    /// empty slots and fixing every relation (and the root) that pointed at a moved `Node`.
+    /// Returns the new capacity.
+    ///
+    /// Unlike `shrink_to_fit`, this can reclaim space left behind by removed `Node`s anywhere in
+    /// the `Tree`. The price is that **every `NodeId` handed out before the call must be
+    /// considered invalid** afterwards: moved `Node`s get new `NodeId`s, and the old ones either
+    /// return `None` or could be confused with a later insertion. Only use this if you don't keep
+    /// `NodeId`s around; fetch fresh ones (e.g. via `root_id` or a traversal) after compacting.
+    ///
+    /// ```
+    /// # use nary_tree::*;
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let one_id = root.append2(1);
+    /// root.append2(2);
+    ///
+    /// tree.remove(one_id, RemoveBehavior::DropChildren);
+    /// assert!(tree.capacity() >= 3);
+    ///
+    /// assert_eq!(tree.compact_internal(), 2);
+    ///
+    /// let root = tree.root().unwrap();
+    /// assert_eq!(root.first_child().unwrap().data(), &2);
+    /// assert_eq!(root.last_child().unwrap().data(), &2);
+    /// ```
+    pub fn compact_internal(&mut self) -> usize {
+        let rekeys = self.core_tree.compact_rekeyed();
+        if let Some(root_id) = self.root_id {
+            let new_root_id = rekeys.get(&root_id.index.index).unwrap_or(&root_id);
+            self.root_id = Some(self.core_tree.stamp(*new_root_id));
+        }
+        self.capacity()
+    }
+
+    #[cfg(feature = "experimental")]
+    /// Reduce the capacity as much as possible by moving `Node`s from the back of the slab to
+    /// empty slots, updating the index for elements when necessary.
+    /// This will increase the generation of all moved `Node`s, making obsolete the `NodeId`s
+    /// pointing to the old index.  The root and every relation are fixed up the same way as by
+    /// `compact_internal`, so the `NodeId`s handed out afterwards are current (see `is_current`).
+    ///
+    /// ```
+    /// # use nary_tree::*;
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let one_id = root.append2(1);
+    /// let five_id = root.append2(5);
+    /// let two_id = tree.get_mut(one_id).unwrap().append2(2);
+    /// let mut two = tree.get_mut(two_id).unwrap();
+    /// two.append2(3);
+    /// two.append2(4);
+    /// let mut five = tree.get_mut(five_id).unwrap();
+    /// let six_id = five.append2(6);
+    /// five.append2(8);
+    /// tree.get_mut(six_id).unwrap().append2(7);
+    ///
+    /// // 0
+    /// // ├── 1
+    /// // │   └── 2
+    /// // │       ├── 3
+    /// // │       └── 4
+    /// // ├── 5
+    /// // │   ├── 6
+    /// // │   │   └── 7
+    /// // │   └── 8
+    ///
+    /// let three_id = tree.find(&3).unwrap()[0];
+    /// let five_id = tree.find(&5).unwrap()[0];
+    ///
+    /// tree.remove(three_id, RemoveBehavior::DropChildren);
+    /// tree.remove(five_id, RemoveBehavior::DropChildren);
+    ///
+    /// // 0
+    /// // └── 1
+    /// //     └── 2
+    /// //         └── 4
+    ///
+    /// let two = tree.get(tree.find(&2).unwrap()[0]).unwrap();
+    /// assert_eq!(two.first_child().unwrap().data(), &4);
+    ///
+    /// let four = tree.get(tree.find(&4).unwrap()[0]).unwrap();
+    /// assert!(four.prev_sibling().is_none());
+    /// assert_eq!(tree.root().unwrap().last_child().unwrap().data(), &1);
+    ///
+    /// assert!(tree.capacity() >= 9);
+    ///
+    /// tree.compact();
+    ///
+    /// let two = tree.get(tree.find(&2).unwrap()[0]).unwrap();
+    /// assert_eq!(two.first_child().unwrap().data(), &4);
+    ///
+    /// let four = tree.get(tree.find(&4).unwrap()[0]).unwrap();
+    /// assert!(four.prev_sibling().is_none());
+    /// assert_eq!(tree.root().unwrap().last_child().unwrap().data(), &1);
+    ///
+    /// assert!(tree.capacity() == 4);
+    /// ```
+    pub fn compact(&mut self) -> usize {
+        self.compact_internal()
+    }
+
+    ///
+    /// Registers a callback that is called with the `NodeId` of every `Node` inserted into the
+    /// `Tree` from now on, replacing any callback registered before.
+    ///
+    /// The callback runs as soon as the `Node` is stored, before it is linked to its relatives.
+    /// It is not called for the `Node`s moved around by `compact_internal` (or `compact`), whose
+    /// `NodeId`s change without any insertion taking place.  A `Tree` without callbacks pays
+    /// nothing more than a check for their presence.
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let inserted = Arc::new(Mutex::new(Vec::new()));
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    ///
+    /// let inserted_ids = Arc::clone(&inserted);
+    /// tree.on_insert(move |node_id| inserted_ids.lock().unwrap().push(node_id));
+    ///
+    /// let two_id = tree.root_mut().unwrap().append2(2);
+    /// assert_eq!(*inserted.lock().unwrap(), vec![two_id]);
+    /// ```
+    ///
+    pub fn on_insert<F: FnMut(NodeId) + Send + Sync + 'static>(&mut self, callback: F) {
+        self.core_tree.hooks.on_insert = Some(Box::new(callback));
+    }
+
+    ///
+    /// Registers a callback that is called with the `NodeId` of every `Node` removed from the
+    /// `Tree` from now on, replacing any callback registered before.
+    ///
+    /// The callback runs for each `Node` dropped from the `Tree`, including the descendants
+    /// dropped along with a `Node` removed with `DropChildren`.  Like `on_insert`, it is not
+    /// called when compaction changes `NodeId`s.
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use nary_tree::behaviors::RemoveBehavior::DropChildren;
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let removed = Arc::new(Mutex::new(Vec::new()));
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let two_id = tree.root_mut().unwrap().append2(2);
+    /// let three_id = tree.get_mut(two_id).unwrap().append2(3);
+    ///
+    /// let removed_ids = Arc::clone(&removed);
+    /// tree.on_remove(move |node_id| removed_ids.lock().unwrap().push(node_id));
+    ///
+    /// tree.remove(two_id, DropChildren);
+    /// assert_eq!(removed.lock().unwrap().len(), 2);
+    /// assert!(removed.lock().unwrap().contains(&three_id));
+    /// ```
+    ///
+    pub fn on_remove<F: FnMut(NodeId) + Send + Sync + 'static>(&mut self, callback: F) {
+        self.core_tree.hooks.on_remove = Some(Box::new(callback));
+    }
+
+    ///
+    /// Gives the `Node` identified by `node_id` the stable key `key`, replacing any key it had.
+    /// Unlike `NodeId`s, keys survive `compact_internal`; a key goes away with its `Node` when the
+    /// `Node` is removed.  Use `find_by_key` to look `Node`s up by key.
+    ///
+    /// Returns `false` (and changes nothing) if `node_id` points to nothing (or belongs to a
+    /// different `Tree`) or if another `Node` already has `key`.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let two_id = tree.root_mut().unwrap().append2(2);
+    ///
+    /// assert!(tree.set_key(root_id, 100));
+    /// assert!(!tree.set_key(two_id, 100));
+    /// assert_eq!(tree.key_of(root_id), Some(100));
+    /// assert_eq!(tree.key_of(two_id), None);
+    /// ```
+    ///
+    pub fn set_key(&mut self, node_id: NodeId, key: u64) -> bool {
+        self.core_tree.set_key(node_id, key)
+    }
+
+    ///
+    /// Returns the stable key of the `Node` identified by `node_id`, if it has one (see
+    /// `set_key`).
+    ///
+    pub fn key_of(&self, node_id: NodeId) -> Option<u64> {
+        self.core_tree.key_of(node_id)
+    }
+
+    ///
+    /// Returns the `NodeId` of the `Node` that has the stable key `key` (see `set_key`).  The
+    /// lookup goes through a map and takes constant time.
+    ///
+    /// ```
+    /// use nary_tree::behaviors::RemoveBehavior::DropChildren;
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let two_id = root.append2(2);
+    /// let three_id = root.append_with_key(3, 42).unwrap();
+    ///
+    /// tree.remove(two_id, DropChildren);
+    /// tree.compact_internal();
+    ///
+    /// let three_id = tree.find_by_key(42).unwrap();
+    /// assert_eq!(tree.get(three_id).unwrap().data(), &3);
+    /// assert_eq!(tree.find_by_key(7), None);
+    /// ```
+    ///
+    pub fn find_by_key(&self, key: u64) -> Option<NodeId> {
+        self.core_tree.find_by_key(key)
+    }
+
+    ///
+    /// Returns the `NodeId`s of the `Node`s from the root down to the `Node` identified by
+    /// `node_id` (both included), ordered top-down.  This is the reverse of walking its
+    /// `ancestors` plus the `Node` itself.
+    ///
+    /// Returns a `None`-value if `node_id` points to nothing (or belongs to a different `Tree`),
+    /// or if its `Node` isn't reachable from the root.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("home").build();
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let docs_id = tree.root_mut().unwrap().append2("docs");
+    /// let notes_id = tree.get_mut(docs_id).unwrap().append2("notes");
+    ///
+    /// assert_eq!(tree.spine(notes_id), Some(vec![root_id, docs_id, notes_id]));
+    /// assert_eq!(tree.spine(root_id), Some(vec![root_id]));
+    /// ```
+    ///
+    pub fn spine(&self, node_id: NodeId) -> Option<Vec<NodeId>> {
+        let node = self.get(node_id)?;
+        let mut spine: Vec<NodeId> = node
+            .ancestors()
+            .map(|ancestor| ancestor.node_id())
+            .collect();
+        spine.reverse();
+        spine.push(node_id);
+
+        if spine.first() == self.root_id.as_ref() {
+            Some(spine)
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Returns the `NodeId`s from the root down to the first `Node`, in depth-first pre-order,
+    /// whose data matches `pred` (both included), ordered top-down like `spine`.  Returns a
+    /// `None`-value if no `Node` matches.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("/").build();
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let mut root = tree.root_mut().unwrap();
+    /// let etc_id = root.append2("etc");
+    /// let usr_id = root.append2("usr");
+    /// let hosts_id = tree.get_mut(etc_id).unwrap().append2("hosts");
+    /// tree.get_mut(usr_id).unwrap().append2("hosts");
+    ///
+    /// assert_eq!(tree.find_path(|name| *name == "hosts"), Some(vec![root_id, etc_id, hosts_id]));
+    /// assert_eq!(tree.find_path(|name| *name == "var"), None);
+    /// ```
+    ///
+    pub fn find_path<F: FnMut(&T) -> bool>(&self, mut pred: F) -> Option<Vec<NodeId>> {
+        let found = self
+            .root()?
+            .traverse_pre_order()
+            .find(|node| pred(node.data()))?;
+        self.spine(found.node_id())
+    }
+
+    ///
+    /// Returns the position of the `Node` identified by `node_id` in a depth-first pre-order
+    /// traversal of the whole `Tree`, the root being at position 0.
+    ///
+    /// Returns a `None`-value if `node_id` points to nothing (or belongs to a different `Tree`),
+    /// or if its `Node` isn't reachable from the root.  The positions of all the `Node`s are
+    /// worked out together with `cached_pre_order`, and kept until the shape of the `Tree` changes.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let mut root = tree.root_mut().unwrap();
+    /// let two_id = root.append2(2);
+    /// let four_id = root.append2(4);
+    /// let three_id = tree.get_mut(two_id).unwrap().append2(3);
+    ///
+    /// assert_eq!(tree.pre_order_index(root_id), Some(0));
+    /// assert_eq!(tree.pre_order_index(three_id), Some(2));
+    /// assert_eq!(tree.pre_order_index(four_id), Some(3));
+    /// ```
+    ///
+    pub fn pre_order_index(&self, node_id: NodeId) -> Option<usize> {
+        self.get_node(node_id)?;
+        self.core_tree
+            .pre_order_positions
+            .get_or_init(|| {
+                self.cached_pre_order()
+                    .iter()
+                    .enumerate()
+                    .map(|(index, node_id)| (*node_id, index))
+                    .collect()
+            })
+            .get(&node_id)
+            .copied()
+    }
+
+    ///
+    /// Returns the number of `Node`s from `from` to `to` (both included) in a depth-first
+    /// pre-order traversal of the whole `Tree`.
+    ///
+    /// Returns a `None`-value if either `NodeId` has no `pre_order_index`, or if `from` comes
+    /// after `to`.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let mut root = tree.root_mut().unwrap();
+    /// let two_id = root.append2(2);
+    /// let four_id = root.append2(4);
+    /// tree.get_mut(two_id).unwrap().append2(3);
+    ///
+    /// assert_eq!(tree.range_count(two_id, four_id), Some(3));
+    /// assert_eq!(tree.range_count(root_id, root_id), Some(1));
+    /// assert_eq!(tree.range_count(four_id, two_id), None);
+    /// ```
+    ///
+    pub fn range_count(&self, from: NodeId, to: NodeId) -> Option<usize> {
+        let from = self.pre_order_index(from)?;
+        let to = self.pre_order_index(to)?;
+        to.checked_sub(from).map(|distance| distance + 1)
+    }
+
+    ///
+    /// Returns the `NodeId` of the `Node` that follows the one identified by `node_id` in an
+    /// in-order traversal of the `Tree`, or a `None`-value if it is the last one (or if `node_id`
+    /// points to nothing or belongs to a different `Tree`).
+    ///
+    /// In-order for a `Node` with any number of children means: the sub-tree of its first child,
+    /// then the `Node` itself, then the sub-trees of the rest of its children in order.  With at
+    /// most two children per `Node` this is the usual binary tree in-order.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// // in-order: 1 2 3 4 5
+    /// let mut tree = TreeBuilder::new().with_root(2).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let one_id = root.append2(1);
+    /// let four_id = root.append2(4);
+    /// let mut four = tree.get_mut(four_id).unwrap();
+    /// let three_id = four.append2(3);
+    /// let five_id = four.append2(5);
+    /// let two_id = tree.root_id().unwrap();
+    ///
+    /// assert_eq!(tree.successor(one_id), Some(two_id));
+    /// assert_eq!(tree.successor(two_id), Some(three_id));
+    /// assert_eq!(tree.successor(three_id), Some(four_id));
+    /// assert_eq!(tree.successor(four_id), Some(five_id));
+    /// assert_eq!(tree.successor(five_id), None);
+    /// ```
+    ///
+    pub fn successor(&self, node_id: NodeId) -> Option<NodeId> {
+        let first_child = self.get_node(node_id)?.relatives.first_child;
+        if let Some(second_child) = first_child.and_then(|id| self.get_node_next_sibling_id(id)) {
+            return Some(self.in_order_first(second_child));
+        }
+
+        // the `Node` is the last of its own sub-tree, so look past the sub-trees it is in
+        let mut current = node_id;
+        loop {
+            let relatives = self.get_node_relatives(current);
+            let parent_id = relatives.parent?;
+            if relatives.prev_sibling.is_none() {
+                return Some(parent_id);
+            }
+            if let Some(next_sibling) = relatives.next_sibling {
+                return Some(self.in_order_first(next_sibling));
+            }
+            current = parent_id;
+        }
+    }
+
+    ///
+    /// Returns the `NodeId` of the `Node` that comes before the one identified by `node_id` in
+    /// an in-order traversal of the `Tree` (as defined for `successor`), or a `None`-value if it
+    /// is the first one (or if `node_id` points to nothing or belongs to a different `Tree`).
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// // in-order: 1 2 3 4 5
+    /// let mut tree = TreeBuilder::new().with_root(2).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let one_id = root.append2(1);
+    /// let four_id = root.append2(4);
+    /// let mut four = tree.get_mut(four_id).unwrap();
+    /// let three_id = four.append2(3);
+    /// let five_id = four.append2(5);
+    /// let two_id = tree.root_id().unwrap();
+    ///
+    /// assert_eq!(tree.predecessor(five_id), Some(four_id));
+    /// assert_eq!(tree.predecessor(four_id), Some(three_id));
+    /// assert_eq!(tree.predecessor(three_id), Some(two_id));
+    /// assert_eq!(tree.predecessor(two_id), Some(one_id));
+    /// assert_eq!(tree.predecessor(one_id), None);
+    /// ```
+    ///
+    pub fn predecessor(&self, node_id: NodeId) -> Option<NodeId> {
+        if let Some(first_child) = self.get_node(node_id)?.relatives.first_child {
+            return Some(self.in_order_last(first_child));
+        }
+
+        // the `Node` is the first of its own sub-tree, so look before the sub-trees it is in
+        let mut current = node_id;
+        loop {
+            let relatives = self.get_node_relatives(current);
+            let parent_id = relatives.parent?;
+            if let Some(prev_sibling) = relatives.prev_sibling {
+                return match self.get_node_prev_sibling_id(prev_sibling) {
+                    Some(_) => Some(self.in_order_last(prev_sibling)),
+                    None => Some(parent_id),
+                };
+            }
+            current = parent_id;
+        }
+    }
+
+    // the first `Node` of an in-order traversal of the sub-tree: its leftmost leaf
+    fn in_order_first(&self, mut node_id: NodeId) -> NodeId {
+        while let Some(first_child) = self.get_node_relatives(node_id).first_child {
+            node_id = first_child;
+        }
+        node_id
+    }
+
+    // the last `Node` of an in-order traversal of the sub-tree: the `Node` itself, unless it has
+    // more than one child and its last child's sub-tree comes after it
+    fn in_order_last(&self, mut node_id: NodeId) -> NodeId {
+        loop {
+            let relatives = self.get_node_relatives(node_id);
+            if relatives.first_child == relatives.last_child {
+                return node_id;
+            }
+            node_id = relatives.last_child.expect("last child must exist");
+        }
+    }
+
+    ///
+    /// Returns the number of `Node`s reachable from the root that have no children.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// assert_eq!(tree.leaf_count(), 1);
+    ///
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// root.append2(2);
+    /// root.append(3).append(4);
+    /// assert_eq!(tree.leaf_count(), 2);
+    /// ```
+    ///
+    pub fn leaf_count(&self) -> usize {
+        self.root()
+            .map(|root| {
+                root.traverse_pre_order()
+                    .filter(|node| node.first_child().is_none())
+                    .count()
+            })
+            .unwrap_or(0)
+    }
+
+    ///
+    /// Returns `true` if the `Node`s identified by `a` and `b` are two different children of the
+    /// same parent.  Returns `false` otherwise, including when either `NodeId` points to nothing
+    /// (or belongs to a different `Tree`).
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let mut root = tree.root_mut().unwrap();
+    /// let two_id = root.append2(2);
+    /// let three_id = root.append2(3);
+    /// let four_id = tree.get_mut(two_id).unwrap().append2(4);
+    ///
+    /// assert!(tree.are_siblings(two_id, three_id));
+    /// assert!(!tree.are_siblings(two_id, two_id));
+    /// assert!(!tree.are_siblings(three_id, four_id));
+    /// assert!(!tree.are_siblings(root_id, two_id));
+    /// ```
+    ///
+    pub fn are_siblings(&self, a: NodeId, b: NodeId) -> bool {
+        match (self.get_node(a), self.get_node(b)) {
+            (Some(a_node), Some(b_node)) => {
+                a != b
+                    && a_node.relatives.parent.is_some()
+                    && a_node.relatives.parent == b_node.relatives.parent
+            }
+            _ => false,
+        }
+    }
+
+    ///
+    /// Returns the number of `Node`s reachable from the root whose data satisfies `pred`.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// root.append2(2);
+    /// root.append(3).append(4);
+    ///
+    /// assert_eq!(tree.count_by(|data| data % 2 == 0), 2);
+    /// assert_eq!(tree.count_by(|_| true), 4);
+    /// ```
+    ///
+    pub fn count_by<F: FnMut(&T) -> bool>(&self, mut pred: F) -> usize {
+        let mut count = 0;
+        // a pre-order walk straight over the links, without building a `NodeRef` per `Node`
+        let mut next = self.root_id;
+        while let Some(node_id) = next {
+            let node = self.get_node(node_id).expect("node must exist");
+            if pred(&node.data) {
+                count += 1;
+            }
+            next = node.relatives.first_child;
+            let mut current = Some(node_id);
+            while next.is_none() {
+                match current.filter(|&id| Some(id) != self.root_id) {
+                    Some(id) => {
+                        let relatives = self.get_node(id).expect("node must exist").relatives;
+                        next = relatives.next_sibling;
+                        current = relatives.parent;
+                    }
+                    None => break,
+                }
+            }
+        }
+        count
+    }
+
+    ///
+    /// Returns the number of direct children of every `Node` reachable from the root, keyed by
+    /// `NodeId`.  Leaves map to 0 and an empty `Tree` returns an empty map.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let two_id = root.append2(2);
+    /// let three_id = root.append(3).append(4).parent().unwrap().node_id();
+    ///
+    /// let counts = tree.child_counts();
+    /// assert_eq!(counts.len(), 4);
+    /// assert_eq!(counts[&root_id], 2);
+    /// assert_eq!(counts[&two_id], 0);
+    /// assert_eq!(counts[&three_id], 1);
+    /// ```
+    ///
+    pub fn child_counts(&self) -> HashMap<NodeId, usize> {
+        let mut counts = HashMap::new();
+        if let Some(root) = self.root() {
+            for node in root.traverse_pre_order() {
+                counts.insert(node.node_id(), 0);
+                if let Some(parent) = node.parent() {
+                    *counts
+                        .get_mut(&parent.node_id())
+                        .expect("parent counted first") += 1;
+                }
+            }
+        }
+        counts
+    }
+
+    ///
+    /// Returns the `NodeId`s of every `Node` for which `pred` returns `true`, in pre-order.
+    /// Besides the `Node`'s data, `pred` receives the `Node`'s depth: 0 for the root, 1 for its
+    /// children and so on.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let two_id = root.append2(2);
+    /// let four_id = root.append(3).append(4).node_id();
+    ///
+    /// let matches = tree.find_by_with_depth(|_, depth| depth >= 1);
+    /// assert_eq!(matches.len(), 3);
+    /// assert_eq!(matches[0], two_id);
+    ///
+    /// let matches = tree.find_by_with_depth(|data, depth| data % 2 == 0 && depth >= 2);
+    /// assert_eq!(matches, vec![four_id]);
+    /// ```
+    ///
+    pub fn find_by_with_depth<F: FnMut(&T, usize) -> bool>(&self, mut pred: F) -> Vec<NodeId> {
+        let mut matches = Vec::new();
+        if let Some(root) = self.root() {
+            let mut stack = vec![(root, 0)];
+            while let Some((node, depth)) = stack.pop() {
+                if pred(node.data(), depth) {
+                    matches.push(node.node_id());
+                }
+                let children: Vec<NodeRef<T, E>> = node.children().collect();
+                stack.extend(children.into_iter().rev().map(|child| (child, depth + 1)));
+            }
+        }
+        matches
+    }
+
+    ///
+    /// Returns the `NodeId` of the `Node` whose data yields the smallest key, or a `None`-value
+    /// if the `Tree` is empty.  If several `Node`s share the smallest key, the first one in
+    /// pre-order is returned.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(("root", 5)).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let a_id = root.append2(("a", 1));
+    /// root.append2(("b", 1));
+    ///
+    /// assert_eq!(tree.min_by_key(|(_, priority)| *priority), Some(a_id));
+    /// ```
+    ///
+    pub fn min_by_key<K: Ord, F: Fn(&T) -> K>(&self, f: F) -> Option<NodeId> {
+        self.root()?
+            .traverse_pre_order()
+            .map(|node| (f(node.data()), node.node_id()))
+            .reduce(|min, next| if next.0 < min.0 { next } else { min })
+            .map(|(_, node_id)| node_id)
+    }
+
+    ///
+    /// Returns the `NodeId` of the `Node` whose data yields the largest key, or a `None`-value
+    /// if the `Tree` is empty.  If several `Node`s share the largest key, the first one in
+    /// pre-order is returned.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(("root", 0)).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let a_id = root.append2(("a", 3));
+    /// root.append2(("b", 3));
+    ///
+    /// assert_eq!(tree.max_by_key(|(_, priority)| *priority), Some(a_id));
+    /// ```
+    ///
+    pub fn max_by_key<K: Ord, F: Fn(&T) -> K>(&self, f: F) -> Option<NodeId> {
+        self.root()?
+            .traverse_pre_order()
+            .map(|node| (f(node.data()), node.node_id()))
+            .reduce(|max, next| if next.0 > max.0 { next } else { max })
+            .map(|(_, node_id)| node_id)
+    }
+
+    ///
+    /// Calls `f` with a mutable reference to the data of every `Node` in the `Tree`, in
+    /// depth-first post-order: children before their parent, so a `Node`'s value can be
+    /// recomputed from values its children were already updated with.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let two_id = root.append2(2);
+    /// root.append2(4);
+    /// tree.get_mut(two_id).unwrap().append2(3);
+    ///
+    /// let mut order = Vec::new();
+    /// tree.for_each_post_order_mut(|data| {
+    ///     order.push(*data);
+    ///     *data *= 10;
+    /// });
+    /// assert_eq!(order, vec![3, 2, 4, 1]);
+    /// assert_eq!(tree.root().unwrap().data(), &10);
+    /// ```
+    ///
+    pub fn for_each_post_order_mut<F: FnMut(&mut T)>(&mut self, mut f: F) {
+        let node_ids: Vec<NodeId> = match self.root() {
+            Some(root) => root
+                .traverse_post_order()
+                .map(|node| node.node_id())
+                .collect(),
+            None => return,
+        };
+
+        self.core_tree.data_version += 1;
+        for node_id in node_ids {
+            f(&mut self.get_node_mut(node_id).expect("node must exist").data);
+        }
+    }
+
+    ///
+    /// Reverses the order of the children of every `Node` in the `Tree`, mirroring it.  Calling
+    /// it twice restores the original order.
+    ///
+    /// No data is moved and every `NodeId` stays valid; only the sibling links are rewired.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let one_id = root.append2(1);
+    /// root.append2(4);
+    /// let mut one = tree.get_mut(one_id).unwrap();
+    /// one.append2(2);
+    /// one.append2(3);
+    ///
+    /// tree.reverse();
+    ///
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// 0
+    /// ├── 4
+    /// └── 1
+    ///     ├── 3
+    ///     └── 2
+    /// ");
+    /// ```
+    ///
+    pub fn reverse(&mut self) {
+        let node_ids: Vec<NodeId> = match self.root() {
+            Some(root) => root
+                .traverse_pre_order()
+                .map(|node| node.node_id())
+                .collect(),
+            None => return,
+        };
+
+        self.core_tree.structure_changed();
+        for node_id in node_ids {
+            let relatives = &mut self
+                .get_node_mut(node_id)
+                .expect("node must exist")
+                .relatives;
+            std::mem::swap(&mut relatives.first_child, &mut relatives.last_child);
+            std::mem::swap(&mut relatives.prev_sibling, &mut relatives.next_sibling);
+        }
+    }
+
+    ///
+    /// Collapses every chain of `Node`s that have exactly one child: each such `Node` absorbs its
+    /// only child, whose data is merged into the `Node`'s own with `merge(data, child_data)` and
+    /// whose children become the `Node`'s children (in the same order).  This repeats until no
+    /// `Node` is left with exactly one child.
+    ///
+    /// The `Node` at the top of a chain keeps its `NodeId`, its parent and its siblings; this
+    /// includes the root, which absorbs its only child like any other `Node`.  The `NodeId`s of
+    /// the absorbed `Node`s are no longer valid.  `merge` is called top-down along each chain.
+    ///
+    /// If `merge` panics, the data it was given is lost, so the `Node` it was merging into is
+    /// removed the way `remove` with `OrphanChildren` would: its children are left as orphans
+    /// (see `orphans`), and if it was the root the `Tree` is left without one.  The rest of the
+    /// `Tree` stays valid, with the chains above it already collapsed.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("expr".to_string()).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let term_id = root.append("term".to_string()).node_id();
+    /// let mut term = tree.get_mut(term_id).unwrap();
+    /// term.append2("a".to_string());
+    /// term.append("factor".to_string()).append("b".to_string());
+    ///
+    /// tree.collapse_chains(|data, child_data| format!("{}/{}", data, child_data));
+    ///
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// expr/term
+    /// ├── a
+    /// └── factor/b
+    /// ");
+    /// ```
+    ///
+    pub fn collapse_chains<F: FnMut(T, T) -> T>(&mut self, mut merge: F) {
+        let node_ids: Vec<NodeId> = match self.root() {
+            Some(root) => root
+                .traverse_pre_order()
+                .map(|node| node.node_id())
+                .collect(),
+            None => return,
+        };
+
+        for node_id in node_ids {
+            // absorbed nodes were visited through the node at the top of their chain
+            if self.get_node(node_id).is_none() {
+                continue;
+            }
+
+            loop {
+                let child_id = match self.get_node_relatives(node_id) {
+                    Relatives {
+                        first_child: Some(first),
+                        last_child: Some(last),
+                        ..
+                    } if first == last => first,
+                    _ => break,
+                };
+
+                let Relatives {
+                    first_child,
+                    last_child,
+                    ..
+                } = self.get_node_relatives(child_id);
+                for grandchild_id in self.new_node_ref(child_id).children_ids() {
+                    self.set_parent(grandchild_id, Some(node_id));
+                }
+                self.set_first_child(node_id, first_child);
+                self.set_last_child(node_id, last_child);
+
+                let child_data = self.core_tree.remove(child_id).expect("child must exist");
+                let relatives = self.get_node_relatives(node_id);
+                let merged = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                    self.core_tree
+                        .replace_data_with(node_id, |data| merge(data, child_data))
+                }));
+                if let Err(payload) = merged {
+                    // the node's data went into `merge` along with its slot
+                    self.detach_vacated(node_id, relatives);
+                    std::panic::resume_unwind(payload);
+                }
+            }
+        }
+    }
+
+    // takes a node whose slot was vacated out of the tree, as `remove` with `OrphanChildren`
+    // would have, going by the relatives it had
+    fn detach_vacated(&mut self, node_id: NodeId, relatives: Relatives) {
+        let Relatives {
+            parent,
+            prev_sibling,
+            next_sibling,
+            first_child,
+            ..
+        } = relatives;
+
+        if let Some(parent) = parent {
+            let parent_relatives = self.get_node_relatives(parent);
+            if parent_relatives.first_child == Some(node_id) {
+                self.set_first_child(parent, next_sibling);
+            }
+            if parent_relatives.last_child == Some(node_id) {
+                self.set_last_child(parent, prev_sibling);
+            }
+        }
+        if let Some(prev) = prev_sibling {
+            self.set_next_sibling(prev, next_sibling);
+        }
+        if let Some(next) = next_sibling {
+            self.set_prev_sibling(next, prev_sibling);
+        }
+
+        let mut next_child = first_child;
+        while let Some(child_id) = next_child {
+            next_child = self.get_node_next_sibling_id(child_id);
+            self.set_parent(child_id, None);
+        }
+        if self.root_id == Some(node_id) {
+            self.root_id = None;
+        }
+        self.core_tree.forget(node_id);
+    }
+
+    ///
+    /// Returns an `Iterator` over the `Tree` in depth-first pre-order, yielding each `Node`'s
+    /// data along with its parent's data (`None` for the root).
+    ///
+    /// Only shared references are handed out: a mutable reference to a parent's data would alias
+    /// the one handed out with each of its other children.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// root.append2(2);
+    /// root.append(3).append(4);
+    ///
+    /// let pairs = tree.iter_with_parent()
+    ///     .map(|(data, parent)| (*data, parent.copied()))
+    ///     .collect::<Vec<(i32, Option<i32>)>>();
+    /// assert_eq!(pairs, vec![(1, None), (2, Some(1)), (3, Some(1)), (4, Some(3))]);
+    /// ```
+    ///
+    pub fn iter_with_parent(&self) -> impl Iterator<Item = (&T, Option<&T>)> {
+        self.root()
+            .into_iter()
+            .flat_map(|root| root.traverse_pre_order())
+            .map(move |node| {
+                let parent = self
+                    .get_node_relatives(node.node_id())
+                    .parent
+                    .map(|parent_id| self.new_node_ref(parent_id).data());
+                (node.data(), parent)
+            })
+    }
+
+    ///
+    /// Returns an `Iterator` over the `Tree` in depth-first pre-order, yielding each `Node`'s
+    /// `NodeId`, its parent's `NodeId` (`None` for the root) and its data.  Since parents come
+    /// before their children, this is the shape of an adjacency table that can be loaded back
+    /// in the same order.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let root_id = tree.root_id().expect("root doesn't exist?");
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let two_id = root.append2(2);
+    /// let three_id = tree.get_mut(two_id).unwrap().append2(3);
+    ///
+    /// let rows = tree.iter_with_parent_id().collect::<Vec<_>>();
+    /// assert_eq!(rows, vec![
+    ///     (root_id, None, &1),
+    ///     (two_id, Some(root_id), &2),
+    ///     (three_id, Some(two_id), &3),
+    /// ]);
+    /// ```
+    ///
+    pub fn iter_with_parent_id(&self) -> impl Iterator<Item = (NodeId, Option<NodeId>, &T)> {
+        self.root()
+            .into_iter()
+            .flat_map(|root| root.traverse_pre_order())
+            .map(move |node| {
+                let node_id = node.node_id();
+                (
+                    node_id,
+                    self.get_node_relatives(node_id).parent,
+                    node.data(),
+                )
+            })
+    }
+
+    ///
+    /// Returns an `Iterator` that takes the `Node`s reachable from the root out of the `Tree` one
+    /// at a time, in depth-first pre-order, yielding their data.  Each `Node` is removed as its
+    /// data is yielded, so memory is handed back as the iteration goes, and nothing happens
+    /// until the first call to `next`.
+    ///
+    /// If the iteration stops early, the `Node`s not yielded yet are left in the `Tree` with
+    /// their data and relations untouched, but the root is gone after the first item: each
+    /// sub-tree below a yielded `Node` that wasn't reached yet remains as an orphaned sub-tree
+    /// (see `orphans`).  Draining to the end leaves the reachable part of the `Tree` empty.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// let two_id = root.append2(2);
+    /// let four_id = root.append2(4);
+    /// let three_id = tree.get_mut(two_id).unwrap().append2(3);
+    ///
+    /// let drained = tree.drain_pre_order().take(2).collect::<Vec<i32>>();
+    /// assert_eq!(drained, vec![1, 2]);
+    /// assert!(tree.root().is_none());
+    ///
+    /// // the sub-trees of 3 and 4 are still there
+    /// assert_eq!(tree.get(three_id).unwrap().data(), &3);
+    /// assert_eq!(tree.get(four_id).unwrap().data(), &4);
+    ///
+    /// assert_eq!(tree.drain_pre_order().count(), 0);
+    /// ```
+    ///
+    pub fn drain_pre_order(&mut self) -> impl Iterator<Item = T> + '_ {
+        // the sub-trees still to visit, with the next one on top
+        let mut pending: Vec<NodeId> = self.root_id.into_iter().collect();
+        std::iter::from_fn(move || {
+            let node_id = pending.pop()?;
+            let mut child = self.get_node_relatives(node_id).last_child;
+            while let Some(child_id) = child {
+                pending.push(child_id);
+                child = self.get_node_relatives(child_id).prev_sibling;
+            }
+            self.remove(node_id, RemoveBehavior::OrphanChildren)
+        })
+    }
+
+    ///
+    /// Returns an `Iterator` over every `Node` in the `Tree` in the order they are stored in the
+    /// backing slab, yielding each `Node`'s `NodeId` along with a reference to its data.
+    ///
+    /// This is the fastest way to visit every `Node` since no relations are followed, but the
+    /// order is unrelated to the `Tree`'s structure: parents can come after their children and
+    /// siblings can come in any order.  Orphaned `Node`s are included as well.  Use it for
+    /// order-independent work such as sums or maximums.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// root.prepend2(4);
+    /// root.append(2).append(3);
+    ///
+    /// let sum: i32 = tree.iter_slab_order().map(|(_, data)| data).sum();
+    /// assert_eq!(sum, 10);
+    ///
+    /// for (node_id, data) in tree.iter_slab_order() {
+    ///     assert_eq!(tree.get(node_id).unwrap().data(), data);
+    /// }
+    /// ```
+    ///
+    pub fn iter_slab_order(&self) -> impl Iterator<Item = (NodeId, &T)> {
+        self.core_tree
+            .iter()
+            .map(|(node_id, node)| (node_id, &node.data))
+    }
+
+    ///
+    /// Consume the `Tree` and build a `Tree<U>` of identical shape by moving each `Node`'s data
+    /// through `f`.  `f` is called once per `Node`, in pre-order, so no data needs to be cloned.
+    ///
+    /// Only `Node`s reachable from the root are kept; orphaned `Node`s are dropped.  The new
+    /// `Tree` hands out its own `NodeId`s.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().expect("root doesn't exist?");
+    /// root.append(2).append(3);
+    ///
+    /// let tree = tree.map_into(|value| value.to_string());
+    ///
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// 1
+    /// └── 2
+    ///     └── 3
+    /// ");
+    /// ```
+    ///
+    pub fn map_into<U, F: FnMut(T) -> U>(mut self, mut f: F) -> Tree<U, E> {
+        let nodes: Vec<(NodeId, Option<NodeId>)> = match self.root() {
+            Some(root) => root
+                .traverse_pre_order()
+                .map(|node| (node.node_id(), node.parent().map(|parent| parent.node_id())))
+                .collect(),
+            None => return TreeBuilder::new().build_with_edges(),
+        };
+
+        let mut tree = TreeBuilder::new()
+            .with_capacity(nodes.len())
+            .build_with_edges();
+        let mut new_ids = HashMap::with_capacity(nodes.len());
+        for (node_id, parent_id) in nodes {
+            let edge = self.get_node_mut(node_id).and_then(|node| node.edge.take());
+            let data = f(self.core_tree.remove(node_id).expect("node must exist"));
+            let new_id = match parent_id {
+                Some(parent_id) => {
+                    let mut parent = tree
+                        .get_mut(new_ids[&parent_id])
+                        .expect("parent is mapped before its children");
+                    match edge {
+                        Some(edge) => parent.append_with_edge(data, edge),
+                        None => parent.append2(data),
+                    }
+                }
+                None => tree.set_root(data),
+            };
+            new_ids.insert(node_id, new_id);
+        }
+        tree
+    }
+
+    // the data (and edge data) of each node in pre-order along with its number of children,
+    // which is enough to rebuild the tree's shape
+    fn pre_order_with_child_counts(&self) -> impl Iterator<Item = (&T, Option<&E>, usize)> {
+        self.root()
+            .into_iter()
+            .flat_map(|root| root.traverse_pre_order())
+            .map(|node| (node.data(), node.parent_edge(), node.children().count()))
+    }
+
+    pub(crate) fn get_node(&self, node_id: NodeId) -> Option<&Node<T, E>> {
+        self.core_tree.get(node_id)
+    }
+
+    pub(crate) fn get_node_mut(&mut self, node_id: NodeId) -> Option<&mut Node<T, E>> {
+        self.core_tree.get_mut(node_id)
+    }
+
+    pub(crate) fn set_prev_siblings_next_sibling(
+        &mut self,
+        current_id: NodeId,
+        next_sibling: Option<NodeId>,
+    ) {
+        if let Some(prev_sibling_id) = self.get_node_prev_sibling_id(current_id) {
+            self.set_next_sibling(prev_sibling_id, next_sibling);
+        }
+    }
+
+    pub(crate) fn set_next_siblings_prev_sibling(
+        &mut self,
+        current_id: NodeId,
+        prev_sibling: Option<NodeId>,
+    ) {
+        if let Some(next_sibling_id) = self.get_node_next_sibling_id(current_id) {
+            self.set_prev_sibling(next_sibling_id, prev_sibling);
+        }
+    }
+
+    pub(crate) fn set_parent(&mut self, node_id: NodeId, parent_id: Option<NodeId>) {
+        self.core_tree.structure_changed();
+        let parent_id = parent_id.map(|id| self.core_tree.stamp(id));
+        if let Some(node) = self.get_node_mut(node_id) {
+            node.relatives.parent = parent_id;
+        } else {
+            unreachable!()
+        }
+    }
+
+    pub(crate) fn set_prev_sibling(&mut self, node_id: NodeId, prev_sibling: Option<NodeId>) {
+        self.core_tree.structure_changed();
+        let prev_sibling = prev_sibling.map(|id| self.core_tree.stamp(id));
+        if let Some(node) = self.get_node_mut(node_id) {
+            node.relatives.prev_sibling = prev_sibling;
+        } else {
+            unreachable!()
+        }
+    }
+
+    pub(crate) fn set_next_sibling(&mut self, node_id: NodeId, next_sibling: Option<NodeId>) {
+        self.core_tree.structure_changed();
+        let next_sibling = next_sibling.map(|id| self.core_tree.stamp(id));
+        if let Some(node) = self.get_node_mut(node_id) {
+            node.relatives.next_sibling = next_sibling;
+        } else {
+            unreachable!()
+        }
+    }
+
+    pub(crate) fn set_first_child(&mut self, node_id: NodeId, first_child: Option<NodeId>) {
+        self.core_tree.structure_changed();
+        let first_child = first_child.map(|id| self.core_tree.stamp(id));
+        if let Some(node) = self.get_node_mut(node_id) {
+            node.relatives.first_child = first_child;
+        } else {
+            unreachable!()
+        }
+    }
+
+    pub(crate) fn set_last_child(&mut self, node_id: NodeId, last_child: Option<NodeId>) {
+        self.core_tree.structure_changed();
+        let last_child = last_child.map(|id| self.core_tree.stamp(id));
+        if let Some(node) = self.get_node_mut(node_id) {
+            node.relatives.last_child = last_child;
+        } else {
+            unreachable!()
+        }
+    }
+
+    // moves the nodes reachable from the root of `subtree` into this tree, keeping their
+    // structure, and returns the id of the moved root (which has no parent yet)
+    pub(crate) fn adopt(&mut self, mut subtree: Tree<T, E>) -> Option<NodeId> {
+        let root_id = subtree.root_id?;
+        Some(self.adopt_from(&mut subtree, root_id))
+    }
+
+    // moves the node identified by `top` in `source`, along with its sub-tree, into this tree,
+    // keeping their structure, and returns the new id of `top` (which has no parent yet)
+    fn adopt_from(&mut self, source: &mut Tree<T, E>, top: NodeId) -> NodeId {
+        let nodes: Vec<(NodeId, Option<NodeId>)> = source
+            .get(top)
+            .expect("node must exist")
+            .traverse_pre_order()
+            .map(|node| match node.node_id() {
+                node_id if node_id == top => (node_id, None),
+                node_id => (node_id, node.parent().map(|parent| parent.node_id())),
+            })
+            .collect();
+
+        let mut new_ids = HashMap::with_capacity(nodes.len());
+        for (node_id, parent_id) in nodes {
+            let edge = source
+                .get_node_mut(node_id)
+                .and_then(|node| node.edge.take());
+            let data = source.core_tree.remove(node_id).expect("node must exist");
+            let new_id = self.core_tree.insert(data);
+            if let Some(parent_id) = parent_id {
+                self.get_node_mut(new_id).expect("node must exist").edge = edge;
+                let new_parent_id = new_ids[&parent_id];
+                let last_child = self.get_node_relatives(new_parent_id).last_child;
+                self.link_child(new_id, new_parent_id, last_child);
+            }
+            new_ids.insert(node_id, new_id);
+        }
+        new_ids[&top]
+    }
+
+    // detaches the node from its parent and siblings, leaving it (and its sub-tree) orphaned
+    pub(crate) fn unlink(&mut self, node_id: NodeId) {
+        let Relatives {
+            parent,
+            prev_sibling,
+            next_sibling,
+            ..
+        } = self.get_node_relatives(node_id);
+
+        let (is_first_child, is_last_child) = self.is_node_first_last_child(node_id);
+
+        if is_first_child {
+            // parent first child = my next sibling
+            self.set_first_child(parent.expect("parent must exist"), next_sibling);
+        }
+        if is_last_child {
+            // parent last child = my prev sibling
+            self.set_last_child(parent.expect("parent must exist"), prev_sibling);
+        }
+        if let Some(prev) = prev_sibling {
+            self.set_next_sibling(prev, next_sibling);
+        }
+        if let Some(next) = next_sibling {
+            self.set_prev_sibling(next, prev_sibling);
+        }
+
+        self.set_parent(node_id, None);
+        self.set_prev_sibling(node_id, None);
+        self.set_next_sibling(node_id, None);
+    }
+
+    // links an unlinked node as a child of `parent_id`, right after `prev_sibling` (or as the
+    // first child if `prev_sibling` is None)
+    pub(crate) fn link_child(
+        &mut self,
+        node_id: NodeId,
+        parent_id: NodeId,
+        prev_sibling: Option<NodeId>,
+    ) {
+        let next_sibling = match prev_sibling {
+            Some(prev_id) => self.get_node_next_sibling_id(prev_id),
+            None => self.get_node_relatives(parent_id).first_child,
+        };
+
+        self.set_parent(node_id, Some(parent_id));
+        self.set_prev_sibling(node_id, prev_sibling);
+        self.set_next_sibling(node_id, next_sibling);
+
+        match prev_sibling {
+            Some(prev_id) => self.set_next_sibling(prev_id, Some(node_id)),
+            None => self.set_first_child(parent_id, Some(node_id)),
+        }
+        match next_sibling {
+            Some(next_id) => self.set_prev_sibling(next_id, Some(node_id)),
+            None => self.set_last_child(parent_id, Some(node_id)),
+        }
+    }
+
+    // links the given nodes as the only children of `parent`, in order, overwriting their
+    // parent and sibling links
+    pub(crate) fn link_children(&mut self, parent: NodeId, children: &[NodeId]) {
+        let mut prev_sibling = None;
+        for &node_id in children {
+            self.set_parent(node_id, Some(parent));
+            self.set_prev_sibling(node_id, prev_sibling);
+            if let Some(prev_id) = prev_sibling {
+                self.set_next_sibling(prev_id, Some(node_id));
+            }
+            prev_sibling = Some(node_id);
+        }
+        if let Some(last_id) = prev_sibling {
+            self.set_next_sibling(last_id, None);
+        }
+        self.set_first_child(parent, children.first().copied());
+        self.set_last_child(parent, prev_sibling);
+    }
+
+    pub(crate) fn get_node_prev_sibling_id(&self, node_id: NodeId) -> Option<NodeId> {
+        if let Some(node) = self.get_node(node_id) {
+            node.relatives.prev_sibling
+        } else {
+            unreachable!()
+        }
+    }
+
+    pub(crate) fn get_node_next_sibling_id(&self, node_id: NodeId) -> Option<NodeId> {
+        if let Some(node) = self.get_node(node_id) {
+            node.relatives.next_sibling
+        } else {
+            unreachable!()
+        }
+    }
+
+    pub(crate) fn get_node_relatives(&self, node_id: NodeId) -> Relatives {
+        if let Some(node) = self.get_node(node_id) {
+            node.relatives
+        } else {
+            unreachable!()
+        }
+    }
+
+    fn drop_children<F: FnMut(T)>(&mut self, node_id: NodeId, mut on_dropped: F) {
+        let sub_tree_ids: Vec<NodeId> = self
+            .get(node_id)
+            .expect("node must exist")
+            .traverse_level_order()
+            .skip(1) // skip the "root" of the sub-tree, which is the "current" node
+            .map(|node_ref| node_ref.node_id())
+            .collect();
+
+        for id in sub_tree_ids {
+            if let Some(data) = self.core_tree.remove(id) {
+                on_dropped(data);
+            }
+        }
+    }
+
+    fn orphan_children(&mut self, node_id: NodeId) {
+        let child_ids: Vec<NodeId> = self
+            .get(node_id)
+            .expect("node must exist")
+            .children()
+            .map(|node_ref| node_ref.node_id())
+            .collect();
+
+        for id in child_ids {
+            self.set_parent(id, None);
+        }
+    }
+
+    fn new_node_ref(&self, node_id: NodeId) -> NodeRef<'_, T, E> {
+        NodeRef::new(node_id, self)
+    }
+
+    fn new_node_mut(&mut self, node_id: NodeId) -> NodeMut<'_, T, E> {
+        NodeMut::new(node_id, self)
+    }
+
+    fn node_at_path(&self, path: &[usize]) -> Option<NodeId> {
+        let mut node = self.root()?;
+        for &position in path {
+            node = node.children().nth(position)?;
+        }
+        Some(node.node_id())
+    }
+
+    pub(crate) fn is_node_first_last_child(&self, node_id: NodeId) -> (bool, bool) {
+        if let Some(node) = self.get_node(node_id) {
+            node.relatives
+                .parent
+                .and_then(|parent_id| self.get_node(parent_id))
+                .map(|parent| {
+                    let Relatives {
+                        first_child: first,
+                        last_child: last,
+                        ..
+                    } = parent.relatives;
+                    (
+                        first.map(|child_id| child_id == node_id).unwrap_or(false),
+                        last.map(|child_id| child_id == node_id).unwrap_or(false),
+                    )
+                })
+                .unwrap_or((false, false))
+        } else {
+            (false, false)
+        }
+    }
+}
+
+impl<T: PartialEq, E> Tree<T, E> {
+    /// Find all the `Node`s that contain data and return a `Some`-Vec of their `NodeId`s
+    /// or `None` if none found.
+    /// ```
+    /// # use nary_tree::*;
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let mut root = {
+    ///     let one = root.append(1);
+    ///     let mut two = one.append(2);
+    ///     two.append2(3);
+    ///     two.append2(4);
+    ///     two.parent().unwrap().parent().unwrap()
+    /// };
+    /// let root = {
+    ///     let five = root.append(5);
+    ///     let mut five = five.append(6).append(7).parent().unwrap().parent().unwrap();
+    ///     five.append2(8);
+    ///     five.parent().unwrap()
+    /// };
+    /// root.append(9);
+    ///
+    /// // 0
+    /// // ├── 1
+    /// // │   └── 2
+    /// // │       ├── 3
+    /// // │       └── 4
+    /// // ├── 5
+    /// // │   ├── 6
+    /// // │   │   └── 7
+    /// // │   └── 8
+    /// // └── 9
+    ///
+    /// let matches = tree.find(&6).unwrap();
+    /// assert_eq!(matches.len(), 1);
+    /// assert_eq!(tree.get(matches[0]).unwrap().data(), &6);
+    /// ```
+    pub fn find(&self, data: &T) -> Option<Vec<NodeId>> {
+        let mut matches = Vec::with_capacity(1);
+        if let Some(root) = self.root() {
+            for node in root.traverse_level_order() {
+                if node.data() == data {
+                    matches.push(node.node_id());
+                }
+            }
+        }
+        if matches.is_empty() {
+            None
+        } else {
+            Some(matches)
+        }
+    }
+}
+
+impl<T: Hash + Eq, E> Tree<T, E> {
+    /// Find the groups of identical sub-trees: sub-trees with the same shape and equal data in
+    /// every position.  Returns one `Vec` of `NodeId`s per group, each listing the roots of the
+    /// identical sub-trees in pre-order; the first `NodeId` of a group can serve as its canonical
+    /// representative.  Groups are ordered by the pre-order position of their first member and
+    /// only sub-trees occurring at least twice are reported.
+    ///
+    /// Children of duplicated sub-trees are duplicated as well, so they form groups of their own.
+    ///
+    /// ```
+    /// # use nary_tree::*;
+    /// let mut tree = TreeBuilder::new().with_root("+").build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let left_id = root.append2("*");
+    /// let right_id = root.append2("*");
+    /// for id in [left_id, right_id] {
+    ///     let mut product = tree.get_mut(id).unwrap();
+    ///     product.append2("x");
+    ///     product.append2("y");
+    /// }
+    ///
+    /// // +
+    /// // ├── *
+    /// // │   ├── x
+    /// // │   └── y
+    /// // └── *
+    /// //     ├── x
+    /// //     └── y
+    ///
+    /// let groups = tree.find_duplicate_subtrees();
+    /// assert_eq!(groups.len(), 3);
+    /// assert_eq!(groups[0], vec![left_id, right_id]);
+    /// assert_eq!(tree.get(groups[1][0]).unwrap().data(), &"x");
+    /// assert_eq!(tree.get(groups[2][0]).unwrap().data(), &"y");
+    /// ```
+    pub fn find_duplicate_subtrees(&self) -> Vec<Vec<NodeId>> {
+        let root = match self.root() {
+            Some(root) => root,
+            None => return Vec::new(),
+        };
+
+        // number each distinct sub-tree, children first so that a sub-tree is identified by its
+        // own data and the numbers of its children
+        let mut numbers: HashMap<(&T, Vec<usize>), usize> = HashMap::new();
+        let mut node_numbers: HashMap<NodeId, usize> = HashMap::new();
+        for node in root.traverse_post_order() {
+            let children = node
+                .children()
+                .map(|child| node_numbers[&child.node_id()])
+                .collect();
+            let next_number = numbers.len();
+            let number = *numbers
+                .entry((node.data(), children))
+                .or_insert(next_number);
+            node_numbers.insert(node.node_id(), number);
+        }
+
+        let mut groups: Vec<Vec<NodeId>> = Vec::new();
+        let mut group_positions: HashMap<usize, usize> = HashMap::new();
+        for node in root.traverse_pre_order() {
+            let number = node_numbers[&node.node_id()];
+            let position = *group_positions.entry(number).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[position].push(node.node_id());
+        }
+        groups.retain(|group| group.len() > 1);
+        groups
+    }
+}
+
+impl<T: Clone, E> Tree<T, E> {
+    /// Apply an edit script (as produced by `tree_diff`) to this `Tree`, one edit after the
+    /// other.  Removed `Node`s are removed with `DropChildren`.
+    ///
+    /// Returns `false` as soon as an edit's path doesn't lead to a `Node` (or, for an `Insert`,
+    /// to a valid position); the edits before it stay applied and the ones after it are skipped.
+    /// Returns `true` if every edit was applied.
+    ///
+    /// ```
+    /// use nary_tree::diff::tree_diff;
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut old = TreeBuilder::new().with_root(1).build();
+    /// let mut root = old.root_mut().unwrap();
+    /// root.append2(2);
+    /// root.append2(3);
+    ///
+    /// let mut new = TreeBuilder::new().with_root(1).build();
+    /// new.root_mut().unwrap().append(4).append(5);
+    ///
+    /// assert!(old.apply_edits(&tree_diff(&old, &new)));
+    ///
+    /// let mut s = String::new();
+    /// old.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// 1
+    /// └── 4
+    ///     └── 5
+    /// ");
+    /// ```
+    pub fn apply_edits(&mut self, edits: &[TreeEdit<T>]) -> bool {
+        edits.iter().all(|edit| self.apply_edit(edit))
+    }
+
+    ///
+    /// Returns a new `Tree` holding a deep copy of the sub-tree rooted at the `Node` identified
+    /// by `node_id`, which becomes the new `Tree`'s root.  This `Tree` is left untouched.
+    ///
+    /// The new `Tree` is independent from this one: it has its own id, so none of this `Tree`'s
+    /// `NodeId`s are valid in it.  Returns `None` if `node_id` doesn't identify a `Node` in this
+    /// `Tree`.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append2(2);
+    /// let three_id = root.append2(3);
+    /// tree.get_mut(three_id).unwrap().append2(4);
+    ///
+    /// let copy = tree.clone_subtree(three_id).unwrap();
+    /// assert!(copy.get(three_id).is_none());
+    ///
+    /// let mut s = String::new();
+    /// copy.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// 3
+    /// └── 4
+    /// ");
+    /// assert_eq!(tree.get(three_id).unwrap().first_child().unwrap().data(), &4);
+    /// ```
+    ///
+    pub fn clone_subtree(&self, node_id: NodeId) -> Option<Tree<T, E>>
+    where
+        E: Clone,
+    {
+        let node = self.get(node_id)?;
+
+        let mut tree = TreeBuilder::new().build_with_edges();
+        let mut new_ids = HashMap::new();
+        for descendant in node.traverse_pre_order() {
+            let data = descendant.data().clone();
+            let new_id = match descendant.parent() {
+                Some(parent) if descendant.node_id() != node_id => {
+                    let mut new_parent = tree
+                        .get_mut(new_ids[&parent.node_id()])
+                        .expect("parent is cloned before its children");
+                    match descendant.parent_edge() {
+                        Some(edge) => new_parent.append_with_edge(data, edge.clone()),
+                        None => new_parent.append2(data),
+                    }
+                }
+                _ => tree.set_root(data),
+            };
+            new_ids.insert(descendant.node_id(), new_id);
+        }
+        Some(tree)
+    }
+
+    ///
+    /// Returns a copy of the data of every `Node` in the `Tree`, in depth-first pre-order.  The
+    /// snapshot owns its data, so it can outlive the `Tree` or be sent elsewhere.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(1).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let two_id = root.append2(2);
+    /// root.append2(4);
+    /// tree.get_mut(two_id).unwrap().append2(3);
+    ///
+    /// let snapshot = tree.snapshot_pre_order();
+    /// drop(tree);
+    /// assert_eq!(snapshot, vec![1, 2, 3, 4]);
+    /// ```
+    ///
+    pub fn snapshot_pre_order(&self) -> Vec<T> {
+        let mut snapshot = Vec::with_capacity(self.core_tree.len());
+        if let Some(root) = self.root() {
+            snapshot.extend(root.traverse_pre_order().map(|node| node.data().clone()));
+        }
+        snapshot
+    }
+
+    ///
+    /// Returns clones of the data of the `Node`s from the root down to the `Node` identified by
+    /// `node_id` (both included), ordered top-down: the data along its `spine`.
+    ///
+    /// Returns a `None`-value if `node_id` points to nothing (or belongs to a different `Tree`),
+    /// or if its `Node` isn't reachable from the root.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("home").build();
+    /// let docs_id = tree.root_mut().unwrap().append2("docs");
+    /// let notes_id = tree.get_mut(docs_id).unwrap().append2("notes");
+    ///
+    /// assert_eq!(tree.data_path(notes_id), Some(vec!["home", "docs", "notes"]));
+    /// assert_eq!(tree.data_path(docs_id).unwrap().join(" > "), "home > docs");
+    /// ```
+    ///
+    pub fn data_path(&self, node_id: NodeId) -> Option<Vec<T>> {
+        let node = self.get(node_id)?;
+        let top = node.ancestors().last().map_or(node_id, |top| top.node_id());
+        if Some(top) != self.root_id {
+            return None;
+        }
+
+        let mut path = Vec::with_capacity(node.ancestors().count() + 1);
+        path.push(node.data().clone());
+        path.extend(node.ancestors().map(|ancestor| ancestor.data().clone()));
+        path.reverse();
+        Some(path)
+    }
+
+    ///
+    /// Returns the data of every `Node` in the `Tree` along with the parent-child edges between
+    /// them, as `(parent, child)` index pairs into the returned data.
+    ///
+    /// The indices are the order in which the `Node`s were assigned a place in the returned data
+    /// (a pre-order traversal from the root), not their position in the `Tree`'s storage.  An
+    /// empty `Tree` returns no data and no edges.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root('a').build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// let b_id = root.append2('b');
+    /// root.append2('d');
+    /// tree.get_mut(b_id).unwrap().append2('c');
+    ///
+    /// let (data, edges) = tree.to_adjacency_list();
+    /// assert_eq!(data, vec!['a', 'b', 'c', 'd']);
+    /// assert_eq!(edges, vec![(0, 1), (1, 2), (0, 3)]);
+    /// ```
+    ///
+    pub fn to_adjacency_list(&self) -> (Vec<T>, Vec<(usize, usize)>) {
+        let mut data = Vec::new();
+        let mut edges = Vec::new();
+        let mut stack: Vec<(NodeRef<T, E>, Option<usize>)> =
+            self.root().map(|root| (root, None)).into_iter().collect();
+        while let Some((node, parent)) = stack.pop() {
+            let index = data.len();
+            data.push(node.data().clone());
+            if let Some(parent) = parent {
+                edges.push((parent, index));
+            }
+            let children: Vec<NodeRef<T, E>> = node.children().collect();
+            stack.extend(children.into_iter().rev().map(|child| (child, Some(index))));
+        }
+        (data, edges)
+    }
+
+    fn apply_edit(&mut self, edit: &TreeEdit<T>) -> bool {
+        match edit {
+            TreeEdit::Insert { path, data } => match path.split_last() {
+                None => {
+                    self.set_root(data.clone());
+                    true
+                }
+                Some((0, parent_path)) => self
+                    .node_at_path(parent_path)
+                    .and_then(|parent_id| self.get_mut(parent_id))
+                    .map(|mut parent| parent.prepend2(data.clone()))
+                    .is_some(),
+                Some((position, parent_path)) => {
+                    let mut prev_path = parent_path.to_vec();
+                    prev_path.push(position - 1);
+                    self.node_at_path(&prev_path)
+                        .and_then(|prev_id| self.insert_after(prev_id, data.clone()))
+                        .is_some()
+                }
+            },
+            TreeEdit::Remove { path } => self
+                .node_at_path(path)
+                .and_then(|node_id| self.remove(node_id, RemoveBehavior::DropChildren))
+                .is_some(),
+            TreeEdit::Update { path, data } => self
+                .node_at_path(path)
+                .and_then(|node_id| self.get_mut(node_id))
+                .map(|mut node| *node.data() = data.clone())
+                .is_some(),
+        }
+    }
+}
+
+impl Tree<String> {
+    ///
+    /// Parses an outline into a `Tree`: every non-blank line of `text` becomes a `Node`, and each
+    /// line indented by one more `indent_unit` spaces than the line before it is a child of that
+    /// line.  The first line is the root; leading and trailing whitespace is not part of the data.
+    /// Only spaces count as indentation.
+    ///
+    /// Returns a `ParseError` carrying the line number if a line isn't indented by a whole number
+    /// of `indent_unit`s, is indented with tabs or other whitespace, is nested more than one level
+    /// deeper than the line before it, or is a second root.  Empty text parses into an empty `Tree`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `indent_unit` is 0.
+    ///
+    /// ```
+    /// use nary_tree::tree::Tree;
+    ///
+    /// let tree = Tree::from_indented_text("\
+    /// fruits
+    ///   apple
+    ///     gala
+    ///   pear
+    /// ", 2).unwrap();
+    ///
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// fruits
+    /// ├── apple
+    /// │   └── gala
+    /// └── pear
+    /// ");
+    ///
+    /// let error = Tree::from_indented_text("fruits\n   apple\n", 2).unwrap_err();
+    /// assert_eq!(error.line(), 2);
+    /// ```
+    ///
+    pub fn from_indented_text(text: &str, indent_unit: usize) -> Result<Tree<String>, ParseError> {
+        parse::indented_text(text, indent_unit)
+    }
+
+    ///
+    /// Parses the output of `write_formatted` back into a `Tree`.
+    ///
+    /// The first line is the root, taken as is.  Every other line must start with the columns
+    /// `write_formatted` draws for its ancestors (`│   ` for an ancestor with more siblings to
+    /// come, four spaces otherwise) followed by `├── ` or `└── `; the rest of the line is the
+    /// `Node`'s data.  Empty text parses into an empty `Tree`.
+    ///
+    /// Returns a `ParseError` carrying the line number if a line's prefix doesn't match the
+    /// `Tree` built so far, for instance a `│` column below a last child or a sibling following
+    /// a `└── `.  Data spanning several lines can't be told apart from `Node`s and isn't
+    /// supported.
+    ///
+    /// ```
+    /// use nary_tree::tree::{Tree, TreeBuilder};
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("0".to_string()).build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append("1".to_string()).append("2".to_string());
+    ///
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// let parsed = Tree::from_formatted(&s).unwrap();
+    /// assert_eq!(parsed.to_string(), s);
+    ///
+    /// let error = Tree::from_formatted("0\n└── 1\n└── 2\n").unwrap_err();
+    /// assert_eq!(error.line(), 3);
+    /// ```
+    ///
+    pub fn from_formatted(text: &str) -> Result<Tree<String>, ParseError> {
+        parse::formatted(text)
+    }
+}
+
+impl<T> Default for Tree<T> {
+    fn default() -> Self {
+        TreeBuilder::new().build()
+    }
+}
+
+impl<T: std::fmt::Display, E> Tree<T, E> {
+    /// Write formatted tree representation and nodes with debug formatting.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let root = tree.root_mut().unwrap();
+    /// let mut root = root.append(1).append(2).parent().unwrap().parent().unwrap();
+    /// root.append2(3);
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// 0
+    /// ├── 1
+    /// │   └── 2
+    /// └── 3
+    /// ");
+    /// ```
+    ///
+    /// Writes nothing if the tree is empty.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let tree = TreeBuilder::<i32>::new().build();
+    /// let mut s = String::new();
+    /// tree.write_formatted(&mut s).unwrap();
+    /// assert_eq!(&s, "");
+    /// ```
+    pub fn write_formatted<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+        self.write_formatted_lines(w, |w, node| writeln!(w, "{}", node.data()))
+    }
+
+    /// Write formatted tree representation like `write_formatted`, prefixing each node with
+    /// `#` followed by the slab index of its `NodeId`.
+    ///
+    /// This is meant for debugging: the index is what tells `NodeId`s of the same `Tree` apart
+    /// in their `Debug` output, so it lets you match the rendered tree with the ids you hold.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root(0).build();
+    /// let root = tree.root_mut().unwrap();
+    /// let mut root = root.append(1).append(2).parent().unwrap().parent().unwrap();
+    /// root.append2(3);
+    /// let mut s = String::new();
+    /// tree.write_formatted_with_ids(&mut s).unwrap();
+    /// assert_eq!(&s, "\
+    /// #0 0
+    /// ├── #1 1
+    /// │   └── #2 2
+    /// └── #3 3
+    /// ");
+    /// ```
+    pub fn write_formatted_with_ids<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+        self.write_formatted_lines(w, |w, node| {
+            writeln!(w, "#{} {}", node.node_id().index.index, node.data())
+        })
+    }
+
+    /// Returns an `Iterator` over the `Tree` in depth-first pre-order, yielding each `Node`'s
+    /// `NodeId` along with its path: the `Display` output of the root and every `Node` down to
+    /// (and including) this one, joined with `separator`.
+    ///
+    /// Each path is built from its parent's path, so no ancestors are walked per `Node`.
+    ///
+    /// ```
+    /// use nary_tree::tree::TreeBuilder;
+    ///
+    /// let mut tree = TreeBuilder::new().with_root("usr").build();
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append2("bin");
+    /// root.append("lib").append("rustlib");
+    ///
+    /// let paths = tree.iter_with_paths("/")
+    ///     .map(|(_, path)| path)
+    ///     .collect::<Vec<String>>();
+    /// assert_eq!(paths, vec!["usr", "usr/bin", "usr/lib", "usr/lib/rustlib"]);
+    /// ```
+    pub fn iter_with_paths<'a>(&'a self, separator: &'a str) -> Paths<'a, T, E> {
+        Paths::new(self.root(), separator)
+    }
+
+    fn write_formatted_lines<W, F>(&self, w: &mut W, mut write_node: F) -> std::fmt::Result
+    where
+        W: std::fmt::Write,
+        F: FnMut(&mut W, &NodeRef<T, E>) -> std::fmt::Result,
+    {
+        if let Some(root) = self.root() {
+            let node_id = root.node_id();
+            let childn = 0;
+            let level = 0;
+            let last = vec![];
+            let mut stack = vec![(node_id, childn, level, last)];
+            while let Some((node_id, childn, level, last)) = stack.pop() {
+                debug_assert_eq!(
+                    last.len(),
+                    level,
+                    "each previous level should indicate whether it has reached the last node"
+                );
+                let node = self
+                    .get(node_id)
+                    .expect("getting node of existing node ref id");
+                if childn == 0 {
+                    for i in 1..level {
+                        if last[i - 1] {
+                            write!(w, "    ")?;
+                        } else {
+                            write!(w, "│   ")?;
+                        }
+                    }
+                    if level > 0 {
+                        if last[level - 1] {
+                            write!(w, "└── ")?;
+                        } else {
+                            write!(w, "├── ")?;
+                        }
+                    }
+                    write_node(w, &node)?;
+                }
+                let mut children = node.children().skip(childn);
+                if let Some(child) = children.next() {
+                    let mut next_last = last.clone();
+                    if children.next().is_some() {
+                        stack.push((node_id, childn + 1, level, last));
+                        next_last.push(false);
+                    } else {
+                        next_last.push(true);
+                    }
+                    stack.push((child.node_id(), 0, level + 1, next_last));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+///
+/// `Tree`s are equal when they have the same shape and equal data in the same positions.  Where
+/// the `Node`s are stored, the `Tree`s' ids and orphaned `Node`s make no difference.
+///
+/// ```
+/// use nary_tree::tree::TreeBuilder;
+///
+/// let mut first = TreeBuilder::new().with_root(1).build();
+/// let mut root = first.root_mut().unwrap();
+/// root.append2(2);
+/// root.append2(3);
+///
+/// let mut second = TreeBuilder::new().with_root(1).build();
+/// let mut root = second.root_mut().unwrap();
+/// root.append2(3);
+/// root.prepend2(2);
+/// assert_eq!(first, second);
+///
+/// second.root_mut().unwrap().move_child(0, 1);
+/// assert_ne!(first, second);
+/// ```
+///
+impl<T: PartialEq, E: PartialEq> PartialEq for Tree<T, E> {
+    fn eq(&self, other: &Tree<T, E>) -> bool {
+        self.pre_order_with_child_counts()
+            .eq(other.pre_order_with_child_counts())
+    }
+}
+
+impl<T: Eq, E: Eq> Eq for Tree<T, E> {}
+
+///
+/// Hashes the shape of the `Tree` along with its data, so that equal `Tree`s hash the same.
+///
+impl<T: Hash, E: Hash> Hash for Tree<T, E> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.root().is_some().hash(state);
+        for (data, edge, child_count) in self.pre_order_with_child_counts() {
+            data.hash(state);
+            edge.hash(state);
+            child_count.hash(state);
+        }
+    }
+}
+
+impl<T: Display, E> Display for Tree<T, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_formatted(f)
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod tree_tests {
+    use super::*;
+    use crate::behaviors::RemoveBehavior::{DropChildren, OrphanChildren};
+
+    #[test]
+    fn capacity() {
+        let tree = TreeBuilder::new().with_root(1).with_capacity(5).build();
+        assert_eq!(tree.capacity(), 5);
+    }
+
+    #[test]
+    fn root_id() {
+        let tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().expect("root doesn't exist?");
+        let root = tree.get(root_id).unwrap();
+        assert_eq!(root.data(), &1);
+    }
+
+    #[test]
+    fn remove_root_drop() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().expect("root doesn't exist?");
+
+        tree.remove(root_id, RemoveBehavior::DropChildren);
+        assert!(tree.root().is_none());
+    }
+
+    #[test]
+    fn remove_root_orphan() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().expect("root doesn't exist?");
+
+        tree.remove(root_id, RemoveBehavior::OrphanChildren);
+        assert!(tree.root().is_none());
+    }
+
+    #[test]
+    fn root() {
+        let tree = TreeBuilder::new().with_root(1).build();
+        let root = tree.root().expect("root doesn't exist?");
+        assert_eq!(root.data(), &1);
+    }
+
+    #[test]
+    fn set_root_empty_tree() {
+        let mut tree = TreeBuilder::new().build();
+        let root_id = tree.set_root(1);
+
+        assert_eq!(tree.root_id(), Some(root_id));
+
+        let root = tree.get_node(root_id).unwrap();
+        assert_eq!(root.data, 1);
+        assert_eq!(root.relatives.parent, None);
+        assert_eq!(root.relatives.prev_sibling, None);
+        assert_eq!(root.relatives.next_sibling, None);
+        assert_eq!(root.relatives.first_child, None);
+        assert_eq!(root.relatives.last_child, None);
+    }
+
+    #[test]
+    fn set_root_non_empty_tree() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let old_root_id = tree.root_id().unwrap();
+        let mut old_root = tree.root_mut().unwrap();
+        let two_id = old_root.append2(2);
+        let three_id = old_root.append2(3);
+
+        let root_id = tree.set_root(0);
+
+        assert_eq!(tree.root_id(), Some(root_id));
+
+        let root = tree.get_node(root_id).unwrap();
+        assert_eq!(root.data, 0);
+        assert_eq!(root.relatives.parent, None);
+        assert_eq!(root.relatives.prev_sibling, None);
+        assert_eq!(root.relatives.next_sibling, None);
+        assert_eq!(root.relatives.first_child, Some(old_root_id));
+        assert_eq!(root.relatives.last_child, Some(old_root_id));
+
+        let old_root = tree.get_node(old_root_id).unwrap();
+        assert_eq!(old_root.data, 1);
+        assert_eq!(old_root.relatives.parent, Some(root_id));
+        assert_eq!(old_root.relatives.prev_sibling, None);
+        assert_eq!(old_root.relatives.next_sibling, None);
+        assert_eq!(old_root.relatives.first_child, Some(two_id));
+        assert_eq!(old_root.relatives.last_child, Some(three_id));
+
+        let two = tree.get_node(two_id).unwrap();
+        assert_eq!(two.relatives.parent, Some(old_root_id));
+    }
+
+    #[test]
+    fn set_root_after_removing_root() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let old_root_id = tree.root_id().unwrap();
+        tree.remove(old_root_id, DropChildren);
+
+        let root_id = tree.set_root(2);
+
+        let root = tree.get_node(root_id).unwrap();
+        assert_eq!(root.relatives.first_child, None);
+        assert_eq!(root.relatives.last_child, None);
+    }
+
+    #[test]
+    fn replace_root_data() {
+        let mut tree = TreeBuilder::new().with_root(1).with_capacity(1).build();
+        let root_id = tree.root_id().unwrap();
+
+        assert_eq!(tree.replace_root_data(2), Some(1));
+        assert_eq!(tree.root_id(), Some(root_id));
+        assert_eq!(tree.capacity(), 1);
+
+        let root = tree.get_node(root_id).unwrap();
+        assert_eq!(root.data, 2);
+        assert_eq!(root.relatives.first_child, None);
+
+        tree.remove(root_id, DropChildren);
+        assert_eq!(tree.replace_root_data(3), None);
+    }
+
+    #[test]
+    fn root_mut() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let mut root = tree.root_mut().expect("root doesn't exist?");
+
+        assert_eq!(root.data(), &mut 1);
+
+        *root.data() = 2;
+        assert_eq!(root.data(), &mut 2);
+    }
+
+    #[test]
+    fn get() {
+        let tree = TreeBuilder::new().with_root(1).build();
+
+        let root_id = tree.root_id().expect("root doesn't exist?");
+        let root = tree.get(root_id);
+        assert!(root.is_some());
+
+        let root = root.unwrap();
+        assert_eq!(root.data(), &1);
+    }
+
+    #[test]
+    fn get_mut() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+
+        let root_id = tree.root_id().expect("root doesn't exist?");
+        let root = tree.get_mut(root_id);
+        assert!(root.is_some());
+
+        let mut root = root.unwrap();
+        assert_eq!(root.data(), &mut 1);
+
+        *root.data() = 2;
+        assert_eq!(root.data(), &mut 2);
+    }
+
+    #[test]
+    fn get_node() {
+        let tree = TreeBuilder::new().with_root(1).build();
+
+        let root_id = tree.root_id().expect("root doesn't exist?");
+        let root = tree.get_node(root_id);
+        assert!(root.is_some());
+
+        let root = root.unwrap();
+        assert_eq!(root.data, 1);
+    }
+
+    #[test]
+    fn get_node_mut() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+
+        let root_id = tree.root_id().expect("root doesn't exist?");
+        let root = tree.get_node_mut(root_id);
+        assert!(root.is_some());
+
+        let root = root.unwrap();
+        assert_eq!(root.data, 1);
+
+        root.data = 2;
+        assert_eq!(root.data, 2);
+    }
+
+    #[test]
+    fn remove_drop() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+
+        let two_id;
+        let three_id;
+        let four_id;
+        let five_id;
+        {
+            let mut root = tree.root_mut().expect("root doesn't exist?");
+            two_id = root.append2(2);
+            three_id = root.append2(3);
+            four_id = root.append2(4);
+        }
+        {
+            five_id = tree
+                .get_mut(three_id)
+                .expect("three doesn't exist?")
+                .append(5)
+                .node_id();
+        }
+
+        //        1
+        //      / | \
+        //     2  3  4
+        //        |
+        //        5
+
+        tree.remove(three_id, DropChildren);
+
+        let root = tree
+            .get_node(tree.root_id().expect("tree doesn't exist?"))
+            .unwrap();
+        assert!(root.relatives.first_child.is_some());
+        assert!(root.relatives.last_child.is_some());
+        assert_eq!(root.relatives.first_child.unwrap(), two_id);
+        assert_eq!(root.relatives.last_child.unwrap(), four_id);
+
+        let two = tree.get_node(two_id);
+        assert!(two.is_some());
+
+        let two = two.unwrap();
+        assert_eq!(two.relatives.next_sibling, Some(four_id));
+
+        let four = tree.get_node(four_id);
+        assert!(four.is_some());
+
+        let four = four.unwrap();
+        assert_eq!(four.relatives.prev_sibling, Some(two_id));
+
+        let five = tree.get_node(five_id);
+        assert!(five.is_none());
+    }
+
+    #[test]
+    fn remove_into_orphan() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let two_id = tree.root_mut().unwrap().append2(2);
+        tree.get_mut(two_id).unwrap().append2(3);
+
+        let mut sink = Vec::new();
+        assert_eq!(tree.remove_into(two_id, OrphanChildren, &mut sink), 1);
+        assert_eq!(sink, vec![2]);
+
+        assert_eq!(tree.remove_into(two_id, OrphanChildren, &mut sink), 0);
+        assert_eq!(sink, vec![2]);
+    }
+
+    /// Test that there is no panic if caller tries to remove a removed node
+    #[test]
+    fn address_dropped() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let two_id = tree.root_mut().expect("root doesn't exist").node_id();
+        tree.remove(two_id, DropChildren);
+        tree.remove(two_id, DropChildren);
+    }
+
+    #[test]
+    fn remove_orphan() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+
+        let two_id;
+        let three_id;
+        let four_id;
+        let five_id;
+        {
+            let mut root = tree.root_mut().expect("root doesn't exist?");
+            two_id = root.append2(2);
+            three_id = root.append2(3);
+            four_id = root.append2(4);
+        }
+        {
+            five_id = tree
+                .get_mut(three_id)
+                .expect("three doesn't exist?")
+                .append(5)
+                .node_id();
+        }
+
+        //        1
+        //      / | \
+        //     2  3  4
+        //        |
+        //        5
+
+        tree.remove(three_id, OrphanChildren);
+
+        let root = tree
+            .get_node(tree.root_id().expect("tree doesn't exist?"))
+            .unwrap();
+        assert!(root.relatives.first_child.is_some());
+        assert!(root.relatives.last_child.is_some());
+        assert_eq!(root.relatives.first_child.unwrap(), two_id);
+        assert_eq!(root.relatives.last_child.unwrap(), four_id);
+
+        let two = tree.get_node(two_id);
+        assert!(two.is_some());
+
+        let two = two.unwrap();
+        assert_eq!(two.relatives.next_sibling, Some(four_id));
+
+        let four = tree.get_node(four_id);
+        assert!(four.is_some());
+
+        let four = four.unwrap();
+        assert_eq!(four.relatives.prev_sibling, Some(two_id));
+
+        let five = tree.get_node(five_id);
+        assert!(five.is_some());
+
+        let five = five.unwrap();
+        assert_eq!(five.relatives.parent, None);
+    }
+
+    #[test]
+    fn get_path_data_mut() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().expect("root doesn't exist?");
+        let two_id = tree.root_mut().unwrap().append2(2);
+        let three_id = tree.get_mut(two_id).unwrap().append2(3);
+        let four_id = tree.root_mut().unwrap().append2(4);
+
+        let path = tree.get_path_data_mut(two_id, three_id).unwrap();
+        assert_eq!(path, vec![&mut 2, &mut 3]);
+
+        let path = tree.get_path_data_mut(root_id, root_id).unwrap();
+        assert_eq!(path, vec![&mut 1]);
+
+        assert!(tree.get_path_data_mut(four_id, three_id).is_none());
+        assert!(tree.get_path_data_mut(three_id, two_id).is_none());
+
+        tree.remove(three_id, DropChildren);
+        assert!(tree.get_path_data_mut(root_id, three_id).is_none());
+    }
+
+    #[test]
+    fn bubble_up() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        let one_id = root.append2(0);
+        let two_id = tree.get_mut(one_id).unwrap().append2(0);
+        let three_id = tree.get_mut(two_id).unwrap().append2(5);
+
+        let mut calls = Vec::new();
+        tree.bubble_up(three_id, |parent, child| {
+            calls.push((*parent, *child));
+            *parent = *child + 1;
+        });
+        assert_eq!(calls, vec![(0, 5), (0, 6), (0, 7)]);
+        let values: Vec<i32> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|n| *n.data())
+            .collect();
+        assert_eq!(values, vec![8, 7, 6, 5]);
+
+        tree.bubble_up(tree.root_id().unwrap(), |_, _| panic!("root has no parent"));
+
+        tree.remove(one_id, OrphanChildren);
+        tree.bubble_up(three_id, |parent, child| *parent += child);
+        assert_eq!(tree.get(two_id).unwrap().data(), &11);
+        assert_eq!(tree.root().unwrap().data(), &8);
+
+        tree.bubble_up(one_id, |_, _| panic!("removed node"));
+    }
+
+    #[test]
+    fn get_many_mut() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().expect("root doesn't exist?");
+        let two_id = tree.root_mut().unwrap().append2(2);
+        let three_id = tree.get_mut(two_id).unwrap().append2(3);
+
+        let [three, root, two] = tree.get_many_mut([three_id, root_id, two_id]).unwrap();
+        *three += 10;
+        *root += 20;
+        *two += 30;
+        let values: Vec<i32> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|n| *n.data())
+            .collect();
+        assert_eq!(values, vec![21, 32, 13]);
+
+        assert_eq!(tree.get_many_mut([]), Some([]));
+        assert!(tree.get_many_mut([root_id, three_id, root_id]).is_none());
+
+        let other = TreeBuilder::new().with_root(0).build();
+        let other_root_id = other.root_id().unwrap();
+        assert!(tree.get_many_mut([root_id, other_root_id]).is_none());
+
+        tree.remove(three_id, DropChildren);
+        assert!(tree.get_many_mut([root_id, three_id]).is_none());
+    }
+
+    #[test]
+    fn insert_after_last_child() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().expect("root doesn't exist?");
+        let two_id = tree.root_mut().unwrap().append2(2);
+
+        let three_id = tree.insert_after(two_id, 3).unwrap();
+
+        let root = tree.get_node(root_id).unwrap();
+        assert_eq!(root.relatives.first_child, Some(two_id));
+        assert_eq!(root.relatives.last_child, Some(three_id));
+
+        let two = tree.get_node(two_id).unwrap();
+        assert_eq!(two.relatives.next_sibling, Some(three_id));
+
+        let three = tree.get_node(three_id).unwrap();
+        assert_eq!(three.relatives.parent, Some(root_id));
+        assert_eq!(three.relatives.prev_sibling, Some(two_id));
+        assert_eq!(three.relatives.next_sibling, None);
+    }
+
+    #[test]
+    fn insert_before_first_child() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().expect("root doesn't exist?");
+        let three_id = tree.root_mut().unwrap().append2(3);
+
+        let two_id = tree.insert_before(three_id, 2).unwrap();
+
+        let root = tree.get_node(root_id).unwrap();
+        assert_eq!(root.relatives.first_child, Some(two_id));
+        assert_eq!(root.relatives.last_child, Some(three_id));
+
+        let two = tree.get_node(two_id).unwrap();
+        assert_eq!(two.relatives.parent, Some(root_id));
+        assert_eq!(two.relatives.prev_sibling, None);
+        assert_eq!(two.relatives.next_sibling, Some(three_id));
+
+        let three = tree.get_node(three_id).unwrap();
+        assert_eq!(three.relatives.prev_sibling, Some(two_id));
+    }
+
+    #[test]
+    fn insert_sibling_of_root_or_removed() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().expect("root doesn't exist?");
+        let two_id = tree.root_mut().unwrap().append2(2);
+        tree.remove(two_id, DropChildren);
+
+        assert!(tree.insert_after(root_id, 3).is_none());
+        assert!(tree.insert_before(root_id, 3).is_none());
+        assert!(tree.insert_after(two_id, 3).is_none());
+        assert!(tree.insert_before(two_id, 3).is_none());
+    }
+
+    #[test]
+    fn spine() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().unwrap();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let four_id = root.append2(4);
+        let three_id = tree.get_mut(two_id).unwrap().append2(3);
+
+        assert_eq!(tree.spine(root_id), Some(vec![root_id]));
+        assert_eq!(tree.spine(three_id), Some(vec![root_id, two_id, three_id]));
+        assert_eq!(tree.spine(four_id), Some(vec![root_id, four_id]));
+
+        tree.remove(two_id, OrphanChildren);
+        assert_eq!(tree.spine(two_id), None);
+        assert_eq!(tree.spine(three_id), None);
+    }
+
+    #[test]
+    fn find_path() {
+        let empty: Tree<i32> = Tree::new();
+        assert_eq!(empty.find_path(|_| true), None);
+
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().unwrap();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let four_id = root.append2(4);
+        let three_id = tree.get_mut(two_id).unwrap().append2(3);
+        let orphan_id = tree.get_mut(four_id).unwrap().append2(0);
+        tree.get_mut(orphan_id).unwrap().append2(5);
+        tree.remove(orphan_id, OrphanChildren);
+
+        assert_eq!(tree.find_path(|data| *data == 1), Some(vec![root_id]));
+        assert_eq!(
+            tree.find_path(|data| *data > 2),
+            Some(vec![root_id, two_id, three_id])
+        );
+        assert_eq!(tree.find_path(|data| *data == 5), None);
+
+        let mut visited = Vec::new();
+        tree.find_path(|data| {
+            visited.push(*data);
+            *data == 3
+        });
+        assert_eq!(visited, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn pre_order_index() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().unwrap();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let four_id = root.append2(4);
+        let three_id = tree.get_mut(two_id).unwrap().append2(3);
+        let five_id = tree.get_mut(four_id).unwrap().append2(5);
+
+        let ids = [root_id, two_id, three_id, four_id, five_id];
+        for (index, node_id) in ids.iter().enumerate() {
+            assert_eq!(tree.pre_order_index(*node_id), Some(index));
+        }
+
+        tree.remove(two_id, OrphanChildren);
+        assert_eq!(tree.pre_order_index(two_id), None);
+        assert_eq!(tree.pre_order_index(three_id), None);
+        assert_eq!(tree.pre_order_index(five_id), Some(2));
+
+        // the cached positions follow changes to the shape of the tree
+        let six_id = tree.root_mut().unwrap().prepend2(6);
+        assert_eq!(tree.pre_order_index(six_id), Some(1));
+        assert_eq!(tree.pre_order_index(five_id), Some(3));
+        assert_eq!(tree.range_count(six_id, five_id), Some(3));
+
+        let other = TreeBuilder::new().with_root(1).build();
+        assert_eq!(tree.pre_order_index(other.root_id().unwrap()), None);
+    }
+
+    #[test]
+    fn reparent_many() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let root_id = tree.root_id().unwrap();
+        let mut root = tree.root_mut().unwrap();
+        let one_id = root.append2(1);
+        let two_id = root.append2(2);
+        let five_id = root.append2(5);
+        let mut two = tree.get_mut(two_id).unwrap();
+        let three_id = two.append2(3);
+        let four_id = two.append2(4);
+
+        // 0
+        // ├── 1
+        // ├── 2
+        // │   ├── 3
+        // │   └── 4
+        // └── 5
+
+        assert_eq!(tree.reparent_many(&[five_id, three_id], one_id), Ok(()));
+        let one = tree.get(one_id).unwrap();
+        assert_eq!(one.children_ids(), vec![five_id, three_id]);
+        assert_eq!(
+            tree.get(three_id).unwrap().parent().unwrap().node_id(),
+            one_id
+        );
+        assert_eq!(tree.get(two_id).unwrap().children_ids(), vec![four_id]);
+        assert_eq!(tree.root().unwrap().children_ids(), vec![one_id, two_id]);
+        assert!(tree.get(two_id).unwrap().next_sibling().is_none());
+
+        // moving a whole sub-tree under a node
+        assert_eq!(tree.reparent_many(&[one_id], four_id), Ok(()));
+        let values: Vec<i32> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|n| *n.data())
+            .collect();
+        assert_eq!(values, vec![0, 2, 4, 1, 5, 3]);
+
+        // a failed move leaves everything in place
+        let before = tree.to_string();
+        assert_eq!(
+            tree.reparent_many(&[three_id, two_id], five_id),
+            Err(MoveError::WouldCreateCycle(two_id))
+        );
+        assert_eq!(
+            tree.reparent_many(&[five_id], five_id),
+            Err(MoveError::WouldCreateCycle(five_id))
+        );
+        assert_eq!(
+            tree.reparent_many(&[three_id, root_id], five_id),
+            Err(MoveError::CannotMoveRoot)
+        );
+        assert_eq!(tree.to_string(), before);
+
+        tree.remove(three_id, DropChildren);
+        assert_eq!(
+            tree.reparent_many(&[five_id], three_id),
+            Err(MoveError::InvalidNodeId(three_id))
+        );
+        assert_eq!(
+            tree.reparent_many(&[three_id], five_id),
+            Err(MoveError::InvalidNodeId(three_id))
+        );
+        assert_eq!(
+            tree.get(five_id).unwrap().parent().unwrap().node_id(),
+            one_id
+        );
+    }
+
+    #[test]
+    fn memory_footprint() {
+        let empty: Tree<u8> = Tree::new();
+        assert_eq!(empty.memory_footprint(), std::mem::size_of::<Tree<u8>>());
+
+        let mut tree = TreeBuilder::new().with_root(0u64).with_capacity(4).build();
+        let footprint = tree.memory_footprint();
+        assert!(footprint >= std::mem::size_of::<Tree<u64>>() + 4 * std::mem::size_of::<u64>());
+
+        // in-use nodes don't change the estimate, only the capacity does
+        tree.root_mut().unwrap().append2(1);
+        assert_eq!(tree.memory_footprint(), footprint);
+        for i in 2..10 {
+            tree.root_mut().unwrap().append2(i);
+        }
+        assert!(tree.memory_footprint() > footprint);
+
+        // so do keys, dirty marks and the cached pre-order
+        let footprint = tree.memory_footprint();
+        let root_id = tree.root_id().unwrap();
+        tree.set_key(root_id, 1);
+        assert!(tree.memory_footprint() > footprint);
+        let footprint = tree.memory_footprint();
+        tree.root_mut().unwrap().data_mut_tracked();
+        assert!(tree.memory_footprint() > footprint);
+        let footprint = tree.memory_footprint();
+        tree.range_count(root_id, root_id);
+        assert!(tree.memory_footprint() >= footprint + 10 * std::mem::size_of::<NodeId>() * 2);
+    }
+
+    #[test]
+    fn from_parent_array() {
+        let tree: Tree<i32> = Tree::from_parent_array(Vec::new()).unwrap();
+        assert!(tree.root().is_none());
+
+        // parents may come after their children
+        let tree =
+            Tree::from_parent_array(vec![(3, Some(2)), (4, Some(3)), (2, Some(3)), (1, None)])
+                .unwrap();
+        let values: Vec<i32> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|n| *n.data())
+            .collect();
+        assert_eq!(values, vec![1, 4, 2, 3]);
+        assert_eq!(tree.root().unwrap().first_child().unwrap().data(), &4);
+    }
+
+    #[test]
+    fn from_parent_array_errors() {
+        assert_eq!(
+            Tree::from_parent_array(vec![(1, Some(0))]).unwrap_err(),
+            BuildError::NoRoot
+        );
+        assert_eq!(
+            Tree::from_parent_array(vec![(1, None), (2, Some(0)), (3, None)]).unwrap_err(),
+            BuildError::MultipleRoots(2)
+        );
+        assert_eq!(
+            Tree::from_parent_array(vec![(1, None), (2, Some(3))]).unwrap_err(),
+            BuildError::InvalidParent(1)
+        );
+        assert_eq!(
+            Tree::from_parent_array(vec![
+                (1, None),
+                (2, Some(0)),
+                (3, Some(3)),
+                (4, Some(4)),
+                (5, Some(2))
+            ])
+            .unwrap_err(),
+            BuildError::Cycle(2)
+        );
+        assert_eq!(
+            BuildError::Cycle(2).to_string(),
+            "item 2 is part of a cycle"
+        );
+    }
+
+    #[test]
+    fn count_by() {
+        let empty: Tree<i32> = Tree::new();
+        assert_eq!(empty.count_by(|_| true), 0);
+
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        root.append2(5);
+        let mut two = tree.get_mut(two_id).unwrap();
+        let three_id = two.append2(3);
+        two.append2(4);
+        tree.get_mut(three_id).unwrap().append2(6);
+        assert_eq!(tree.count_by(|_| true), 6);
+        assert_eq!(tree.count_by(|data| data % 2 == 1), 3);
+
+        // orphaned nodes aren't reachable from the root
+        tree.remove(two_id, RemoveBehavior::OrphanChildren);
+        assert_eq!(tree.count_by(|_| true), 2);
+        assert_eq!(tree.count_by(|data| *data > 2), 1);
+    }
+
+    #[test]
+    fn insert_between() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().unwrap();
+        let two_id = tree.root_mut().unwrap().append2(2);
+        let three_id = tree.get_mut(two_id).unwrap().append2(3);
+
+        // the only child, which is both the first and the last
+        let four_id = tree.insert_between(two_id, three_id, 4).unwrap();
+        let two = tree.get(two_id).unwrap();
+        assert_eq!(two.first_child().unwrap().node_id(), four_id);
+        assert_eq!(two.last_child().unwrap().node_id(), four_id);
+        let four = tree.get(four_id).unwrap();
+        assert_eq!(four.parent().unwrap().node_id(), two_id);
+        assert_eq!(four.first_child().unwrap().node_id(), three_id);
+        assert_eq!(four.last_child().unwrap().node_id(), three_id);
+        let three = tree.get(three_id).unwrap();
+        assert_eq!(three.parent().unwrap().node_id(), four_id);
+        assert!(three.prev_sibling().is_none());
+        assert!(three.next_sibling().is_none());
+
+        // the first of several children
+        let five_id = tree.root_mut().unwrap().append2(5);
+        let six_id = tree.insert_between(root_id, two_id, 6).unwrap();
+        let root = tree.root().unwrap();
+        assert_eq!(root.first_child().unwrap().node_id(), six_id);
+        let six = tree.get(six_id).unwrap();
+        assert_eq!(six.next_sibling().unwrap().node_id(), five_id);
+        assert_eq!(
+            tree.get(five_id).unwrap().prev_sibling().unwrap().node_id(),
+            six_id
+        );
+
+        assert!(tree.insert_between(root_id, three_id, 7).is_none());
+        assert!(tree.insert_between(five_id, root_id, 7).is_none());
+        tree.remove(five_id, RemoveBehavior::DropChildren);
+        assert!(tree.insert_between(root_id, five_id, 7).is_none());
+        assert_eq!(tree.count_by(|_| true), 5);
+    }
+
+    #[test]
+    fn replace_node() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().unwrap();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+        let four_id = root.append2(4);
+        let five_id = tree.get_mut(three_id).unwrap().append2(5);
+
+        // a middle child keeps its siblings and children
+        assert_eq!(tree.replace_node(three_id, 6), Some(3));
+        assert!(tree.get(three_id).is_none());
+        let two = tree.get(two_id).unwrap();
+        let six = two.next_sibling().unwrap();
+        let six_id = six.node_id();
+        assert_eq!(six.data(), &6);
+        assert_eq!(six.parent().unwrap().node_id(), root_id);
+        assert_eq!(six.next_sibling().unwrap().node_id(), four_id);
+        assert_eq!(
+            tree.get(four_id).unwrap().prev_sibling().unwrap().node_id(),
+            six_id
+        );
+        assert_eq!(
+            tree.get(five_id).unwrap().parent().unwrap().node_id(),
+            six_id
+        );
+
+        // the root stays the root
+        assert_eq!(tree.replace_node(root_id, 7), Some(1));
+        let root = tree.root().unwrap();
+        assert_eq!(root.data(), &7);
+        let children: Vec<i32> = root.children().map(|n| *n.data()).collect();
+        assert_eq!(children, vec![2, 6, 4]);
+        assert_eq!(
+            tree.get(two_id).unwrap().parent().unwrap().node_id(),
+            root.node_id()
+        );
+
+        // a leaf
+        assert_eq!(tree.replace_node(four_id, 8), Some(4));
+        let root = tree.root().unwrap();
+        assert_eq!(root.last_child().unwrap().data(), &8);
+        assert_eq!(tree.count_by(|_| true), 5);
+
+        assert_eq!(tree.replace_node(four_id, 9), None);
+    }
+
+    #[test]
+    fn successor_and_predecessor() {
+        let empty_root = TreeBuilder::new().with_root(0).build();
+        let root_id = empty_root.root_id().unwrap();
+        assert_eq!(empty_root.successor(root_id), None);
+        assert_eq!(empty_root.predecessor(root_id), None);
+
+        // a node with three children, one with a single child and a deeper chain:
+        //
+        //   0
+        //   ├── 1
+        //   │   └── 2
+        //   ├── 3
+        //   │   ├── 4
+        //   │   ├── 5
+        //   │   └── 6
+        //   └── 7
+        //
+        // in-order: 2 1 0 4 3 5 6 7
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        let one_id = root.append2(1);
+        let three_id = root.append2(3);
+        root.append2(7);
+        let mut three = tree.get_mut(three_id).unwrap();
+        three.append2(4);
+        three.append2(5);
+        three.append2(6);
+        tree.get_mut(one_id).unwrap().append2(2);
+
+        let root_id = tree.root_id().unwrap();
+        let mut in_order = Vec::new();
+        let mut current = Some(tree.in_order_first(root_id));
+        while let Some(node_id) = current {
+            in_order.push(*tree.get(node_id).unwrap().data());
+            current = tree.successor(node_id);
+        }
+        assert_eq!(in_order, vec![2, 1, 0, 4, 3, 5, 6, 7]);
+
+        let mut reversed = Vec::new();
+        let mut current = Some(tree.in_order_last(root_id));
+        while let Some(node_id) = current {
+            reversed.push(*tree.get(node_id).unwrap().data());
+            current = tree.predecessor(node_id);
+        }
+        reversed.reverse();
+        assert_eq!(reversed, in_order);
+    }
+
+    #[test]
+    fn cached_pre_order() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().unwrap();
+        let two_id = tree.root_mut().unwrap().append2(2);
+        let three_id = tree.root_mut().unwrap().append2(3);
+
+        let cached = tree.cached_pre_order().as_ptr();
+        assert_eq!(tree.cached_pre_order(), &[root_id, two_id, three_id]);
+        assert_eq!(tree.cached_pre_order().as_ptr(), cached);
+
+        // data changes keep the cache
+        *tree.get_mut(two_id).unwrap().data() = 4;
+        assert_eq!(tree.cached_pre_order().as_ptr(), cached);
+
+        tree.reverse();
+        assert_eq!(tree.cached_pre_order(), &[root_id, three_id, two_id]);
+        tree.remove(three_id, RemoveBehavior::DropChildren);
+        assert_eq!(tree.cached_pre_order(), &[root_id, two_id]);
+        tree.remove(root_id, RemoveBehavior::OrphanChildren);
+        assert!(tree.cached_pre_order().is_empty());
+        let new_root_id = tree.set_root(5);
+        assert_eq!(tree.cached_pre_order(), &[new_root_id]);
+    }
+
+    #[test]
+    fn generate() {
+        let tree = Tree::generate(0, |_| vec![1, 2], 0);
+        assert_eq!(tree.count_by(|_| true), 1);
+
+        // expanding on the way down, leaves are made wherever `expand` returns nothing
+        let tree = Tree::generate(3, |n| (0..*n).collect(), 10);
+        let values: Vec<u32> = tree
+            .root()
+            .unwrap()
+            .traverse_level_order()
+            .map(|n| *n.data())
+            .collect();
+        assert_eq!(values, vec![3, 0, 1, 2, 0, 0, 1, 0]);
+
+        // a single chain deeper than the default stack could recurse through
+        let tree = Tree::generate(0u32, |n| vec![n + 1], 100_000);
+        assert_eq!(tree.count_by(|_| true), 100_001);
+        assert_eq!(tree.root().unwrap().last_descendant().data(), &100_000);
+    }
+
+    #[test]
+    fn keys() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().unwrap();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+        let four_id = tree.get_mut(three_id).unwrap().append2(4);
+
+        assert!(tree.set_key(two_id, 2));
+        assert!(tree.set_key(four_id, 4));
+        assert!(tree.set_key(three_id, 3));
+        assert!(!tree.set_key(root_id, 3));
+        assert_eq!(tree.find_by_key(3), Some(three_id));
+
+        // re-keying a node frees its old key
+        assert!(tree.set_key(three_id, 30));
+        assert_eq!(tree.find_by_key(3), None);
+        assert_eq!(tree.find_by_key(30), Some(three_id));
+        assert!(tree.set_key(three_id, 30));
+
+        // removed nodes (and dropped children) lose their keys
+        tree.remove(three_id, RemoveBehavior::DropChildren);
+        assert_eq!(tree.find_by_key(30), None);
+        assert_eq!(tree.find_by_key(4), None);
+        assert!(tree.set_key(root_id, 4));
+        assert!(!tree.set_key(three_id, 5));
+
+        // and compaction keeps them on the moved nodes
+        let mut root = tree.root_mut().unwrap();
+        root.append_with_key(5, 5).unwrap();
+        assert!(root.append_with_key(6, 5).is_none());
+        tree.remove(two_id, RemoveBehavior::DropChildren);
+        tree.compact_internal();
+        let five_id = tree.find_by_key(5).unwrap();
+        assert_eq!(tree.get(five_id).unwrap().data(), &5);
+        assert_eq!(tree.key_of(five_id), Some(5));
+        assert_eq!(tree.find_by_key(4), tree.root_id());
+        assert_eq!(tree.count_by(|_| true), 2);
+    }
+
+    #[test]
+    fn ancestors_data_mut() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().unwrap();
+        let two_id = tree.root_mut().unwrap().append2(2);
+        let three_id = tree.get_mut(two_id).unwrap().append2(3);
+
+        assert!(tree.ancestors_data_mut(root_id).unwrap().is_empty());
+
+        let ancestors = tree.ancestors_data_mut(three_id).unwrap();
+        assert_eq!(
+            ancestors.iter().map(|d| **d).collect::<Vec<i32>>(),
+            vec![2, 1]
+        );
+        for data in ancestors {
+            *data *= 10;
+        }
+        let values: Vec<i32> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|n| *n.data())
+            .collect();
+        assert_eq!(values, vec![10, 20, 3]);
+
+        tree.remove(three_id, RemoveBehavior::DropChildren);
+        assert!(tree.ancestors_data_mut(three_id).is_none());
+    }
+
+    #[test]
+    fn are_siblings() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().unwrap();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+        let four_id = tree.get_mut(two_id).unwrap().append2(4);
+        let five_id = tree.get_mut(three_id).unwrap().append2(5);
+
+        assert!(tree.are_siblings(three_id, two_id));
+        assert!(!tree.are_siblings(four_id, five_id));
+        assert!(!tree.are_siblings(root_id, root_id));
+
+        // orphans don't share a parent
+        tree.remove(two_id, RemoveBehavior::OrphanChildren);
+        tree.remove(three_id, RemoveBehavior::OrphanChildren);
+        assert!(!tree.are_siblings(four_id, five_id));
+        assert!(!tree.are_siblings(two_id, three_id));
+
+        let other = TreeBuilder::new().with_root(1).build();
+        assert!(!tree.are_siblings(root_id, other.root_id().unwrap()));
+    }
+
+    #[test]
+    fn edges() {
+        let mut tree: Tree<&str, u8> = TreeBuilder::new().with_root("a").build_with_edges();
+        let root_id = tree.root_id().unwrap();
+        let mut root = tree.root_mut().unwrap();
+        let b_id = root.append_with_edge("b", 1);
+        let c_id = root.append2("c");
+        let d_id = tree.get_mut(b_id).unwrap().append_with_edge("d", 2);
+
+        assert_eq!(tree.get(b_id).unwrap().parent_edge(), Some(&1));
+        assert_eq!(tree.get(c_id).unwrap().parent_edge(), None);
+
+        // edges stay with their node when it moves, and are hidden while it has no parent
+        tree.reparent_many(&[d_id], c_id).unwrap();
+        assert_eq!(tree.get(d_id).unwrap().parent_edge(), Some(&2));
+        tree.remove(c_id, OrphanChildren);
+        assert_eq!(tree.get(d_id).unwrap().parent_edge(), None);
+        tree.get_mut(root_id).unwrap().append_with_edge("c", 3);
+
+        let copy = tree.clone_subtree(root_id).unwrap();
+        assert!(copy == tree);
+        let mut other: Tree<&str, u8> = TreeBuilder::new().with_root("a").build_with_edges();
+        let mut root = other.root_mut().unwrap();
+        root.append_with_edge("b", 1);
+        root.append_with_edge("c", 4);
+        assert!(other != tree);
+
+        let replaced = tree.root().unwrap().last_child().unwrap().node_id();
+        tree.replace_node(replaced, "e");
+        let mapped = tree.map_into(|data| data.to_uppercase());
+        let root = mapped.root().unwrap();
+        let edges: Vec<Option<u8>> = root
+            .children()
+            .map(|child| child.parent_edge().copied())
+            .collect();
+        assert_eq!(edges, vec![Some(1), Some(3)]);
+        assert_eq!(root.last_child().unwrap().data(), "E");
+    }
+
+    #[test]
+    fn drain_filter() {
+        let mut empty: Tree<i32> = Tree::new();
+        assert!(empty.drain_filter(|_| true).is_empty());
+
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        let one_id = root.append2(1);
+        root.append2(6);
+        let mut one = tree.get_mut(one_id).unwrap();
+        let two_id = one.append2(2);
+        one.append2(5);
+        let mut two = tree.get_mut(two_id).unwrap();
+        two.append2(3);
+        two.append2(4);
+
+        // nested matches: the children move up as many levels as needed
+        let drained = tree.drain_filter(|data| *data == 1 || *data == 2 || *data == 4);
+        assert_eq!(drained, vec![1, 2, 4]);
+        let children: Vec<i32> = tree.root().unwrap().children().map(|n| *n.data()).collect();
+        assert_eq!(children, vec![3, 5, 6]);
+        let root = tree.root().unwrap();
+        assert_eq!(root.first_child().unwrap().data(), &3);
+        assert_eq!(root.last_child().unwrap().data(), &6);
+        assert!(tree.get(one_id).is_none());
+
+        // the root stays put
+        assert_eq!(tree.drain_filter(|_| true), vec![3, 5, 6]);
+        assert_eq!(tree.root().unwrap().data(), &0);
+        assert!(tree.root().unwrap().first_child().is_none());
+        assert_eq!(tree.count_by(|_| true), 1);
+    }
+
+    #[test]
+    fn swap_subtrees() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let root_id = tree.root_id().unwrap();
+        let mut root = tree.root_mut().unwrap();
+        let one_id = root.append2(1);
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+        let four_id = root.append2(4);
+        let children = |tree: &Tree<i32>| -> Vec<i32> {
+            tree.root().unwrap().children().map(|n| *n.data()).collect()
+        };
+
+        // adjacent siblings, both ways round
+        tree.swap_subtrees(one_id, two_id).unwrap();
+        assert_eq!(children(&tree), vec![2, 1, 3, 4]);
+        tree.swap_subtrees(one_id, two_id).unwrap();
+        assert_eq!(children(&tree), vec![1, 2, 3, 4]);
+
+        // the first and the last child
+        tree.swap_subtrees(four_id, one_id).unwrap();
+        assert_eq!(children(&tree), vec![4, 2, 3, 1]);
+        let root = tree.root().unwrap();
+        assert_eq!(root.first_child().unwrap().node_id(), four_id);
+        assert_eq!(root.last_child().unwrap().node_id(), one_id);
+        assert!(tree.get(one_id).unwrap().next_sibling().is_none());
+        assert!(tree.get(four_id).unwrap().prev_sibling().is_none());
+
+        tree.swap_subtrees(three_id, three_id).unwrap();
+        assert_eq!(children(&tree), vec![4, 2, 3, 1]);
+
+        // an orphan can swap places with the root
+        let five_id = tree.get_mut(two_id).unwrap().append2(5);
+        assert_eq!(tree.swap_subtrees(five_id, two_id), Err(SwapError::Nested));
+        assert_eq!(tree.swap_subtrees(root_id, five_id), Err(SwapError::Nested));
+        tree.remove(three_id, OrphanChildren);
+        assert_eq!(
+            tree.swap_subtrees(three_id, one_id),
+            Err(SwapError::InvalidNodeId(three_id))
+        );
+        tree.remove(two_id, OrphanChildren);
+        tree.swap_subtrees(five_id, root_id).unwrap();
+        assert_eq!(tree.root_id(), Some(five_id));
+        assert!(tree.get(root_id).unwrap().parent().is_none());
+        assert_eq!(tree.count_by(|_| true), 1);
+    }
+
+    #[test]
+    fn require_root() {
+        let tree = TreeBuilder::new()
+            .with_root(1)
+            .with_capacity(5)
+            .with_growth_policy(GrowthPolicy::Fixed(2))
+            .require_root()
+            .build()
+            .unwrap();
+        assert_eq!(tree.capacity(), 5);
+        assert!(tree.root_id().is_some());
+
+        let result = TreeBuilder::<i32>::new().require_root().build();
+        assert_eq!(result.unwrap_err(), BuildError::NoRoot);
+    }
+
+    #[test]
+    fn trim_leaves() {
+        let mut tree = Tree::new();
+        assert!(tree.trim_leaves().is_empty());
+
+        tree.set_root(1);
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+        root.append2(4);
+        let five_id = tree.get_mut(two_id).unwrap().append2(5);
+        tree.get_mut(five_id).unwrap().append2(6);
+        tree.get_mut(three_id).unwrap().append2(7);
+
+        assert_eq!(tree.trim_leaves(), vec![6, 7, 4]);
+        assert_eq!(tree.root().unwrap().traverse_pre_order().count(), 4);
+        assert!(tree.get(five_id).is_some());
+        assert_eq!(tree.trim_leaves(), vec![5, 3]);
+        assert_eq!(tree.trim_leaves(), vec![2]);
+        assert_eq!(tree.trim_leaves(), vec![1]);
+        assert!(tree.root_id().is_none());
+    }
+
+    #[test]
+    fn into_flat() {
+        let (data, parents) = Tree::<i32>::new().into_flat();
+        assert!(data.is_empty() && parents.is_empty());
+
+        let items = vec![
+            (1, None),
+            (2, Some(0)),
+            (3, Some(0)),
+            (4, Some(1)),
+            (5, Some(3)),
+            (6, Some(1)),
+        ];
+        let tree = Tree::from_parent_array(items.clone()).unwrap();
+        let expected = Tree::from_parent_array(items).unwrap();
+        let (data, parents) = tree.into_flat();
+        assert_eq!(data, vec![1, 2, 4, 5, 6, 3]);
+        assert_eq!(
+            parents,
+            vec![None, Some(0), Some(1), Some(2), Some(1), Some(0)]
+        );
+
+        let rebuilt = Tree::from_parent_array(data.into_iter().zip(parents)).unwrap();
+        assert_eq!(rebuilt, expected);
+    }
+
+    #[test]
+    fn remove_with() {
+        let mut tree = Tree::new();
+        tree.set_root(1);
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+        let four_id = tree.get_mut(two_id).unwrap().append2(4);
+        tree.get_mut(two_id).unwrap().append2(5);
+        tree.get_mut(four_id).unwrap().append2(6);
+
+        let mut dropped = Vec::new();
+        assert_eq!(
+            tree.remove_with(three_id, |data| dropped.push(data)),
+            Some(3)
+        );
+        assert!(dropped.is_empty());
+        assert_eq!(tree.remove_with(three_id, |data| dropped.push(data)), None);
+
+        assert_eq!(tree.remove_with(two_id, |data| dropped.push(data)), Some(2));
+        assert_eq!(dropped, vec![4, 5, 6]);
+        assert!(tree.get(four_id).is_none());
+
+        let root_id = tree.root_id().unwrap();
+        assert_eq!(
+            tree.remove_with(root_id, |data| dropped.push(data)),
+            Some(1)
+        );
+        assert!(tree.root().is_none());
+        assert_eq!(dropped, vec![4, 5, 6]);
+    }
+
+    #[test]
+    fn dirty_nodes() {
+        let mut tree = Tree::new();
+        let root_id = tree.set_root(1);
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+        let four_id = tree.get_mut(two_id).unwrap().append2(4);
+        let five_id = tree.get_mut(four_id).unwrap().append2(5);
+
+        *tree.get_mut(two_id).unwrap().data() = 20;
+        assert!(tree.dirty_nodes().is_empty());
+
+        *tree.get_mut(four_id).unwrap().data_mut_tracked() = 40;
+        assert_eq!(tree.get(four_id).unwrap().data(), &40);
+        let mut dirty = tree.dirty_nodes();
+        dirty.sort();
+        let mut expected = vec![root_id, two_id, four_id];
+        expected.sort();
+        assert_eq!(dirty, expected);
+
+        *tree.get_mut(three_id).unwrap().data_mut_tracked() = 30;
+        assert_eq!(tree.dirty_nodes().len(), 4);
+        assert!(!tree.dirty_nodes().contains(&five_id));
+
+        tree.remove(four_id, DropChildren);
+        assert_eq!(tree.dirty_nodes().len(), 3);
+        assert!(!tree.dirty_nodes().contains(&four_id));
+
+        tree.clear_dirty();
+        assert!(tree.dirty_nodes().is_empty());
+    }
+
+    #[test]
+    fn is_empty_and_single_node() {
+        let mut tree = Tree::new();
+        assert!(tree.is_empty());
+        assert!(!tree.is_single_node());
+
+        let root_id = tree.set_root(1);
+        assert!(!tree.is_empty());
+        assert!(tree.is_single_node());
+
+        let two_id = tree.root_mut().unwrap().append2(2);
+        assert!(!tree.is_single_node());
+
+        tree.remove(two_id, DropChildren);
+        assert!(tree.is_single_node());
+
+        tree.root_mut().unwrap().append2(3);
+        tree.remove(root_id, OrphanChildren);
+        assert!(tree.is_empty());
+        assert!(!tree.is_single_node());
+    }
+
+    #[test]
+    fn fragmentation_ratio() {
+        let mut tree = Tree::new();
+        assert_eq!(tree.fragmentation_ratio(), 0.0);
+
+        let mut tree_with_capacity = TreeBuilder::<i32>::new().with_capacity(8).build();
+        assert_eq!(tree_with_capacity.fragmentation_ratio(), 0.0);
+        tree_with_capacity.set_root(1);
+        assert_eq!(tree_with_capacity.fragmentation_ratio(), 0.875);
+
+        tree.set_root(0);
+        let mut root = tree.root_mut().unwrap();
+        let ids: Vec<NodeId> = (1..8).map(|i| root.append2(i)).collect();
+        let capacity = tree.capacity() as f64;
+        assert_eq!(tree.fragmentation_ratio(), (capacity - 8.0) / capacity);
+
+        for node_id in &ids[..4] {
+            tree.remove(*node_id, DropChildren);
+        }
+        assert_eq!(tree.fragmentation_ratio(), (capacity - 4.0) / capacity);
+
+        // emptied out, the capacity left behind doesn't count as fragmentation
+        let root_id = tree.root_id().unwrap();
+        tree.remove(root_id, DropChildren);
+        assert_eq!(tree.fragmentation_ratio(), 0.0);
+    }
+
+    #[test]
+    fn move_up_and_down() {
+        let mut tree = Tree::new();
+        let root_id = tree.set_root(0);
+        let mut root = tree.root_mut().unwrap();
+        let one_id = root.append2(1);
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+        let four_id = tree.get_mut(two_id).unwrap().append2(4);
+
+        let children = |tree: &Tree<i32>| -> Vec<i32> {
+            tree.root().unwrap().children().map(|c| *c.data()).collect()
+        };
+
+        assert!(tree.move_up(three_id));
+        assert_eq!(children(&tree), vec![1, 3, 2]);
+        assert!(tree.move_up(three_id));
+        assert_eq!(children(&tree), vec![3, 1, 2]);
+        assert!(!tree.move_up(three_id));
+
+        assert!(tree.move_down(one_id));
+        assert_eq!(children(&tree), vec![3, 2, 1]);
+        assert!(!tree.move_down(one_id));
+        assert_eq!(tree.root().unwrap().last_child().unwrap().node_id(), one_id);
+
+        // sub-trees move along
+        assert_eq!(
+            tree.get(two_id).unwrap().first_child().unwrap().node_id(),
+            four_id
+        );
+
+        assert!(!tree.move_up(root_id));
+        assert!(!tree.move_down(root_id));
+        assert!(!tree.move_up(four_id));
+        assert!(!tree.move_down(four_id));
+
+        tree.remove(two_id, DropChildren);
+        assert!(!tree.move_up(two_id));
+        assert!(!tree.move_down(two_id));
+        assert_eq!(children(&tree), vec![3, 1]);
+    }
+
+    #[test]
+    fn range_count() {
+        let mut tree = Tree::new();
+        let root_id = tree.set_root(1);
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let five_id = root.append2(5);
+        let three_id = tree.get_mut(two_id).unwrap().append2(3);
+        let four_id = tree.get_mut(three_id).unwrap().append2(4);
+
+        assert_eq!(tree.range_count(root_id, five_id), Some(5));
+        assert_eq!(tree.range_count(three_id, five_id), Some(3));
+        assert_eq!(tree.range_count(four_id, four_id), Some(1));
+        assert_eq!(tree.range_count(five_id, root_id), None);
+
+        tree.remove(three_id, OrphanChildren);
+        assert_eq!(tree.range_count(two_id, five_id), Some(2));
+        assert_eq!(tree.range_count(four_id, five_id), None);
+        assert_eq!(tree.range_count(three_id, five_id), None);
+    }
+
+    #[test]
+    fn stack_builder() {
+        let mut builder = StackBuilder::new();
+        assert!(!builder.pop());
+        assert!(builder.sibling(0).is_none());
+        assert!(builder.current().is_none());
+
+        let one_id = builder.push(1);
+        assert_eq!(builder.current(), Some(one_id));
+        assert!(!builder.pop());
+        assert!(builder.sibling(0).is_none());
+        assert_eq!(builder.current(), Some(one_id));
+
+        builder.push(2);
+        builder.push(3);
+        let four_id = builder.sibling(4).unwrap();
+        assert_eq!(builder.current(), Some(four_id));
+        assert!(builder.pop());
+        assert!(builder.pop());
+        assert_eq!(builder.current(), Some(one_id));
+        builder.push(5);
+        let tree = builder.finish();
+
+        assert_eq!(tree.root_id(), Some(one_id));
+        let values: Vec<(i32, usize)> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order_positioned()
+            .map(|(node, depth, _)| (*node.data(), depth))
+            .collect();
+        assert_eq!(values, vec![(1, 0), (2, 1), (3, 2), (4, 2), (5, 1)]);
+    }
+
+    #[test]
+    fn orphans() {
+        let mut tree = Tree::new();
+        assert!(tree.orphans().is_empty());
+
+        let root_id = tree.set_root(1);
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+        let four_id = tree.get_mut(two_id).unwrap().append2(4);
+        let five_id = tree.get_mut(two_id).unwrap().append2(5);
+        let six_id = tree.get_mut(four_id).unwrap().append2(6);
+        assert!(tree.orphans().is_empty());
+
+        tree.remove(two_id, OrphanChildren);
+        let mut orphans = tree.orphans();
+        orphans.sort();
+        let mut expected = vec![four_id, five_id, six_id];
+        expected.sort();
+        assert_eq!(orphans, expected);
+
+        assert_eq!(tree.collect_orphans(), 3);
+        assert!(tree.orphans().is_empty());
+        assert!(tree.get(six_id).is_none());
+        assert_eq!(tree.get(three_id).unwrap().data(), &3);
+        assert_eq!(tree.collect_orphans(), 0);
+
+        // without a root, everything left is an orphan
+        tree.remove(root_id, OrphanChildren);
+        assert_eq!(tree.orphans(), vec![three_id]);
+        assert_eq!(tree.collect_orphans(), 1);
+        assert!(tree.get(three_id).is_none());
+    }
+
+    #[test]
+    fn traverse_pre_order_into() {
+        let mut other = Tree::new();
+        let mut buf = vec![other.set_root(0)];
+        let mut tree = Tree::new();
+        tree.traverse_pre_order_into(&mut buf);
+        assert!(buf.is_empty());
+
+        tree.set_root(1);
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let five_id = root.append2(5);
+        let three_id = tree.get_mut(two_id).unwrap().append2(3);
+        tree.get_mut(three_id).unwrap().append2(4);
+        tree.get_mut(five_id).unwrap().append2(6);
+
+        tree.traverse_pre_order_into(&mut buf);
+        assert_eq!(buf, tree.cached_pre_order());
+        let capacity = buf.capacity();
+
+        tree.remove(two_id, DropChildren);
+        tree.traverse_pre_order_into(&mut buf);
+        assert_eq!(buf, tree.cached_pre_order());
+        assert_eq!(buf.len(), 3);
+        assert_eq!(buf.capacity(), capacity);
+    }
+
+    #[test]
+    fn data_path() {
+        let mut tree = Tree::new();
+        let root_id = tree.set_root('a');
+        let mut root = tree.root_mut().unwrap();
+        let b_id = root.append2('b');
+        let c_id = tree.get_mut(b_id).unwrap().append2('c');
+        let d_id = tree.get_mut(c_id).unwrap().append2('d');
+
+        assert_eq!(tree.data_path(root_id), Some(vec!['a']));
+        assert_eq!(tree.data_path(d_id), Some(vec!['a', 'b', 'c', 'd']));
+        assert_eq!(
+            tree.data_path(d_id).unwrap().len(),
+            tree.spine(d_id).unwrap().len()
+        );
+
+        tree.remove(b_id, OrphanChildren);
+        assert_eq!(tree.data_path(b_id), None);
+        assert_eq!(tree.data_path(d_id), None);
+
+        let other = Tree::from_parent_array(vec![('x', None)]).unwrap();
+        assert_eq!(tree.data_path(other.root_id().unwrap()), None);
+    }
+
+    #[test]
+    fn stats() {
+        let tree: Tree<i32> = Tree::new();
+        let stats = tree.stats();
+        assert_eq!(stats.node_count, 0);
+        assert_eq!(stats.height, 0);
+        assert_eq!(stats.leaf_count, 0);
+        assert_eq!(stats.free_slots, stats.capacity);
+        assert!(!stats.recommends_compaction());
+
+        let mut tree = TreeBuilder::new().with_root(1).with_capacity(6).build();
+        assert_eq!(tree.stats().height, 0);
+        assert_eq!(tree.stats().leaf_count, 1);
+
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        root.append2(3);
+        let four_id = tree.get_mut(two_id).unwrap().append2(4);
+        tree.get_mut(four_id).unwrap().append2(5);
+
+        let stats = tree.stats();
+        assert_eq!(stats.node_count, 5);
+        assert_eq!(stats.capacity, 6);
+        assert_eq!(stats.height, 3);
+        assert_eq!(stats.leaf_count, 2);
+        assert_eq!(stats.free_slots, 1);
+        assert_eq!(stats.leaf_count, tree.leaf_count());
+
+        // orphans are stored but don't count towards the shape of the tree
+        tree.remove(two_id, OrphanChildren);
+        let stats = tree.stats();
+        assert_eq!(stats.node_count, 4);
+        assert_eq!(stats.height, 1);
+        assert_eq!(stats.leaf_count, 1);
+        assert_eq!(stats.free_slots, 2);
+    }
+
+    #[test]
+    fn insert_parent() {
+        let mut tree = Tree::new();
+        let root_id = tree.set_root(1);
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+        let four_id = root.append2(4);
+        let five_id = tree.get_mut(three_id).unwrap().append2(5);
+
+        let wrapper_id = tree.insert_parent(three_id, 6).unwrap();
+        let wrapper = tree.get(wrapper_id).unwrap();
+        assert_eq!(wrapper.parent().unwrap().node_id(), root_id);
+        assert_eq!(wrapper.prev_sibling().unwrap().node_id(), two_id);
+        assert_eq!(wrapper.next_sibling().unwrap().node_id(), four_id);
+        assert_eq!(wrapper.children_ids(), vec![three_id]);
+        let three = tree.get(three_id).unwrap();
+        assert!(three.prev_sibling().is_none() && three.next_sibling().is_none());
+        assert_eq!(three.children_ids(), vec![five_id]);
+
+        // first and last children keep the parent's ends in sync
+        let first_id = tree.insert_parent(two_id, 7).unwrap();
+        let last_id = tree.insert_parent(four_id, 8).unwrap();
+        assert_eq!(
+            tree.root().unwrap().children_ids(),
+            vec![first_id, wrapper_id, last_id]
+        );
+
+        let new_root_id = tree.insert_parent(root_id, 0).unwrap();
+        assert_eq!(tree.root_id(), Some(new_root_id));
+        assert_eq!(tree.root().unwrap().children_ids(), vec![root_id]);
+
+        tree.remove(wrapper_id, OrphanChildren);
+        let orphan_parent_id = tree.insert_parent(three_id, 9).unwrap();
+        assert_eq!(
+            tree.get(three_id).unwrap().parent().unwrap().node_id(),
+            orphan_parent_id
+        );
+        assert!(tree.get(orphan_parent_id).unwrap().parent().is_none());
+        assert_eq!(tree.root_id(), Some(new_root_id));
+
+        let other = Tree::from_parent_array(vec![(1, None)]).unwrap();
+        assert!(tree.insert_parent(other.root_id().unwrap(), 10).is_none());
+    }
+
+    #[test]
+    fn flatten_node() {
+        let mut tree = Tree::new();
+        let root_id = tree.set_root('r');
+        let mut root = tree.root_mut().unwrap();
+        let a_id = root.append2('a');
+        let x_id = root.append2('x');
+        let d_id = root.append2('d');
+        let mut x = tree.get_mut(x_id).unwrap();
+        let b_id = x.append2('b');
+        let c_id = x.append2('c');
+        let e_id = tree.get_mut(c_id).unwrap().append2('e');
+
+        assert_eq!(tree.flatten_node(x_id), Some('x'));
+        assert!(tree.get(x_id).is_none());
+        assert_eq!(
+            tree.root().unwrap().children_ids(),
+            vec![a_id, b_id, c_id, d_id]
+        );
+        for id in [a_id, b_id, c_id, d_id] {
+            assert_eq!(tree.get(id).unwrap().parent().unwrap().node_id(), root_id);
+        }
+        let b = tree.get(b_id).unwrap();
+        assert_eq!(b.prev_sibling().unwrap().node_id(), a_id);
+        assert_eq!(b.next_sibling().unwrap().node_id(), c_id);
+        let c = tree.get(c_id).unwrap();
+        assert_eq!(c.next_sibling().unwrap().node_id(), d_id);
+        assert_eq!(
+            tree.get(d_id).unwrap().prev_sibling().unwrap().node_id(),
+            c_id
+        );
+        assert_eq!(c.children_ids(), vec![e_id]);
+
+        // at either end of the parent's children, and for a leaf
+        assert_eq!(tree.flatten_node(c_id), Some('c'));
+        assert_eq!(tree.flatten_node(a_id), Some('a'));
+        assert_eq!(tree.root().unwrap().children_ids(), vec![b_id, e_id, d_id]);
+        let values: Vec<char> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| *node.data())
+            .collect();
+        assert_eq!(values, vec!['r', 'b', 'e', 'd']);
+        let last_child = tree.root().unwrap().last_child().unwrap().node_id();
+        assert_eq!(last_child, d_id);
+
+        assert_eq!(tree.flatten_node(root_id), None);
+        assert_eq!(tree.flatten_node(x_id), None);
+    }
+
+    #[test]
+    fn iter_with_parent_id() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().unwrap();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let five_id = root.append2(5);
+        let mut two = tree.get_mut(two_id).unwrap();
+        let three_id = two.append2(3);
+        let four_id = two.append2(4);
+        let six_id = tree.get_mut(five_id).unwrap().append2(6);
+
+        let rows: Vec<(NodeId, Option<NodeId>, i32)> = tree
+            .iter_with_parent_id()
+            .map(|(node_id, parent_id, data)| (node_id, parent_id, *data))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                (root_id, None, 1),
+                (two_id, Some(root_id), 2),
+                (three_id, Some(two_id), 3),
+                (four_id, Some(two_id), 4),
+                (five_id, Some(root_id), 5),
+                (six_id, Some(five_id), 6)
+            ]
+        );
+
+        // orphans aren't reachable from the root, so they are left out
+        tree.remove(five_id, OrphanChildren);
+        assert!(tree
+            .iter_with_parent_id()
+            .all(|(node_id, _, _)| node_id != six_id));
+
+        let empty: Tree<i32> = Tree::new();
+        assert_eq!(empty.iter_with_parent_id().count(), 0);
+    }
+
+    #[cfg(feature = "experimental")]
+    #[test]
+    fn is_current() {
+        let mut tree = Tree::new();
+        let root_id = tree.set_root(0);
+        let mut root = tree.root_mut().unwrap();
+        let one_id = root.append2(1);
+        let two_id = root.append2(2);
+        let three_id = tree.get_mut(two_id).unwrap().append2(3);
+        let four_id = tree.get_mut(two_id).unwrap().append2(4);
+        tree.set_key(three_id, 7);
+        tree.get_mut(four_id).unwrap().data_mut_tracked();
+
+        tree.remove(one_id, DropChildren);
+        assert_eq!(tree.compaction_generation(), 0);
+        assert!(tree.is_current(root_id) && tree.is_current(four_id));
+
+        tree.compact_internal();
+        assert_eq!(tree.compaction_generation(), 1);
+        for node_id in [root_id, two_id, three_id, four_id] {
+            assert!(!tree.is_current(node_id));
+        }
+        // equality doesn't look at the generation
+        assert_eq!(tree.root_id(), Some(root_id));
+
+        // every id read back from the tree is current
+        for node in tree.root().unwrap().traverse_pre_order() {
+            assert!(tree.is_current(node.node_id()));
+            if let Some(parent) = node.parent() {
+                assert!(tree.is_current(parent.node_id()));
+            }
+        }
+        assert!(tree.is_current(tree.root_id().unwrap()));
+        assert!(tree.is_current(tree.find_by_key(7).unwrap()));
+        assert!(tree.dirty_nodes().into_iter().all(|id| tree.is_current(id)));
+
+        // links made afterwards from an old id come out current as well
+        let five_id = tree.get_mut(three_id).unwrap().append2(5);
+        let five = tree.get(five_id).unwrap();
+        assert!(tree.is_current(five.parent().unwrap().node_id()));
+
+        let other = Tree::from_parent_array(vec![(0, None)]).unwrap();
+        assert!(!tree.is_current(other.root_id().unwrap()));
+    }
+
+    #[cfg(feature = "experimental")]
+    #[test]
+    fn is_current_after_compact() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        root.append2(3);
+        tree.remove(two_id, DropChildren);
+
+        tree.compact();
+        assert_eq!(tree.compaction_generation(), 1);
+        assert!(tree.is_current(tree.root_id().unwrap()));
+        let root = tree.root().unwrap();
+        let three = root.first_child().unwrap();
+        assert_eq!(three.data(), &3);
+        assert!(tree.is_current(three.node_id()));
+        assert!(tree.is_current(three.parent().unwrap().node_id()));
+    }
+
+    #[test]
+    fn traverse_with_ranges() {
+        let empty: Tree<i32> = Tree::new();
+        assert_eq!(empty.traverse_with_ranges().count(), 0);
+
+        let mut tree = Tree::new();
+        let root_id = tree.set_root('a');
+        assert_eq!(
+            tree.traverse_with_ranges()
+                .map(|(node, start, end)| (node.node_id(), start, end))
+                .collect::<Vec<_>>(),
+            vec![(root_id, 0, 1)]
+        );
+
+        let mut root = tree.root_mut().unwrap();
+        let b_id = root.append2('b');
+        root.append2('e');
+        let c_id = tree.get_mut(b_id).unwrap().append2('c');
+        tree.get_mut(c_id).unwrap().append2('d');
+        let f_id = tree.root_mut().unwrap().append2('f');
+        let mut f = tree.get_mut(f_id).unwrap();
+        f.append2('g');
+        f.append2('h');
+
+        let ranges: Vec<(char, usize, usize)> = tree
+            .traverse_with_ranges()
+            .map(|(node, start, end)| (*node.data(), start, end))
+            .collect();
+        assert_eq!(
+            ranges,
+            vec![
+                ('a', 0, 8),
+                ('b', 1, 4),
+                ('c', 2, 4),
+                ('d', 3, 4),
+                ('e', 4, 5),
+                ('f', 5, 8),
+                ('g', 6, 7),
+                ('h', 7, 8)
+            ]
+        );
+        for (node, start, end) in tree.traverse_with_ranges() {
+            assert_eq!(end - start, node.subtree().len());
+        }
+    }
+
+    #[test]
+    fn replace_subtree() {
+        let mut tree = Tree::new();
+        let root_id = tree.set_root(1);
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+        let four_id = root.append2(4);
+        let five_id = tree.get_mut(three_id).unwrap().append2(5);
+        tree.get_mut(five_id).unwrap().append2(6);
+
+        let mut replacement = TreeBuilder::new().with_root(7).build();
+        replacement.root_mut().unwrap().append2(8);
+        let removed = tree.replace_subtree(three_id, replacement).unwrap();
+
+        assert!(tree.get(three_id).is_none() && tree.get(five_id).is_none());
+        let values: Vec<i32> = removed
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| *node.data())
+            .collect();
+        assert_eq!(values, vec![3, 5, 6]);
+        assert!(removed.root().unwrap().parent().is_none());
+
+        let two = tree.get(two_id).unwrap();
+        let grafted = two.next_sibling().unwrap();
+        assert_eq!(grafted.data(), &7);
+        assert_eq!(grafted.parent().unwrap().node_id(), root_id);
+        assert_eq!(grafted.next_sibling().unwrap().node_id(), four_id);
+        assert_eq!(grafted.first_child().unwrap().data(), &8);
+        let grafted_id = grafted.node_id();
+        assert_eq!(
+            tree.get(four_id).unwrap().prev_sibling().unwrap().node_id(),
+            grafted_id
+        );
+
+        // an empty replacement leaves a gap
+        let removed = tree.replace_subtree(four_id, Tree::new()).unwrap();
+        assert_eq!(removed.root().unwrap().data(), &4);
+        assert_eq!(
+            tree.root().unwrap().last_child().unwrap().node_id(),
+            grafted_id
+        );
+
+        // replacing the root replaces the whole tree
+        let whole = tree
+            .replace_subtree(root_id, TreeBuilder::new().with_root(9).build())
+            .unwrap();
+        assert_eq!(tree.root().unwrap().data(), &9);
+        assert_eq!(tree.root().unwrap().traverse_pre_order().count(), 1);
+        assert_eq!(whole.root().unwrap().traverse_pre_order().count(), 4);
+
+        assert!(tree.replace_subtree(root_id, Tree::new()).is_none());
+        assert_eq!(tree.root().unwrap().data(), &9);
+
+        // an orphan has no place to graft the replacement in
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let two_id = tree.root_mut().unwrap().append2(2);
+        let three_id = tree.get_mut(two_id).unwrap().append2(3);
+        tree.remove(two_id, OrphanChildren);
+        let before = tree.core_tree.len();
+        let replacement = TreeBuilder::new().with_root(4).build();
+        assert!(tree.replace_subtree(three_id, replacement).is_none());
+        assert_eq!(tree.get(three_id).unwrap().data(), &3);
+        assert_eq!(tree.core_tree.len(), before);
+        assert_eq!(tree.orphans(), vec![three_id]);
+    }
+
+    #[test]
+    fn drain_pre_order() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let five_id = root.append2(5);
+        let mut two = tree.get_mut(two_id).unwrap();
+        let three_id = two.append2(3);
+        two.append2(4);
+        let six_id = tree.get_mut(five_id).unwrap().append2(6);
+
+        let mut drain = tree.drain_pre_order();
+        assert_eq!(drain.next(), Some(1));
+        assert_eq!(drain.next(), Some(2));
+        assert_eq!(drain.next(), Some(3));
+        drop(drain);
+
+        // what wasn't yielded stays intact, without a root
+        assert!(tree.root().is_none());
+        assert!(tree.get(two_id).is_none() && tree.get(three_id).is_none());
+        let five = tree.get(five_id).unwrap();
+        assert!(five.parent().is_none());
+        assert_eq!(five.children_ids(), vec![six_id]);
+        assert_eq!(tree.orphans().len(), 3);
+
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        root.append2(5);
+        let mut two = tree.get_mut(two_id).unwrap();
+        two.append2(3);
+        two.append2(4);
+        let drained: Vec<i32> = tree.drain_pre_order().collect();
+        assert_eq!(drained, vec![1, 2, 3, 4, 5]);
+        assert!(tree.root().is_none());
+        assert_eq!(tree.stats().node_count, 0);
+
+        let mut empty: Tree<i32> = Tree::new();
+        assert_eq!(empty.drain_pre_order().next(), None);
+    }
+
+    #[test]
+    fn version() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().unwrap();
+        let two_id = tree.root_mut().unwrap().append2(2);
+        let three_id = tree.get_mut(two_id).unwrap().append2(3);
+
+        // reading doesn't change either counter
+        let (version, data_version) = (tree.version(), tree.data_version());
+        let _ = tree.root().unwrap().traverse_pre_order().count();
+        let _ = tree.get_mut(two_id).unwrap();
+        assert_eq!(
+            (tree.version(), tree.data_version()),
+            (version, data_version)
+        );
+
+        // data access only changes the data version
+        tree.get_many_mut([root_id, three_id]).unwrap();
+        assert_eq!(tree.version(), version);
+        assert_ne!(tree.data_version(), data_version);
+
+        let (version, data_version) = (tree.version(), tree.data_version());
+        tree.replace_root_data(4);
+        tree.for_each_post_order_mut(|data| *data += 1);
+        assert_eq!(tree.version(), version);
+        assert!(tree.data_version() > data_version);
+
+        // moving and removing nodes only changes the structural version
+        let data_version = tree.data_version();
+        tree.reparent_many(&[three_id], root_id).unwrap();
+        let moved = tree.version();
+        assert_ne!(moved, version);
+        tree.remove(two_id, RemoveBehavior::DropChildren);
+        assert_ne!(tree.version(), moved);
+        let removed = tree.version();
+        tree.reverse();
+        assert_ne!(tree.version(), removed);
+        assert_eq!(tree.data_version(), data_version);
+    }
+
+    #[test]
+    fn rebuild_links_under() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let root_id = tree.root_id().unwrap();
+        let mut root = tree.root_mut().unwrap();
+        let one_id = root.append2(1);
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+
+        // break everything but the next sibling chain
+        tree.get_node_mut(two_id).unwrap().relatives.prev_sibling = Some(three_id);
+        tree.get_node_mut(two_id).unwrap().relatives.parent = None;
+        tree.get_node_mut(three_id).unwrap().relatives.prev_sibling = None;
+        tree.get_node_mut(root_id).unwrap().relatives.last_child = Some(one_id);
+
+        assert!(tree.rebuild_links_under(root_id));
+        let root = tree.get_node(root_id).unwrap();
+        assert_eq!(root.relatives.first_child, Some(one_id));
+        assert_eq!(root.relatives.last_child, Some(three_id));
+        let two = tree.get_node(two_id).unwrap();
+        assert_eq!(two.relatives.parent, Some(root_id));
+        assert_eq!(two.relatives.prev_sibling, Some(one_id));
+        assert_eq!(
+            tree.get_node(three_id).unwrap().relatives.prev_sibling,
+            Some(two_id)
+        );
+
+        // a loop in the chain is cut
+        tree.get_node_mut(three_id).unwrap().relatives.next_sibling = Some(one_id);
+        assert!(tree.rebuild_links_under(root_id));
+        assert_eq!(
+            tree.get_node(three_id).unwrap().relatives.next_sibling,
+            None
+        );
+        assert_eq!(
+            tree.root().unwrap().children_ids(),
+            vec![one_id, two_id, three_id]
+        );
+
+        // a dead id ends the chain
+        tree.core_tree.remove(two_id);
+        assert!(tree.rebuild_links_under(root_id));
+        assert_eq!(tree.root().unwrap().children_ids(), vec![one_id]);
+        assert_eq!(
+            tree.get_node(root_id).unwrap().relatives.last_child,
+            Some(one_id)
+        );
+
+        tree.core_tree.remove(one_id);
+        assert!(tree.rebuild_links_under(root_id));
+        let root = tree.get_node(root_id).unwrap();
+        assert_eq!(root.relatives.first_child, None);
+        assert_eq!(root.relatives.last_child, None);
+
+        assert!(!tree.rebuild_links_under(one_id));
+    }
+
+    #[test]
+    fn relink_children() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let root_id = tree.root_id().unwrap();
+        let mut root = tree.root_mut().unwrap();
+        let one_id = root.append2(1);
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+        let four_id = tree.get_mut(one_id).unwrap().append2(4);
+        let five_id = tree.get_mut(one_id).unwrap().append2(5);
+
+        assert_eq!(tree.relink_children(two_id, &[five_id, three_id]), Ok(()));
+        assert_eq!(tree.get(one_id).unwrap().children_ids(), vec![four_id]);
+        assert_eq!(tree.root().unwrap().children_ids(), vec![one_id, two_id]);
+        let two = tree.get(two_id).unwrap();
+        assert_eq!(two.children_ids(), vec![five_id, three_id]);
+        assert_eq!(
+            two.last_child().unwrap().prev_sibling().unwrap().node_id(),
+            five_id
+        );
+
+        assert_eq!(tree.relink_children(two_id, &[three_id]), Ok(()));
+        assert!(tree.get(five_id).unwrap().parent().is_none());
+        assert_eq!(tree.get(two_id).unwrap().children_ids(), vec![three_id]);
+
+        assert_eq!(tree.relink_children(two_id, &[]), Ok(()));
+        assert!(tree.get(two_id).unwrap().first_child().is_none());
+        assert!(tree.get(three_id).unwrap().parent().is_none());
+
+        let before = tree.to_string();
+        assert_eq!(
+            tree.relink_children(four_id, &[two_id, two_id]),
+            Err(MoveError::DuplicateNodeId(two_id))
+        );
+        assert_eq!(
+            tree.relink_children(four_id, &[two_id, one_id]),
+            Err(MoveError::WouldCreateCycle(one_id))
+        );
+        assert_eq!(
+            tree.relink_children(four_id, &[root_id]),
+            Err(MoveError::CannotMoveRoot)
+        );
+        tree.remove(three_id, DropChildren);
+        assert_eq!(
+            tree.relink_children(four_id, &[three_id]),
+            Err(MoveError::InvalidNodeId(three_id))
+        );
+        assert_eq!(tree.to_string(), before);
+    }
+
+    #[test]
+    fn leaf_count() {
+        let mut tree = TreeBuilder::new().build();
+        assert_eq!(tree.leaf_count(), 0);
+
+        tree.set_root(1);
+        assert_eq!(tree.leaf_count(), 1);
+
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        root.append2(3);
+        assert_eq!(tree.leaf_count(), 2);
+
+        let mut two = tree.get_mut(two_id).unwrap();
+        two.append2(4);
+        two.append2(5);
+        assert_eq!(tree.leaf_count(), 3);
+
+        tree.remove(two_id, OrphanChildren);
+        assert_eq!(tree.leaf_count(), 1);
+    }
+
+    #[test]
+    fn iter_with_paths() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let five_id = root.append2(5);
+        let three_id = tree.get_mut(two_id).unwrap().append2(3);
+        let four_id = tree.get_mut(two_id).unwrap().append2(4);
+        let root_id = tree.root_id().unwrap();
+
+        let paths: Vec<(NodeId, String)> = tree.iter_with_paths("::").collect();
+        assert_eq!(
+            paths,
+            vec![
+                (root_id, "1".to_string()),
+                (two_id, "1::2".to_string()),
+                (three_id, "1::2::3".to_string()),
+                (four_id, "1::2::4".to_string()),
+                (five_id, "1::5".to_string()),
+            ]
+        );
+
+        let empty: Tree<i32> = Tree::new();
+        assert_eq!(empty.iter_with_paths("/").count(), 0);
+    }
+
+    #[test]
+    fn find_duplicate_subtrees() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        let one_id = root.append2(1);
+        let two_id = root.append2(1);
+        let three_id = root.append2(1);
+        let four_id = tree.get_mut(one_id).unwrap().append2(2);
+        let five_id = tree.get_mut(two_id).unwrap().append2(2);
+        let six_id = tree.get_mut(three_id).unwrap().append2(3);
+
+        // 0
+        // ├── 1
+        // │   └── 2
+        // ├── 1
+        // │   └── 2
+        // └── 1
+        //     └── 3
+
+        let groups = tree.find_duplicate_subtrees();
+        assert_eq!(groups, vec![vec![one_id, two_id], vec![four_id, five_id]]);
+        assert!(!groups
+            .iter()
+            .flatten()
+            .any(|id| *id == three_id || *id == six_id));
+    }
+
+    #[test]
+    fn find_duplicate_subtrees_none() {
+        let tree: Tree<i32> = Tree::new();
+        assert!(tree.find_duplicate_subtrees().is_empty());
+
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        tree.root_mut().unwrap().append(1).append(1);
+        assert!(tree.find_duplicate_subtrees().is_empty());
+    }
+
+    fn hash_of<T: Hash>(tree: &Tree<T>) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        tree.hash(&mut hasher);
+        std::hash::Hasher::finish(&hasher)
     }
 
     #[test]
-    fn remove_root_drop() {
-        let mut tree = TreeBuilder::new().with_root(1).build();
-        let root_id = tree.root_id().expect("root doesn't exist?");
+    fn eq_and_hash() {
+        let mut first = TreeBuilder::new().with_root(1).build();
+        let mut root = first.root_mut().unwrap();
+        let two_id = root.append2(2);
+        root.append2(4);
+        first.get_mut(two_id).unwrap().append2(3);
 
-        tree.remove(root_id, RemoveBehavior::DropChildren);
-        assert!(tree.root().is_none());
+        // same shape and data, stored differently
+        let mut second = TreeBuilder::new().with_root(1).build();
+        let mut root = second.root_mut().unwrap();
+        let removed_id = root.append2(9);
+        let four_id = root.append2(4);
+        let two_id = second.insert_before(four_id, 2).unwrap();
+        second.get_mut(two_id).unwrap().append2(3);
+        second.remove(removed_id, OrphanChildren);
+
+        assert_eq!(first, second);
+        assert_eq!(hash_of(&first), hash_of(&second));
+
+        // same pre-order data, different shape
+        let mut third = TreeBuilder::new().with_root(1).build();
+        third.root_mut().unwrap().append(2).append(3).append(4);
+        assert_ne!(first, third);
+        assert_ne!(hash_of(&first), hash_of(&third));
+
+        let empty: Tree<i32> = Tree::new();
+        assert_eq!(empty, Tree::new());
+        assert_eq!(hash_of(&empty), hash_of(&Tree::<i32>::new()));
+        assert_ne!(empty, first);
     }
 
     #[test]
-    fn remove_root_orphan() {
+    fn child_counts() {
+        let tree: Tree<i32> = Tree::new();
+        assert!(tree.child_counts().is_empty());
+
         let mut tree = TreeBuilder::new().with_root(1).build();
-        let root_id = tree.root_id().expect("root doesn't exist?");
+        let root_id = tree.root_id().unwrap();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+        let mut two = tree.get_mut(two_id).unwrap();
+        let four_id = two.append2(4);
+        let five_id = two.append2(5);
 
-        tree.remove(root_id, RemoveBehavior::OrphanChildren);
-        assert!(tree.root().is_none());
+        let counts = tree.child_counts();
+        assert_eq!(counts.len(), 5);
+        assert_eq!(counts[&root_id], 2);
+        assert_eq!(counts[&two_id], 2);
+        assert_eq!(counts[&three_id], 0);
+        assert_eq!(counts[&four_id], 0);
+        assert_eq!(counts[&five_id], 0);
     }
 
     #[test]
-    fn root() {
-        let tree = TreeBuilder::new().with_root(1).build();
-        let root = tree.root().expect("root doesn't exist?");
-        assert_eq!(root.data(), &1);
-    }
+    fn find_by_with_depth() {
+        let tree: Tree<i32> = Tree::new();
+        assert!(tree.find_by_with_depth(|_, _| true).is_empty());
 
-    #[test]
-    fn root_mut() {
-        let mut tree = TreeBuilder::new().with_root(1).build();
-        let mut root = tree.root_mut().expect("root doesn't exist?");
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let root_id = tree.root_id().unwrap();
+        let mut root = tree.root_mut().unwrap();
+        let one_id = root.append2(1);
+        let four_id = root.append2(4);
+        let mut one = tree.get_mut(one_id).unwrap();
+        let two_id = one.append2(2);
+        let three_id = one.append2(3);
 
-        assert_eq!(root.data(), &mut 1);
+        let mut seen = Vec::new();
+        let matches = tree.find_by_with_depth(|data, depth| {
+            seen.push((*data, depth));
+            depth != 1
+        });
+        assert_eq!(seen, vec![(0, 0), (1, 1), (2, 2), (3, 2), (4, 1)]);
+        assert_eq!(matches, vec![root_id, two_id, three_id]);
 
-        *root.data() = 2;
-        assert_eq!(root.data(), &mut 2);
+        let matches = tree.find_by_with_depth(|data, _| *data == 4);
+        assert_eq!(matches, vec![four_id]);
     }
 
     #[test]
-    fn get() {
-        let tree = TreeBuilder::new().with_root(1).build();
+    fn min_max_by_key() {
+        let tree: Tree<i32> = Tree::new();
+        assert_eq!(tree.min_by_key(|value| *value), None);
+        assert_eq!(tree.max_by_key(|value| *value), None);
 
-        let root_id = tree.root_id().expect("root doesn't exist?");
-        let root = tree.get(root_id);
-        assert!(root.is_some());
+        let mut tree = TreeBuilder::new().with_root(5).build();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let nine_id = root.append2(9);
+        let other_two_id = tree.get_mut(nine_id).unwrap().append2(2);
+        let other_nine_id = tree.get_mut(two_id).unwrap().append2(9);
 
-        let root = root.unwrap();
-        assert_eq!(root.data(), &1);
+        // 5
+        // ├── 2
+        // │   └── 9
+        // └── 9
+        //     └── 2
+
+        assert_eq!(tree.min_by_key(|value| *value), Some(two_id));
+        assert_eq!(tree.max_by_key(|value| *value), Some(other_nine_id));
+        assert_eq!(tree.max_by_key(|value| -value), Some(two_id));
+        assert_ne!(tree.min_by_key(|value| *value), Some(other_two_id));
     }
 
     #[test]
-    fn get_mut() {
+    fn map_into() {
         let mut tree = TreeBuilder::new().with_root(1).build();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        root.append2(4);
+        tree.get_mut(two_id).unwrap().append2(3);
 
-        let root_id = tree.root_id().expect("root doesn't exist?");
-        let root = tree.get_mut(root_id);
-        assert!(root.is_some());
+        let mut calls = Vec::new();
+        let tree = tree.map_into(|value| {
+            calls.push(value);
+            value * 10
+        });
+        assert_eq!(calls, vec![1, 2, 3, 4]);
 
-        let mut root = root.unwrap();
-        assert_eq!(root.data(), &mut 1);
+        let root = tree.root().unwrap();
+        assert_eq!(root.data(), &10);
+        let values: Vec<i32> = root.traverse_level_order().map(|n| *n.data()).collect();
+        assert_eq!(values, vec![10, 20, 40, 30]);
+        assert_eq!(
+            root.last_child().unwrap().prev_sibling().unwrap().data(),
+            &20
+        );
+    }
 
-        *root.data() = 2;
-        assert_eq!(root.data(), &mut 2);
+    #[test]
+    fn snapshot_pre_order() {
+        let empty: Tree<String> = Tree::new();
+        assert!(empty.snapshot_pre_order().is_empty());
+
+        let mut tree = TreeBuilder::new().with_root("a".to_string()).build();
+        let mut root = tree.root_mut().unwrap();
+        let b_id = root.append2("b".to_string());
+        root.append2("d".to_string());
+        tree.get_mut(b_id).unwrap().append2("c".to_string());
+        let orphan_id = tree.get_mut(b_id).unwrap().append2("x".to_string());
+        tree.get_mut(orphan_id).unwrap().append2("y".to_string());
+        tree.remove(orphan_id, OrphanChildren);
+
+        let snapshot = std::thread::spawn({
+            let snapshot = tree.snapshot_pre_order();
+            move || snapshot
+        })
+        .join()
+        .unwrap();
+        assert_eq!(snapshot, vec!["a", "b", "c", "d"]);
     }
 
     #[test]
-    fn get_node() {
-        let tree = TreeBuilder::new().with_root(1).build();
+    fn to_adjacency_list() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let three_id = root.append2(3);
+        tree.get_mut(two_id).unwrap().append2(4);
+        tree.get_mut(three_id).unwrap().append2(5);
+        tree.remove(two_id, RemoveBehavior::DropChildren);
+        tree.get_mut(three_id).unwrap().prepend2(6);
 
-        let root_id = tree.root_id().expect("root doesn't exist?");
-        let root = tree.get_node(root_id);
-        assert!(root.is_some());
+        let (data, edges) = tree.to_adjacency_list();
+        assert_eq!(data, vec![1, 3, 6, 5]);
+        assert_eq!(edges, vec![(0, 1), (1, 2), (1, 3)]);
 
-        let root = root.unwrap();
-        assert_eq!(root.data, 1);
+        let empty: Tree<i32> = Tree::new();
+        assert_eq!(empty.to_adjacency_list(), (vec![], vec![]));
     }
 
     #[test]
-    fn get_node_mut() {
+    fn clone_subtree() {
         let mut tree = TreeBuilder::new().with_root(1).build();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        root.append2(6);
+        let mut two = tree.get_mut(two_id).unwrap();
+        let three_id = two.append2(3);
+        two.append2(5);
+        tree.get_mut(three_id).unwrap().append2(4);
 
-        let root_id = tree.root_id().expect("root doesn't exist?");
-        let root = tree.get_node_mut(root_id);
-        assert!(root.is_some());
+        let mut copy = tree.clone_subtree(two_id).unwrap();
+        let copy_root = copy.root().unwrap();
+        assert!(copy_root.parent().is_none());
+        let values: Vec<i32> = copy_root.traverse_pre_order().map(|n| *n.data()).collect();
+        assert_eq!(values, vec![2, 3, 4, 5]);
 
-        let root = root.unwrap();
-        assert_eq!(root.data, 1);
+        // changing the copy leaves the original alone
+        *copy.root_mut().unwrap().data() = 20;
+        assert_eq!(tree.get(two_id).unwrap().data(), &2);
 
-        root.data = 2;
-        assert_eq!(root.data, 2);
+        let whole = tree.clone_subtree(tree.root_id().unwrap()).unwrap();
+        assert_eq!(whole.to_string(), tree.to_string());
+
+        tree.remove(three_id, RemoveBehavior::DropChildren);
+        assert!(tree.clone_subtree(three_id).is_none());
     }
 
     #[test]
-    fn remove_drop() {
+    fn iter_with_parent() {
         let mut tree = TreeBuilder::new().with_root(1).build();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        root.append2(5);
+        let mut two = tree.get_mut(two_id).unwrap();
+        two.append2(3);
+        two.append2(4);
 
-        let two_id;
-        let three_id;
-        let four_id;
-        let five_id;
-        {
-            let mut root = tree.root_mut().expect("root doesn't exist?");
-            two_id = root.append2(2);
-            three_id = root.append2(3);
-            four_id = root.append2(4);
-        }
-        {
-            five_id = tree
-                .get_mut(three_id)
-                .expect("three doesn't exist?")
-                .append(5)
-                .node_id();
-        }
+        let pairs: Vec<(i32, Option<i32>)> = tree
+            .iter_with_parent()
+            .map(|(data, parent)| (*data, parent.copied()))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                (1, None),
+                (2, Some(1)),
+                (3, Some(2)),
+                (4, Some(2)),
+                (5, Some(1))
+            ]
+        );
 
-        //        1
-        //      / | \
-        //     2  3  4
-        //        |
-        //        5
+        let empty: Tree<i32> = Tree::new();
+        assert_eq!(empty.iter_with_parent().count(), 0);
+    }
 
-        tree.remove(three_id, DropChildren);
+    #[test]
+    fn for_each_post_order_mut() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(1);
+        let five_id = root.append2(1);
+        let mut two = tree.get_mut(two_id).unwrap();
+        two.append2(1);
+        two.append2(1);
 
-        let root = tree
-            .get_node(tree.root_id().expect("tree doesn't exist?"))
-            .unwrap();
-        assert!(root.relatives.first_child.is_some());
-        assert!(root.relatives.last_child.is_some());
-        assert_eq!(root.relatives.first_child.unwrap(), two_id);
-        assert_eq!(root.relatives.last_child.unwrap(), four_id);
+        let mut visited = Vec::new();
+        tree.for_each_post_order_mut(|data| {
+            *data += visited.len() as i32;
+            visited.push(*data);
+        });
+        assert_eq!(visited, vec![1, 2, 3, 4, 5]);
+        let values: Vec<i32> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|n| *n.data())
+            .collect();
+        assert_eq!(values, vec![5, 3, 1, 2, 4]);
+        assert_eq!(tree.get(five_id).unwrap().data(), &4);
 
-        let two = tree.get_node(two_id);
-        assert!(two.is_some());
+        let mut empty: Tree<i32> = Tree::new();
+        empty.for_each_post_order_mut(|_| panic!("empty tree"));
+    }
 
-        let two = two.unwrap();
-        assert_eq!(two.relatives.next_sibling, Some(four_id));
+    #[test]
+    fn reverse() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let five_id = root.append2(5);
+        let six_id = root.append2(6);
+        let mut two = tree.get_mut(two_id).unwrap();
+        let three_id = two.append2(3);
+        two.append2(4);
+        let original = tree.to_string();
 
-        let four = tree.get_node(four_id);
-        assert!(four.is_some());
+        tree.reverse();
 
-        let four = four.unwrap();
-        assert_eq!(four.relatives.prev_sibling, Some(two_id));
+        let root = tree.root().unwrap();
+        assert_eq!(root.first_child().unwrap().node_id(), six_id);
+        assert_eq!(root.last_child().unwrap().node_id(), two_id);
+        let five = tree.get(five_id).unwrap();
+        assert_eq!(five.prev_sibling().unwrap().node_id(), six_id);
+        assert_eq!(five.next_sibling().unwrap().node_id(), two_id);
+        let values: Vec<i32> = root.traverse_pre_order().map(|n| *n.data()).collect();
+        assert_eq!(values, vec![1, 6, 5, 2, 4, 3]);
+        assert_eq!(
+            tree.get(three_id).unwrap().parent().unwrap().node_id(),
+            two_id
+        );
 
-        let five = tree.get_node(five_id);
-        assert!(five.is_none());
+        tree.reverse();
+        assert_eq!(tree.to_string(), original);
+
+        let mut empty: Tree<i32> = Tree::new();
+        empty.reverse();
+        assert!(empty.root().is_none());
     }
 
-    /// Test that there is no panic if caller tries to remove a removed node
     #[test]
-    fn address_dropped() {
+    fn collapse_chains_panicking_merge() {
         let mut tree = TreeBuilder::new().with_root(1).build();
-        let two_id = tree.root_mut().expect("root doesn't exist").node_id();
-        tree.remove(two_id, DropChildren);
-        tree.remove(two_id, DropChildren);
+        let root_id = tree.root_id().unwrap();
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let five_id = root.append2(5);
+        let three_id = tree.get_mut(two_id).unwrap().append2(3);
+        let mut three = tree.get_mut(three_id).unwrap();
+        let four_id = three.append2(4);
+        let six_id = three.append2(6);
+        tree.set_key(two_id, 7);
+
+        // 1
+        // ├── 2
+        // │   └── 3
+        // │       ├── 4
+        // │       └── 6
+        // └── 5
+
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            tree.collapse_chains(|_, _| panic!("merge failed"))
+        }));
+        assert!(result.is_err());
+
+        // 2 lost its data and was taken out, 3 was absorbed, and 4 and 6 became orphans
+        assert!(tree.get(two_id).is_none() && tree.get(three_id).is_none());
+        assert_eq!(tree.find_by_key(7), None);
+        assert_eq!(tree.core_tree.len(), 4);
+        let root = tree.root().unwrap();
+        let values: Vec<i32> = root.traverse_pre_order().map(|node| *node.data()).collect();
+        assert_eq!(values, vec![1, 5]);
+        assert_eq!(root.first_child().unwrap().node_id(), five_id);
+        assert!(tree.get(five_id).unwrap().prev_sibling().is_none());
+
+        let mut orphans = tree.orphans();
+        orphans.sort();
+        let mut expected = vec![four_id, six_id];
+        expected.sort();
+        assert_eq!(orphans, expected);
+        let four = tree.get(four_id).unwrap();
+        assert!(four.parent().is_none());
+        assert_eq!(four.next_sibling().unwrap().data(), &6);
+
+        // and the tree keeps working
+        tree.get_mut(root_id).unwrap().append2(8);
+        assert_eq!(tree.collect_orphans(), 2);
+        assert_eq!(tree.to_string(), "1\n├── 5\n└── 8\n");
     }
 
     #[test]
-    fn remove_orphan() {
+    fn collapse_chains() {
         let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().unwrap();
+        let two_id = tree.root_mut().unwrap().append2(2);
+        let mut two = tree.get_mut(two_id).unwrap();
+        let three_id = two.append2(3);
+        let six_id = two.append2(6);
+        tree.get_mut(three_id).unwrap().append(4).append(5);
+        let mut six = tree.get_mut(six_id).unwrap();
+        let seven_id = six.append2(7);
+        six.append2(8);
+        tree.get_mut(seven_id).unwrap().append2(9);
 
-        let two_id;
-        let three_id;
-        let four_id;
-        let five_id;
-        {
-            let mut root = tree.root_mut().expect("root doesn't exist?");
-            two_id = root.append2(2);
-            three_id = root.append2(3);
-            four_id = root.append2(4);
-        }
-        {
-            five_id = tree
-                .get_mut(three_id)
-                .expect("three doesn't exist?")
-                .append(5)
-                .node_id();
-        }
+        // 1
+        // └── 2
+        //     ├── 3
+        //     │   └── 4
+        //     │       └── 5
+        //     └── 6
+        //         ├── 7
+        //         │   └── 9
+        //         └── 8
 
-        //        1
-        //      / | \
-        //     2  3  4
-        //        |
-        //        5
+        let mut merges = Vec::new();
+        tree.collapse_chains(|data, child_data| {
+            merges.push((data, child_data));
+            data * 10 + child_data
+        });
+        assert_eq!(merges, vec![(1, 2), (3, 4), (34, 5), (7, 9)]);
 
-        tree.remove(three_id, OrphanChildren);
+        let mut s = String::new();
+        tree.write_formatted(&mut s).unwrap();
+        assert_eq!(
+            s,
+            "\
+12
+├── 345
+└── 6
+    ├── 79
+    └── 8
+"
+        );
 
-        let root = tree
-            .get_node(tree.root_id().expect("tree doesn't exist?"))
-            .unwrap();
-        assert!(root.relatives.first_child.is_some());
-        assert!(root.relatives.last_child.is_some());
-        assert_eq!(root.relatives.first_child.unwrap(), two_id);
-        assert_eq!(root.relatives.last_child.unwrap(), four_id);
+        assert_eq!(tree.root_id(), Some(root_id));
+        assert!(tree.get(two_id).is_none());
+        let three = tree.get(three_id).unwrap();
+        assert_eq!(three.parent().unwrap().node_id(), root_id);
+        assert!(three.first_child().is_none());
+        assert_eq!(three.next_sibling().unwrap().node_id(), six_id);
+        assert_eq!(
+            tree.get(six_id).unwrap().parent().unwrap().node_id(),
+            root_id
+        );
 
-        let two = tree.get_node(two_id);
-        assert!(two.is_some());
+        let mut single = TreeBuilder::new().with_root(1).build();
+        single.collapse_chains(|data, child_data| data + child_data);
+        assert_eq!(single.root().unwrap().data(), &1);
+    }
 
-        let two = two.unwrap();
-        assert_eq!(two.relatives.next_sibling, Some(four_id));
+    #[test]
+    fn insert_and_remove_hooks() {
+        use std::sync::{Arc, Mutex};
 
-        let four = tree.get_node(four_id);
-        assert!(four.is_some());
+        // hooks must not keep trees from being shared across threads
+        fn assert_send_sync<S: Send + Sync>() {}
+        assert_send_sync::<Tree<i32>>();
 
-        let four = four.unwrap();
-        assert_eq!(four.relatives.prev_sibling, Some(two_id));
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let root_id = tree.root_id().unwrap();
+        let index = Arc::new(Mutex::new(HashMap::new()));
 
-        let five = tree.get_node(five_id);
-        assert!(five.is_some());
+        let inserted = Arc::clone(&index);
+        tree.on_insert(move |node_id| {
+            inserted.lock().unwrap().insert(node_id, ());
+        });
+        let removed = Arc::clone(&index);
+        tree.on_remove(move |node_id| {
+            removed.lock().unwrap().remove(&node_id);
+        });
 
-        let five = five.unwrap();
-        assert_eq!(five.relatives.parent, None);
+        let two_id = tree.root_mut().unwrap().append2(2);
+        let three_id = tree.get_mut(two_id).unwrap().prepend2(3);
+        let four_id = tree.insert_after(two_id, 4).unwrap();
+        let five_id = tree.insert_before(two_id, 5).unwrap();
+        let six_id = tree.get_mut(three_id).unwrap().append2(6);
+        assert_eq!(index.lock().unwrap().len(), 5);
+
+        tree.remove(five_id, OrphanChildren);
+        tree.remove(two_id, DropChildren);
+        let index = index.lock().unwrap();
+        assert_eq!(index.len(), 1);
+        assert!(index.contains_key(&four_id));
+        assert!(!index.contains_key(&root_id) && !index.contains_key(&six_id));
+    }
+
+    #[test]
+    fn map_into_empty_tree() {
+        let tree: Tree<i32> = Tree::new();
+        let tree = tree.map_into(|value| value.to_string());
+        assert!(tree.root().is_none());
     }
 
     #[test]
@@ -1094,6 +7118,88 @@ mod tree_tests {
         assert!(matches.is_none());
     }
 
+    #[test]
+    fn compact_internal_empty_tree() {
+        let mut tree: Tree<i32> = TreeBuilder::new().with_capacity(10).build();
+        assert_eq!(tree.capacity(), 10);
+        assert_eq!(tree.compact_internal(), 0);
+    }
+
+    #[test]
+    fn compact_internal_tree() {
+        let mut tree = TreeBuilder::new().with_root(0).build();
+        let mut root = tree.root_mut().unwrap();
+        let one_id = root.append2(1);
+        let five_id = root.append2(5);
+        {
+            let mut two = tree.get_mut(one_id).unwrap().append(2);
+            two.append2(3);
+            two.append2(4);
+        }
+        {
+            let mut five = tree.get_mut(five_id).unwrap();
+            let six_id = five.append2(6);
+            five.append2(8);
+            tree.get_mut(six_id).unwrap().append(7);
+        }
+
+        // 0
+        // ├── 1
+        // │   └── 2
+        // │       ├── 3
+        // │       └── 4
+        // ├── 5
+        // │   ├── 6
+        // │   │   └── 7
+        // │   └── 8
+
+        let three_id = tree.find(&3).unwrap()[0];
+
+        tree.remove(three_id, RemoveBehavior::DropChildren);
+        tree.remove(five_id, RemoveBehavior::DropChildren);
+
+        // 0
+        // └── 1
+        //     └── 2
+        //         └── 4
+
+        let two = tree.get(tree.find(&2).unwrap()[0]).unwrap();
+        assert_eq!(two.first_child().unwrap().data(), &4);
+
+        let four = tree.get(tree.find(&4).unwrap()[0]).unwrap();
+        assert!(four.prev_sibling().is_none());
+        assert_eq!(tree.root().unwrap().last_child().unwrap().data(), &1);
+
+        assert!(tree.capacity() >= 9);
+
+        tree.compact_internal();
+
+        let two = tree.get(tree.find(&2).unwrap()[0]).unwrap();
+        assert_eq!(two.first_child().unwrap().data(), &4);
+
+        let four = tree.get(tree.find(&4).unwrap()[0]).unwrap();
+        assert!(four.prev_sibling().is_none());
+        assert_eq!(four.parent().unwrap().data(), &2);
+        assert_eq!(tree.root().unwrap().last_child().unwrap().data(), &1);
+
+        assert!(tree.capacity() == 4);
+    }
+
+    #[test]
+    fn compact_internal_moved_root() {
+        let mut tree = TreeBuilder::new().with_root(1).build();
+        let old_root_id = tree.root_id().unwrap();
+        tree.set_root(0);
+        tree.remove(old_root_id, RemoveBehavior::OrphanChildren);
+
+        tree.compact_internal();
+
+        assert_eq!(tree.capacity(), 1);
+        let root = tree.root().unwrap();
+        assert_eq!(root.data(), &0);
+        assert!(root.first_child().is_none());
+    }
+
     #[cfg(feature = "experimental")]
     #[test]
     fn compact_empty_tree() {
@@ -1107,17 +7213,16 @@ mod tree_tests {
     fn compact_tree() {
         let mut tree = TreeBuilder::new().with_root(0).build();
         let mut root = tree.root_mut().unwrap();
-        {
-            let mut one = root.append(1);
-            let mut two = one.append(2);
-            two.append(3);
-            two.append(4);
-        }
-        {
-            let mut five = root.append(5);
-            five.append(6).append(7);
-            five.append(8);
-        }
+        let one_id = root.append2(1);
+        let five_id = root.append2(5);
+        let two_id = tree.get_mut(one_id).unwrap().append2(2);
+        let mut two = tree.get_mut(two_id).unwrap();
+        two.append2(3);
+        two.append2(4);
+        let mut five = tree.get_mut(five_id).unwrap();
+        let six_id = five.append2(6);
+        five.append2(8);
+        tree.get_mut(six_id).unwrap().append2(7);
 
         // 0
         // ├── 1
@@ -0,0 +1,971 @@
+use std::collections::VecDeque;
+
+use snowflake::ProcessUniqueId;
+
+use crate::behaviors::RemoveBehavior;
+use crate::slab::Slab;
+use crate::NodeId;
+
+#[derive(Debug)]
+struct VecNode<T> {
+    data: T,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+///
+/// A tree structure whose `Node`s keep their children in a `Vec`.
+///
+/// Where `Tree` links siblings to each other, a `VecTree` stores the `NodeId`s of each `Node`'s
+/// children in order.  Getting the n-th child or a child count is O(1), at the cost of O(n)
+/// insertions and removals in the middle of a `Node`'s children, and of finding a `Node`'s
+/// siblings.  Prefer it for high fan-out trees whose children are mostly accessed by position.
+///
+/// The API mirrors `Tree`'s: `NodeId`s identify `Node`s, and `VecNodeRef`/`VecNodeMut` views
+/// are handed out like `NodeRef`/`NodeMut`.
+///
+/// ```
+/// use nary_tree::vec_tree::VecTree;
+///
+/// let mut tree = VecTree::new();
+/// tree.set_root(0);
+/// let mut root = tree.root_mut().expect("root doesn't exist?");
+/// for i in 1..=100 {
+///     root.append2(i);
+/// }
+///
+/// let root = tree.root().unwrap();
+/// assert_eq!(root.child_count(), 100);
+/// assert_eq!(root.nth_child(41).unwrap().data(), &42);
+/// ```
+///
+#[derive(Debug)]
+pub struct VecTree<T> {
+    id: ProcessUniqueId,
+    root_id: Option<NodeId>,
+    slab: Slab<VecNode<T>>,
+}
+
+impl<T> VecTree<T> {
+    ///
+    /// Creates a new, empty `VecTree` with a capacity of 0.
+    ///
+    /// ```
+    /// use nary_tree::vec_tree::VecTree;
+    ///
+    /// let tree: VecTree<i32> = VecTree::new();
+    ///
+    /// # assert!(tree.root().is_none());
+    /// ```
+    ///
+    pub fn new() -> VecTree<T> {
+        VecTree::with_capacity(0)
+    }
+
+    ///
+    /// Creates a new, empty `VecTree` able to hold `capacity` `Node`s without reallocating.
+    ///
+    /// ```
+    /// use nary_tree::vec_tree::VecTree;
+    ///
+    /// let tree: VecTree<i32> = VecTree::with_capacity(10);
+    ///
+    /// # assert_eq!(tree.capacity(), 10);
+    /// ```
+    ///
+    pub fn with_capacity(capacity: usize) -> VecTree<T> {
+        VecTree {
+            id: ProcessUniqueId::new(),
+            root_id: None,
+            slab: Slab::new(capacity),
+        }
+    }
+
+    ///
+    /// Returns the number of `Node`s the `VecTree` can hold without reallocating.
+    ///
+    pub fn capacity(&self) -> usize {
+        self.slab.capacity()
+    }
+
+    ///
+    /// Sets the "root" of the `VecTree` to be `root`.  Like `Tree::set_root`, an existing root
+    /// (with its whole sub-tree) becomes the only child of the new root and keeps its `NodeId`.
+    ///
+    /// ```
+    /// use nary_tree::vec_tree::VecTree;
+    ///
+    /// let mut tree = VecTree::new();
+    /// let one_id = tree.set_root(1);
+    /// let zero_id = tree.set_root(0);
+    ///
+    /// let root = tree.root().unwrap();
+    /// assert_eq!(root.node_id(), zero_id);
+    /// assert_eq!(root.nth_child(0).unwrap().node_id(), one_id);
+    /// ```
+    ///
+    pub fn set_root(&mut self, root: T) -> NodeId {
+        let old_root_id = self.root_id.take();
+        let new_root_id = self.insert(root, None);
+        if let Some(old_root_id) = old_root_id {
+            self.node_mut(old_root_id).parent = Some(new_root_id);
+            self.node_mut(new_root_id).children.push(old_root_id);
+        }
+        self.root_id = Some(new_root_id);
+        new_root_id
+    }
+
+    ///
+    /// Returns the `NodeId` of the root `Node`, if there is one.
+    ///
+    pub fn root_id(&self) -> Option<NodeId> {
+        self.root_id
+    }
+
+    ///
+    /// Returns a `VecNodeRef` pointing to the root `Node`, if there is one.
+    ///
+    pub fn root(&self) -> Option<VecNodeRef<'_, T>> {
+        self.root_id.and_then(|root_id| self.get(root_id))
+    }
+
+    ///
+    /// Returns a `VecNodeMut` pointing to the root `Node`, if there is one.
+    ///
+    pub fn root_mut(&mut self) -> Option<VecNodeMut<'_, T>> {
+        self.root_id.and_then(move |root_id| self.get_mut(root_id))
+    }
+
+    ///
+    /// Returns a `VecNodeRef` pointing to the `Node` that `node_id` identifies.  Returns a
+    /// `None`-value if `node_id` points to nothing (or belongs to a different tree).
+    ///
+    pub fn get(&self, node_id: NodeId) -> Option<VecNodeRef<'_, T>> {
+        self.get_node(node_id).map(|_| VecNodeRef {
+            node_id,
+            tree: self,
+        })
+    }
+
+    ///
+    /// Returns a `VecNodeMut` pointing to the `Node` that `node_id` identifies.  Returns a
+    /// `None`-value if `node_id` points to nothing (or belongs to a different tree).
+    ///
+    pub fn get_mut(&mut self, node_id: NodeId) -> Option<VecNodeMut<'_, T>> {
+        match self.get_node(node_id) {
+            Some(_) => Some(VecNodeMut {
+                node_id,
+                tree: self,
+            }),
+            None => None,
+        }
+    }
+
+    ///
+    /// Removes the `Node` that `node_id` identifies and returns its data.  Its children are
+    /// either dropped along with it or orphaned, depending on `behavior`.  Returns a `None`-value
+    /// if `node_id` points to nothing (or belongs to a different tree).
+    ///
+    /// ```
+    /// use nary_tree::behaviors::RemoveBehavior::DropChildren;
+    /// use nary_tree::vec_tree::VecTree;
+    ///
+    /// let mut tree = VecTree::new();
+    /// tree.set_root(1);
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append2(2);
+    /// let three_id = root.append2(3);
+    /// root.append2(4);
+    ///
+    /// assert_eq!(tree.remove(three_id, DropChildren), Some(3));
+    /// assert_eq!(tree.root().unwrap().nth_child(1).unwrap().data(), &4);
+    /// ```
+    ///
+    pub fn remove(&mut self, node_id: NodeId, behavior: RemoveBehavior) -> Option<T> {
+        let node = self.get_node(node_id)?;
+        match node.parent {
+            Some(parent_id) => self
+                .node_mut(parent_id)
+                .children
+                .retain(|child_id| *child_id != node_id),
+            None if self.root_id == Some(node_id) => self.root_id = None,
+            None => {}
+        }
+
+        let node = self
+            .slab
+            .try_remove(node_id.index)
+            .expect("node must exist");
+        match behavior {
+            RemoveBehavior::DropChildren => {
+                let mut to_drop = node.children;
+                while let Some(id) = to_drop.pop() {
+                    let dropped = self.slab.try_remove(id.index).expect("child must exist");
+                    to_drop.extend(dropped.children);
+                }
+            }
+            RemoveBehavior::OrphanChildren => {
+                for child_id in node.children {
+                    self.node_mut(child_id).parent = None;
+                }
+            }
+        }
+        Some(node.data)
+    }
+
+    fn insert(&mut self, data: T, parent: Option<NodeId>) -> NodeId {
+        let index = self.slab.insert(VecNode {
+            data,
+            parent,
+            children: Vec::new(),
+        });
+        NodeId {
+            tree_id: self.id,
+            index,
+            #[cfg(feature = "experimental")]
+            compaction: 0,
+        }
+    }
+
+    fn get_node(&self, node_id: NodeId) -> Option<&VecNode<T>> {
+        if node_id.tree_id != self.id {
+            return None;
+        }
+        self.slab.get(node_id.index)
+    }
+
+    fn node(&self, node_id: NodeId) -> &VecNode<T> {
+        self.get_node(node_id).expect("node must exist")
+    }
+
+    fn node_mut(&mut self, node_id: NodeId) -> &mut VecNode<T> {
+        debug_assert_eq!(node_id.tree_id, self.id);
+        self.slab.get_mut(node_id.index).expect("node must exist")
+    }
+}
+
+impl<T> Default for VecTree<T> {
+    fn default() -> Self {
+        VecTree::new()
+    }
+}
+
+///
+/// An immutable reference to a given `Node`'s data and its relatives in a `VecTree`.
+///
+#[derive(Debug)]
+pub struct VecNodeRef<'a, T> {
+    node_id: NodeId,
+    tree: &'a VecTree<T>,
+}
+
+impl<'a, T> VecNodeRef<'a, T> {
+    ///
+    /// Returns the `NodeId` that identifies this `Node` in the tree.
+    ///
+    pub fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    ///
+    /// Returns a reference to the data contained by this `Node`.
+    ///
+    pub fn data(&self) -> &'a T {
+        &self.tree.node(self.node_id).data
+    }
+
+    ///
+    /// Returns a `VecNodeRef` pointing to this `Node`'s parent, if it has one.
+    ///
+    pub fn parent(&self) -> Option<VecNodeRef<'a, T>> {
+        self.tree
+            .node(self.node_id)
+            .parent
+            .map(|node_id| self.new_ref(node_id))
+    }
+
+    ///
+    /// Returns the number of children of this `Node`.
+    ///
+    pub fn child_count(&self) -> usize {
+        self.tree.node(self.node_id).children.len()
+    }
+
+    ///
+    /// Returns a `VecNodeRef` pointing to this `Node`'s child at position `n`, if there is one.
+    ///
+    pub fn nth_child(&self, n: usize) -> Option<VecNodeRef<'a, T>> {
+        self.tree
+            .node(self.node_id)
+            .children
+            .get(n)
+            .map(|node_id| self.new_ref(*node_id))
+    }
+
+    ///
+    /// Returns a `VecNodeRef` pointing to this `Node`'s first child, if it has any.
+    ///
+    pub fn first_child(&self) -> Option<VecNodeRef<'a, T>> {
+        self.nth_child(0)
+    }
+
+    ///
+    /// Returns a `VecNodeRef` pointing to this `Node`'s last child, if it has any.
+    ///
+    pub fn last_child(&self) -> Option<VecNodeRef<'a, T>> {
+        self.child_count()
+            .checked_sub(1)
+            .and_then(|last| self.nth_child(last))
+    }
+
+    ///
+    /// Returns a `VecNodeRef` pointing to this `Node`'s previous sibling, if it has one.  This
+    /// searches the parent's children for this `Node`.
+    ///
+    pub fn prev_sibling(&self) -> Option<VecNodeRef<'a, T>> {
+        let (siblings, position) = self.position_among_siblings()?;
+        position
+            .checked_sub(1)
+            .map(|prev| self.new_ref(siblings[prev]))
+    }
+
+    ///
+    /// Returns a `VecNodeRef` pointing to this `Node`'s next sibling, if it has one.  This
+    /// searches the parent's children for this `Node`.
+    ///
+    pub fn next_sibling(&self) -> Option<VecNodeRef<'a, T>> {
+        let (siblings, position) = self.position_among_siblings()?;
+        siblings
+            .get(position + 1)
+            .map(|node_id| self.new_ref(*node_id))
+    }
+
+    ///
+    /// Returns an `Iterator` over this `Node`'s children.
+    ///
+    /// ```
+    /// use nary_tree::vec_tree::VecTree;
+    ///
+    /// let mut tree = VecTree::new();
+    /// tree.set_root(1);
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append2(2);
+    /// root.append2(3);
+    ///
+    /// let root = tree.root().unwrap();
+    /// let values = root.children().map(|child| *child.data()).collect::<Vec<i32>>();
+    /// assert_eq!(values, vec![2, 3]);
+    /// ```
+    ///
+    pub fn children(&self) -> impl Iterator<Item = VecNodeRef<'a, T>> {
+        let tree = self.tree;
+        tree.node(self.node_id)
+            .children
+            .iter()
+            .map(move |node_id| VecNodeRef {
+                node_id: *node_id,
+                tree,
+            })
+    }
+
+    ///
+    /// Returns an `Iterator` over this `Node` and its descendants in depth-first pre-order.
+    ///
+    /// ```
+    /// use nary_tree::vec_tree::VecTree;
+    ///
+    /// let mut tree = VecTree::new();
+    /// tree.set_root(1);
+    /// let mut root = tree.root_mut().unwrap();
+    /// let two_id = root.append2(2);
+    /// root.append2(4);
+    /// tree.get_mut(two_id).unwrap().append2(3);
+    ///
+    /// let root = tree.root().unwrap();
+    /// let values = root.traverse_pre_order().map(|node| *node.data()).collect::<Vec<i32>>();
+    /// assert_eq!(values, vec![1, 2, 3, 4]);
+    /// ```
+    ///
+    pub fn traverse_pre_order(&self) -> VecPreOrder<'a, T> {
+        VecPreOrder {
+            stack: vec![self.node_id],
+            tree: self.tree,
+        }
+    }
+
+    ///
+    /// Returns an `Iterator` over this `Node`'s ancestors, from its parent up to the root.
+    ///
+    /// ```
+    /// use nary_tree::vec_tree::VecTree;
+    ///
+    /// let mut tree = VecTree::new();
+    /// tree.set_root(1);
+    /// let two_id = tree.root_mut().unwrap().append2(2);
+    /// let three_id = tree.get_mut(two_id).unwrap().append2(3);
+    ///
+    /// let three = tree.get(three_id).unwrap();
+    /// let values = three.ancestors().map(|node| *node.data()).collect::<Vec<i32>>();
+    /// assert_eq!(values, vec![2, 1]);
+    /// ```
+    ///
+    pub fn ancestors(&self) -> VecAncestors<'a, T> {
+        VecAncestors {
+            node_id: self.tree.node(self.node_id).parent,
+            tree: self.tree,
+        }
+    }
+
+    ///
+    /// Returns an `Iterator` over this `Node` and its descendants in depth-first post-order:
+    /// each `Node` comes after all of its descendants, so this `Node` comes last.
+    ///
+    /// ```
+    /// use nary_tree::vec_tree::VecTree;
+    ///
+    /// let mut tree = VecTree::new();
+    /// tree.set_root(1);
+    /// let mut root = tree.root_mut().unwrap();
+    /// let two_id = root.append2(2);
+    /// root.append2(4);
+    /// tree.get_mut(two_id).unwrap().append2(3);
+    ///
+    /// let root = tree.root().unwrap();
+    /// let values = root.traverse_post_order().map(|node| *node.data()).collect::<Vec<i32>>();
+    /// assert_eq!(values, vec![3, 2, 4, 1]);
+    /// ```
+    ///
+    pub fn traverse_post_order(&self) -> VecPostOrder<'a, T> {
+        VecPostOrder {
+            stack: vec![(self.node_id, 0)],
+            tree: self.tree,
+        }
+    }
+
+    ///
+    /// Returns an `Iterator` over this `Node` and its descendants in level-order: this `Node`
+    /// first, then its children, then its grandchildren, and so on.
+    ///
+    /// ```
+    /// use nary_tree::vec_tree::VecTree;
+    ///
+    /// let mut tree = VecTree::new();
+    /// tree.set_root(1);
+    /// let mut root = tree.root_mut().unwrap();
+    /// let two_id = root.append2(2);
+    /// root.append2(3);
+    /// tree.get_mut(two_id).unwrap().append2(4);
+    ///
+    /// let root = tree.root().unwrap();
+    /// let values = root.traverse_level_order().map(|node| *node.data()).collect::<Vec<i32>>();
+    /// assert_eq!(values, vec![1, 2, 3, 4]);
+    /// ```
+    ///
+    pub fn traverse_level_order(&self) -> VecLevelOrder<'a, T> {
+        VecLevelOrder {
+            queue: VecDeque::from([self.node_id]),
+            tree: self.tree,
+        }
+    }
+
+    fn new_ref(&self, node_id: NodeId) -> VecNodeRef<'a, T> {
+        VecNodeRef {
+            node_id,
+            tree: self.tree,
+        }
+    }
+
+    fn position_among_siblings(&self) -> Option<(&'a [NodeId], usize)> {
+        let parent_id = self.tree.node(self.node_id).parent?;
+        let siblings = &self.tree.node(parent_id).children;
+        let position = siblings
+            .iter()
+            .position(|node_id| *node_id == self.node_id)
+            .expect("node must be among its parent's children");
+        Some((siblings, position))
+    }
+}
+
+///
+/// A mutable reference to a given `Node`'s data and its relatives in a `VecTree`.
+///
+#[derive(Debug)]
+pub struct VecNodeMut<'a, T> {
+    node_id: NodeId,
+    tree: &'a mut VecTree<T>,
+}
+
+impl<'a, T> VecNodeMut<'a, T> {
+    ///
+    /// Returns the `NodeId` that identifies this `Node` in the tree.
+    ///
+    pub fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    ///
+    /// Returns a mutable reference to the data contained by this `Node`.
+    ///
+    pub fn data(&mut self) -> &mut T {
+        &mut self.tree.node_mut(self.node_id).data
+    }
+
+    ///
+    /// Returns a `VecNodeMut` pointing to this `Node`'s parent, if it has one.
+    ///
+    pub fn parent(self) -> Option<VecNodeMut<'a, T>> {
+        let parent_id = self.tree.node(self.node_id).parent?;
+        Some(VecNodeMut {
+            node_id: parent_id,
+            tree: self.tree,
+        })
+    }
+
+    ///
+    /// Returns a `VecNodeMut` pointing to this `Node`'s child at position `n`, if there is one.
+    ///
+    pub fn nth_child(self, n: usize) -> Option<VecNodeMut<'a, T>> {
+        let child_id = *self.tree.node(self.node_id).children.get(n)?;
+        Some(VecNodeMut {
+            node_id: child_id,
+            tree: self.tree,
+        })
+    }
+
+    ///
+    /// Returns a `VecNodeMut` pointing to this `Node`'s first child, if it has any.
+    ///
+    pub fn first_child(self) -> Option<VecNodeMut<'a, T>> {
+        self.nth_child(0)
+    }
+
+    ///
+    /// Returns a `VecNodeMut` pointing to this `Node`'s last child, if it has any.
+    ///
+    pub fn last_child(self) -> Option<VecNodeMut<'a, T>> {
+        let child_id = *self.tree.node(self.node_id).children.last()?;
+        Some(VecNodeMut {
+            node_id: child_id,
+            tree: self.tree,
+        })
+    }
+
+    ///
+    /// Returns a `VecNodeMut` pointing to this `Node`'s previous sibling, if it has one.  This
+    /// searches the parent's children for this `Node`.
+    ///
+    pub fn prev_sibling(self) -> Option<VecNodeMut<'a, T>> {
+        let sibling_id = self.tree.get(self.node_id)?.prev_sibling()?.node_id();
+        Some(VecNodeMut {
+            node_id: sibling_id,
+            tree: self.tree,
+        })
+    }
+
+    ///
+    /// Returns a `VecNodeMut` pointing to this `Node`'s next sibling, if it has one.  This
+    /// searches the parent's children for this `Node`.
+    ///
+    pub fn next_sibling(self) -> Option<VecNodeMut<'a, T>> {
+        let sibling_id = self.tree.get(self.node_id)?.next_sibling()?.node_id();
+        Some(VecNodeMut {
+            node_id: sibling_id,
+            tree: self.tree,
+        })
+    }
+
+    ///
+    /// Appends a new `Node` containing `data` as this `Node`'s last child and returns a
+    /// `VecNodeMut` pointing to it.
+    ///
+    /// ```
+    /// use nary_tree::vec_tree::VecTree;
+    ///
+    /// let mut tree = VecTree::new();
+    /// tree.set_root(1);
+    /// tree.root_mut().unwrap().append(2).append(3);
+    ///
+    /// let root = tree.root().unwrap();
+    /// let values = root.traverse_pre_order().map(|node| *node.data()).collect::<Vec<i32>>();
+    /// assert_eq!(values, vec![1, 2, 3]);
+    /// ```
+    ///
+    pub fn append(mut self, data: T) -> VecNodeMut<'a, T> {
+        let new_id = self.append2(data);
+        VecNodeMut {
+            node_id: new_id,
+            tree: self.tree,
+        }
+    }
+
+    ///
+    /// Prepends a new `Node` containing `data` as this `Node`'s first child and returns a
+    /// `VecNodeMut` pointing to it.
+    ///
+    pub fn prepend(mut self, data: T) -> VecNodeMut<'a, T> {
+        let new_id = self.prepend2(data);
+        VecNodeMut {
+            node_id: new_id,
+            tree: self.tree,
+        }
+    }
+
+    ///
+    /// Appends a new `Node` containing `data` as this `Node`'s last child and returns its
+    /// `NodeId`.
+    ///
+    pub fn append2(&mut self, data: T) -> NodeId {
+        let len = self.tree.node(self.node_id).children.len();
+        self.insert_child(len, data)
+    }
+
+    ///
+    /// Prepends a new `Node` containing `data` as this `Node`'s first child and returns its
+    /// `NodeId`.
+    ///
+    pub fn prepend2(&mut self, data: T) -> NodeId {
+        self.insert_child(0, data)
+    }
+
+    ///
+    /// Inserts a new `Node` containing `data` at position `index` among this `Node`'s children,
+    /// shifting the children after it, and returns its `NodeId`.  An `index` past the last child
+    /// appends the new `Node`.
+    ///
+    /// ```
+    /// use nary_tree::vec_tree::VecTree;
+    ///
+    /// let mut tree = VecTree::new();
+    /// tree.set_root(0);
+    /// let mut root = tree.root_mut().unwrap();
+    /// root.append2(1);
+    /// root.append2(3);
+    /// root.insert_child(1, 2);
+    /// root.insert_child(10, 4);
+    ///
+    /// let root = tree.root().unwrap();
+    /// let values = root.children().map(|child| *child.data()).collect::<Vec<i32>>();
+    /// assert_eq!(values, vec![1, 2, 3, 4]);
+    /// ```
+    ///
+    pub fn insert_child(&mut self, index: usize, data: T) -> NodeId {
+        let new_id = self.tree.insert(data, Some(self.node_id));
+        let children = &mut self.tree.node_mut(self.node_id).children;
+        children.insert(index.min(children.len()), new_id);
+        new_id
+    }
+
+    ///
+    /// Removes this `Node`'s child at position `n` and returns its data, dropping or orphaning
+    /// its own children depending on `behavior`.  Returns a `None`-value if there is no such
+    /// child.
+    ///
+    pub fn remove_nth_child(&mut self, n: usize, behavior: RemoveBehavior) -> Option<T> {
+        let child_id = *self.tree.node(self.node_id).children.get(n)?;
+        self.tree.remove(child_id, behavior)
+    }
+
+    ///
+    /// Returns a `VecNodeRef` pointing to this `Node`.
+    ///
+    pub fn as_ref(self) -> VecNodeRef<'a, T> {
+        VecNodeRef {
+            node_id: self.node_id,
+            tree: self.tree,
+        }
+    }
+}
+
+/// Depth-first pre-order iterator over a `VecTree`
+pub struct VecPreOrder<'a, T> {
+    stack: Vec<NodeId>,
+    tree: &'a VecTree<T>,
+}
+
+impl<'a, T> Iterator for VecPreOrder<'a, T> {
+    type Item = VecNodeRef<'a, T>;
+
+    fn next(&mut self) -> Option<VecNodeRef<'a, T>> {
+        let node_id = self.stack.pop()?;
+        let children = &self.tree.node(node_id).children;
+        self.stack.extend(children.iter().rev());
+        Some(VecNodeRef {
+            node_id,
+            tree: self.tree,
+        })
+    }
+}
+
+/// Iterator over the ancestors of a `Node` in a `VecTree`, from its parent up to the root
+pub struct VecAncestors<'a, T> {
+    node_id: Option<NodeId>,
+    tree: &'a VecTree<T>,
+}
+
+impl<'a, T> Iterator for VecAncestors<'a, T> {
+    type Item = VecNodeRef<'a, T>;
+
+    fn next(&mut self) -> Option<VecNodeRef<'a, T>> {
+        let node_id = self.node_id?;
+        self.node_id = self.tree.node(node_id).parent;
+        Some(VecNodeRef {
+            node_id,
+            tree: self.tree,
+        })
+    }
+}
+
+/// Depth-first post-order iterator over a `VecTree`
+pub struct VecPostOrder<'a, T> {
+    // the nodes being walked, each with the position of its next child to visit
+    stack: Vec<(NodeId, usize)>,
+    tree: &'a VecTree<T>,
+}
+
+impl<'a, T> Iterator for VecPostOrder<'a, T> {
+    type Item = VecNodeRef<'a, T>;
+
+    fn next(&mut self) -> Option<VecNodeRef<'a, T>> {
+        loop {
+            let (node_id, next_child) = self.stack.last_mut()?;
+            let node_id = *node_id;
+            match self.tree.node(node_id).children.get(*next_child) {
+                Some(child_id) => {
+                    *next_child += 1;
+                    self.stack.push((*child_id, 0));
+                }
+                None => {
+                    self.stack.pop();
+                    return Some(VecNodeRef {
+                        node_id,
+                        tree: self.tree,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Level-order iterator over a `VecTree`
+pub struct VecLevelOrder<'a, T> {
+    queue: VecDeque<NodeId>,
+    tree: &'a VecTree<T>,
+}
+
+impl<'a, T> Iterator for VecLevelOrder<'a, T> {
+    type Item = VecNodeRef<'a, T>;
+
+    fn next(&mut self) -> Option<VecNodeRef<'a, T>> {
+        let node_id = self.queue.pop_front()?;
+        self.queue
+            .extend(self.tree.node(node_id).children.iter().copied());
+        Some(VecNodeRef {
+            node_id,
+            tree: self.tree,
+        })
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod vec_tree_tests {
+    use super::*;
+    use crate::behaviors::RemoveBehavior::{DropChildren, OrphanChildren};
+
+    #[test]
+    fn set_root() {
+        let mut tree = VecTree::new();
+        let one_id = tree.set_root(1);
+        assert_eq!(tree.root_id(), Some(one_id));
+        assert!(tree.root().unwrap().parent().is_none());
+
+        let zero_id = tree.set_root(0);
+        let one = tree.get(one_id).unwrap();
+        assert_eq!(one.parent().unwrap().node_id(), zero_id);
+        assert_eq!(tree.root().unwrap().child_count(), 1);
+    }
+
+    #[test]
+    fn siblings_and_children() {
+        let mut tree = VecTree::new();
+        tree.set_root(0);
+        let mut root = tree.root_mut().unwrap();
+        let one_id = root.append2(1);
+        let two_id = root.append2(2);
+        let zero_id = root.prepend2(-1);
+
+        let root = tree.root().unwrap();
+        assert_eq!(root.first_child().unwrap().node_id(), zero_id);
+        assert_eq!(root.last_child().unwrap().node_id(), two_id);
+
+        let one = tree.get(one_id).unwrap();
+        assert_eq!(one.prev_sibling().unwrap().node_id(), zero_id);
+        assert_eq!(one.next_sibling().unwrap().node_id(), two_id);
+        assert!(tree.get(zero_id).unwrap().prev_sibling().is_none());
+        assert!(tree.get(two_id).unwrap().next_sibling().is_none());
+        assert!(root.next_sibling().is_none());
+        assert!(one.first_child().is_none() && one.last_child().is_none());
+    }
+
+    #[test]
+    fn node_mut_navigation() {
+        let mut tree = VecTree::new();
+        tree.set_root(0);
+        let mut root = tree.root_mut().unwrap();
+        root.append2(1);
+        root.append2(2);
+
+        let mut two = tree.root_mut().unwrap().nth_child(1).unwrap();
+        *two.data() = 20;
+        let mut root = two.parent().unwrap();
+        assert_eq!(root.data(), &mut 0);
+        assert!(root.parent().is_none());
+
+        let root = tree.root().unwrap();
+        assert_eq!(root.nth_child(1).unwrap().data(), &20);
+        assert!(root.nth_child(2).is_none());
+    }
+
+    #[test]
+    fn remove() {
+        let mut tree = VecTree::new();
+        let root_id = tree.set_root(0);
+        let mut root = tree.root_mut().unwrap();
+        let one_id = root.append2(1);
+        let two_id = root.append2(2);
+        let mut one = tree.get_mut(one_id).unwrap();
+        let three_id = one.append2(3);
+        let four_id = one.append2(4);
+        let five_id = tree.get_mut(two_id).unwrap().append2(5);
+
+        assert_eq!(tree.remove(one_id, DropChildren), Some(1));
+        assert!(tree.get(one_id).is_none());
+        assert!(tree.get(three_id).is_none());
+        assert!(tree.get(four_id).is_none());
+        assert_eq!(tree.root().unwrap().child_count(), 1);
+        assert_eq!(tree.remove(one_id, DropChildren), None);
+
+        assert_eq!(
+            tree.get_mut(root_id)
+                .unwrap()
+                .remove_nth_child(0, OrphanChildren),
+            Some(2)
+        );
+        let five = tree.get(five_id).unwrap();
+        assert!(five.parent().is_none());
+        assert_eq!(tree.root().unwrap().child_count(), 0);
+
+        assert_eq!(tree.remove(root_id, DropChildren), Some(0));
+        assert!(tree.root().is_none());
+        assert!(tree.get(five_id).is_some());
+    }
+
+    #[test]
+    fn traverse_pre_order() {
+        let mut tree = VecTree::new();
+        tree.set_root(1);
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let five_id = root.append2(5);
+        let mut two = tree.get_mut(two_id).unwrap();
+        two.append2(4);
+        two.insert_child(0, 3);
+        tree.get_mut(five_id).unwrap().append2(6);
+
+        let values: Vec<i32> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| *node.data())
+            .collect();
+        assert_eq!(values, vec![1, 2, 3, 4, 5, 6]);
+
+        let values: Vec<i32> = tree
+            .get(two_id)
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| *node.data())
+            .collect();
+        assert_eq!(values, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn ancestors_and_other_traversals() {
+        let mut tree = VecTree::new();
+        tree.set_root(1);
+        let mut root = tree.root_mut().unwrap();
+        let two_id = root.append2(2);
+        let five_id = root.append2(5);
+        let mut two = tree.get_mut(two_id).unwrap();
+        let three_id = two.append2(3);
+        two.append2(4);
+        tree.get_mut(five_id).unwrap().append2(6);
+
+        let values = |iter: &mut dyn Iterator<Item = VecNodeRef<i32>>| {
+            iter.map(|node| *node.data()).collect::<Vec<i32>>()
+        };
+        let root = tree.root().unwrap();
+        assert_eq!(
+            values(&mut tree.get(three_id).unwrap().ancestors()),
+            vec![2, 1]
+        );
+        assert_eq!(values(&mut root.ancestors()), Vec::<i32>::new());
+        assert_eq!(
+            values(&mut root.traverse_post_order()),
+            vec![3, 4, 2, 6, 5, 1]
+        );
+        assert_eq!(
+            values(&mut root.traverse_level_order()),
+            vec![1, 2, 5, 3, 4, 6]
+        );
+        let two = tree.get(two_id).unwrap();
+        assert_eq!(values(&mut two.traverse_post_order()), vec![3, 4, 2]);
+        assert_eq!(values(&mut two.traverse_level_order()), vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn node_mut_relatives() {
+        let mut tree = VecTree::new();
+        tree.set_root(0);
+        let mut root = tree.root_mut().unwrap();
+        root.append2(2);
+        let four_id = root.append(3).append(4).node_id();
+        tree.root_mut().unwrap().prepend(1).prepend(-1);
+
+        let mut first = tree.root_mut().unwrap().first_child().unwrap();
+        assert_eq!(first.data(), &mut 1);
+        let mut next = first.next_sibling().unwrap();
+        assert_eq!(next.data(), &mut 2);
+        let mut prev = next.prev_sibling().unwrap();
+        assert_eq!(prev.data(), &mut 1);
+        assert!(prev.prev_sibling().is_none());
+
+        let mut last = tree.root_mut().unwrap().last_child().unwrap();
+        assert_eq!(last.data(), &mut 3);
+        assert!(last.next_sibling().is_none());
+        let four = tree.root_mut().unwrap().last_child().unwrap().last_child();
+        assert_eq!(four.unwrap().as_ref().parent().unwrap().data(), &3);
+        assert!(tree.root_mut().unwrap().next_sibling().is_none());
+
+        let values: Vec<i32> = tree
+            .root()
+            .unwrap()
+            .traverse_pre_order()
+            .map(|node| *node.data())
+            .collect();
+        assert_eq!(values, vec![0, 1, -1, 2, 3, 4]);
+        assert_eq!(tree.get(four_id).unwrap().data(), &4);
+    }
+
+    #[test]
+    fn foreign_ids() {
+        let mut tree = VecTree::new();
+        tree.set_root(1);
+        let mut other = VecTree::new();
+        let other_root_id = other.set_root(1);
+
+        assert!(tree.get(other_root_id).is_none());
+        assert!(tree.get_mut(other_root_id).is_none());
+        assert_eq!(tree.remove(other_root_id, DropChildren), None);
+    }
+}
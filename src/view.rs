@@ -0,0 +1,477 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::hash::Hash;
+
+use crate::iter::Paths;
+use crate::node::NodeRef;
+use crate::tree::{Tree, TreeStats};
+use crate::NodeId;
+
+///
+/// A read-only handle to a `Tree`, exposing only the methods that read or traverse it.
+///
+/// It is no more than a shared reference in disguise, so it is as cheap to copy around; taking a
+/// `TreeView` instead of a `&Tree` makes it explicit that the `Tree` won't be changed.
+///
+/// ```
+/// use nary_tree::tree::TreeBuilder;
+/// use nary_tree::view::TreeView;
+///
+/// fn sum(view: TreeView<i32>) -> i32 {
+///     view.root()
+///         .map(|root| root.traverse_pre_order().map(|node| *node.data()).sum())
+///         .unwrap_or(0)
+/// }
+///
+/// let mut tree = TreeBuilder::new().with_root(1).build();
+/// tree.root_mut().unwrap().append2(2);
+/// assert_eq!(sum(tree.as_view()), 3);
+/// ```
+///
+pub struct TreeView<'a, T, E = ()> {
+    tree: &'a Tree<T, E>,
+}
+
+impl<'a, T, E> TreeView<'a, T, E> {
+    pub(crate) fn new(tree: &'a Tree<T, E>) -> TreeView<'a, T, E> {
+        TreeView { tree }
+    }
+
+    ///
+    /// Returns the `NodeId` of the root `Node` of the `Tree`, if there is one.
+    ///
+    pub fn root_id(&self) -> Option<NodeId> {
+        self.tree.root_id()
+    }
+
+    ///
+    /// Returns a `NodeRef` pointing to the root `Node` of the `Tree`, if there is one.
+    ///
+    pub fn root(&self) -> Option<NodeRef<'a, T, E>> {
+        self.tree.root()
+    }
+
+    ///
+    /// Returns a `NodeRef` pointing to the `Node` that `node_id` identifies.  Returns a
+    /// `None`-value if `node_id` points to nothing (or belongs to a different `Tree`).
+    ///
+    pub fn get(&self, node_id: NodeId) -> Option<NodeRef<'a, T, E>> {
+        self.tree.get(node_id)
+    }
+
+    ///
+    /// Returns `true` if the `Tree` has no root.  See `Tree::is_empty`.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    ///
+    /// Returns `true` if the `Tree` is made of its root alone.
+    ///
+    pub fn is_single_node(&self) -> bool {
+        self.tree.is_single_node()
+    }
+
+    ///
+    /// Returns the number of leaves in the `Tree`.  See `Tree::leaf_count`.
+    ///
+    pub fn leaf_count(&self) -> usize {
+        self.tree.leaf_count()
+    }
+
+    ///
+    /// Returns the structure version of the `Tree`.  See `Tree::version`.
+    ///
+    pub fn version(&self) -> u64 {
+        self.tree.version()
+    }
+
+    ///
+    /// Returns the data version of the `Tree`.  See `Tree::data_version`.
+    ///
+    pub fn data_version(&self) -> u64 {
+        self.tree.data_version()
+    }
+
+    ///
+    /// Returns the compaction generation of the `Tree`.  See `Tree::compaction_generation`.
+    ///
+    pub fn compaction_generation(&self) -> u64 {
+        self.tree.compaction_generation()
+    }
+
+    ///
+    /// Returns `true` if `node_id` is current in the `Tree`.  See `Tree::is_current`.
+    ///
+    #[cfg(feature = "experimental")]
+    pub fn is_current(&self, node_id: NodeId) -> bool {
+        self.tree.is_current(node_id)
+    }
+
+    ///
+    /// Returns the capacity of the `Tree`.  See `Tree::capacity`.
+    ///
+    pub fn capacity(&self) -> usize {
+        self.tree.capacity()
+    }
+
+    ///
+    /// Returns the share of the `Tree`'s capacity that is free.  See
+    /// `Tree::fragmentation_ratio`.
+    ///
+    pub fn fragmentation_ratio(&self) -> f64 {
+        self.tree.fragmentation_ratio()
+    }
+
+    ///
+    /// Returns statistics about the shape and storage of the `Tree`.  See `Tree::stats`.
+    ///
+    pub fn stats(&self) -> TreeStats {
+        self.tree.stats()
+    }
+
+    ///
+    /// Returns an estimate of the number of bytes used by the `Tree`.  See
+    /// `Tree::memory_footprint`.
+    ///
+    pub fn memory_footprint(&self) -> usize {
+        self.tree.memory_footprint()
+    }
+
+    ///
+    /// Returns the `NodeId`s of the `Node`s marked dirty.  See `Tree::dirty_nodes`.
+    ///
+    pub fn dirty_nodes(&self) -> Vec<NodeId> {
+        self.tree.dirty_nodes()
+    }
+
+    ///
+    /// Returns the `NodeId`s of the `Node`s reachable from the root, in pre-order.  See
+    /// `Tree::cached_pre_order`.
+    ///
+    pub fn cached_pre_order(&self) -> &'a [NodeId] {
+        self.tree.cached_pre_order()
+    }
+
+    ///
+    /// Fills `buf` with the `NodeId`s of the `Node`s reachable from the root, in pre-order.  See
+    /// `Tree::traverse_pre_order_into`.
+    ///
+    pub fn traverse_pre_order_into(&self, buf: &mut Vec<NodeId>) {
+        self.tree.traverse_pre_order_into(buf)
+    }
+
+    ///
+    /// Returns every `Node` in pre-order along with the range its sub-tree covers.  See
+    /// `Tree::traverse_with_ranges`.
+    ///
+    pub fn traverse_with_ranges(&self) -> impl Iterator<Item = (NodeRef<'a, T, E>, usize, usize)> {
+        self.tree.traverse_with_ranges()
+    }
+
+    ///
+    /// Returns the `NodeId`s of the `Node`s that can't be reached from the root.  See
+    /// `Tree::orphans`.
+    ///
+    pub fn orphans(&self) -> Vec<NodeId> {
+        self.tree.orphans()
+    }
+
+    ///
+    /// Returns the key of the `Node` that `node_id` identifies, if it has one.  See
+    /// `Tree::key_of`.
+    ///
+    pub fn key_of(&self, node_id: NodeId) -> Option<u64> {
+        self.tree.key_of(node_id)
+    }
+
+    ///
+    /// Returns the `NodeId` of the `Node` with the given key.  See `Tree::find_by_key`.
+    ///
+    pub fn find_by_key(&self, key: u64) -> Option<NodeId> {
+        self.tree.find_by_key(key)
+    }
+
+    ///
+    /// Returns the `NodeId`s on the path from the root to `node_id`.  See `Tree::spine`.
+    ///
+    pub fn spine(&self, node_id: NodeId) -> Option<Vec<NodeId>> {
+        self.tree.spine(node_id)
+    }
+
+    ///
+    /// Returns the path from the root to the first `Node` matching `pred`.  See
+    /// `Tree::find_path`.
+    ///
+    pub fn find_path<F: FnMut(&T) -> bool>(&self, pred: F) -> Option<Vec<NodeId>> {
+        self.tree.find_path(pred)
+    }
+
+    ///
+    /// Returns the position of `node_id` in pre-order.  See `Tree::pre_order_index`.
+    ///
+    pub fn pre_order_index(&self, node_id: NodeId) -> Option<usize> {
+        self.tree.pre_order_index(node_id)
+    }
+
+    ///
+    /// Returns the number of `Node`s from `from` to `to` in pre-order.  See
+    /// `Tree::range_count`.
+    ///
+    pub fn range_count(&self, from: NodeId, to: NodeId) -> Option<usize> {
+        self.tree.range_count(from, to)
+    }
+
+    ///
+    /// Returns the `NodeId` of the in-order successor of `node_id`.  See `Tree::successor`.
+    ///
+    pub fn successor(&self, node_id: NodeId) -> Option<NodeId> {
+        self.tree.successor(node_id)
+    }
+
+    ///
+    /// Returns the `NodeId` of the in-order predecessor of `node_id`.  See
+    /// `Tree::predecessor`.
+    ///
+    pub fn predecessor(&self, node_id: NodeId) -> Option<NodeId> {
+        self.tree.predecessor(node_id)
+    }
+
+    ///
+    /// Returns `true` if `a` and `b` are distinct `Node`s with the same parent.  See
+    /// `Tree::are_siblings`.
+    ///
+    pub fn are_siblings(&self, a: NodeId, b: NodeId) -> bool {
+        self.tree.are_siblings(a, b)
+    }
+
+    ///
+    /// Returns the number of `Node`s whose data matches `pred`.  See `Tree::count_by`.
+    ///
+    pub fn count_by<F: FnMut(&T) -> bool>(&self, pred: F) -> usize {
+        self.tree.count_by(pred)
+    }
+
+    ///
+    /// Returns the number of children of every `Node`.  See `Tree::child_counts`.
+    ///
+    pub fn child_counts(&self) -> HashMap<NodeId, usize> {
+        self.tree.child_counts()
+    }
+
+    ///
+    /// Returns the `NodeId`s of the `Node`s matching `pred`, which is also given their depth.
+    /// See `Tree::find_by_with_depth`.
+    ///
+    pub fn find_by_with_depth<F: FnMut(&T, usize) -> bool>(&self, pred: F) -> Vec<NodeId> {
+        self.tree.find_by_with_depth(pred)
+    }
+
+    ///
+    /// Returns the `NodeId` of the `Node` with the smallest key.  See `Tree::min_by_key`.
+    ///
+    pub fn min_by_key<K: Ord, F: Fn(&T) -> K>(&self, f: F) -> Option<NodeId> {
+        self.tree.min_by_key(f)
+    }
+
+    ///
+    /// Returns the `NodeId` of the `Node` with the largest key.  See `Tree::max_by_key`.
+    ///
+    pub fn max_by_key<K: Ord, F: Fn(&T) -> K>(&self, f: F) -> Option<NodeId> {
+        self.tree.max_by_key(f)
+    }
+
+    ///
+    /// Returns every `Node`'s data along with its parent's data, in pre-order.  See
+    /// `Tree::iter_with_parent`.
+    ///
+    pub fn iter_with_parent(&self) -> impl Iterator<Item = (&'a T, Option<&'a T>)> {
+        self.tree.iter_with_parent()
+    }
+
+    ///
+    /// Returns every `Node`'s `NodeId` and data along with its parent's `NodeId`, in pre-order.
+    /// See `Tree::iter_with_parent_id`.
+    ///
+    pub fn iter_with_parent_id(&self) -> impl Iterator<Item = (NodeId, Option<NodeId>, &'a T)> {
+        self.tree.iter_with_parent_id()
+    }
+
+    ///
+    /// Returns every `Node`'s `NodeId` and data in storage order.  See `Tree::iter_slab_order`.
+    ///
+    pub fn iter_slab_order(&self) -> impl Iterator<Item = (NodeId, &'a T)> {
+        self.tree.iter_slab_order()
+    }
+}
+
+impl<T: PartialEq, E> TreeView<'_, T, E> {
+    ///
+    /// Returns the `NodeId`s of the `Node`s holding `data`.  See `Tree::find`.
+    ///
+    pub fn find(&self, data: &T) -> Option<Vec<NodeId>> {
+        self.tree.find(data)
+    }
+}
+
+impl<T: Hash + Eq, E> TreeView<'_, T, E> {
+    ///
+    /// Returns the groups of identical sub-trees.  See `Tree::find_duplicate_subtrees`.
+    ///
+    pub fn find_duplicate_subtrees(&self) -> Vec<Vec<NodeId>> {
+        self.tree.find_duplicate_subtrees()
+    }
+}
+
+impl<T: Clone, E> TreeView<'_, T, E> {
+    ///
+    /// Returns a copy of the sub-tree under `node_id` as a new `Tree`.  See
+    /// `Tree::clone_subtree`.
+    ///
+    pub fn clone_subtree(&self, node_id: NodeId) -> Option<Tree<T, E>>
+    where
+        E: Clone,
+    {
+        self.tree.clone_subtree(node_id)
+    }
+
+    ///
+    /// Returns a copy of the data of every `Node`, in pre-order.  See
+    /// `Tree::snapshot_pre_order`.
+    ///
+    pub fn snapshot_pre_order(&self) -> Vec<T> {
+        self.tree.snapshot_pre_order()
+    }
+
+    ///
+    /// Returns a copy of the data on the path from the root to `node_id`.  See
+    /// `Tree::data_path`.
+    ///
+    pub fn data_path(&self, node_id: NodeId) -> Option<Vec<T>> {
+        self.tree.data_path(node_id)
+    }
+
+    ///
+    /// Returns the `Tree` as a list of data and a list of parent-child index pairs.  See
+    /// `Tree::to_adjacency_list`.
+    ///
+    pub fn to_adjacency_list(&self) -> (Vec<T>, Vec<(usize, usize)>) {
+        self.tree.to_adjacency_list()
+    }
+}
+
+impl<'a, T: Display, E> TreeView<'a, T, E> {
+    ///
+    /// Writes the `Tree` out with box-drawing characters.  See `Tree::write_formatted`.
+    ///
+    pub fn write_formatted<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+        self.tree.write_formatted(w)
+    }
+
+    ///
+    /// Writes the `Tree` out with box-drawing characters and `NodeId`s.  See
+    /// `Tree::write_formatted_with_ids`.
+    ///
+    pub fn write_formatted_with_ids<W: std::fmt::Write>(&self, w: &mut W) -> std::fmt::Result {
+        self.tree.write_formatted_with_ids(w)
+    }
+
+    ///
+    /// Returns every `Node` along with its path from the root.  See `Tree::iter_with_paths`.
+    ///
+    pub fn iter_with_paths(&self, separator: &'a str) -> Paths<'a, T, E> {
+        self.tree.iter_with_paths(separator)
+    }
+}
+
+impl<T, E> Clone for TreeView<'_, T, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, E> Copy for TreeView<'_, T, E> {}
+
+impl<T: Display, E> Display for TreeView<'_, T, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.tree.fmt(f)
+    }
+}
+
+#[cfg_attr(tarpaulin, skip)]
+#[cfg(test)]
+mod view_tests {
+    use crate::tree::Tree;
+
+    #[test]
+    fn as_view() {
+        let mut tree = Tree::new();
+        let view = tree.as_view();
+        assert!(view.is_empty());
+        assert!(view.root().is_none());
+
+        let root_id = tree.set_root(1);
+        let two_id = tree.root_mut().unwrap().append2(2);
+        tree.root_mut().unwrap().append2(3);
+
+        let view = tree.as_view();
+        let copy = view;
+        assert_eq!(view.root_id(), Some(root_id));
+        assert_eq!(copy.get(two_id).unwrap().data(), &2);
+        assert!(!view.is_empty() && !view.is_single_node());
+        assert_eq!(view.leaf_count(), 2);
+        assert_eq!(view.version(), tree.version());
+        assert_eq!(view.data_version(), tree.data_version());
+        assert_eq!(view.to_string(), tree.to_string());
+
+        // references handed out by the view outlive it
+        let root = {
+            let view = tree.as_view();
+            view.root().unwrap()
+        };
+        assert_eq!(root.data(), &1);
+    }
+
+    #[test]
+    fn forwards_read_only_methods() {
+        let mut tree = Tree::new();
+        let root_id = tree.set_root(1);
+        let two_id = tree.root_mut().unwrap().append2(2);
+        let three_id = tree.get_mut(two_id).unwrap().append2(3);
+        tree.root_mut().unwrap().append2(2);
+        tree.set_key(three_id, 7);
+
+        let view = tree.as_view();
+        assert_eq!(view.find(&2), tree.find(&2));
+        assert_eq!(view.pre_order_index(three_id), Some(2));
+        assert_eq!(view.range_count(root_id, three_id), Some(3));
+        assert_eq!(view.cached_pre_order(), tree.cached_pre_order());
+        assert_eq!(view.spine(three_id), Some(vec![root_id, two_id, three_id]));
+        assert_eq!(view.find_by_key(7), Some(three_id));
+        assert_eq!(view.key_of(three_id), Some(7));
+        assert_eq!(view.stats(), tree.stats());
+        assert_eq!(view.count_by(|data| *data == 2), 2);
+        assert_eq!(view.max_by_key(|data| *data), Some(three_id));
+        assert_eq!(view.data_path(three_id), Some(vec![1, 2, 3]));
+        assert_eq!(view.snapshot_pre_order(), vec![1, 2, 3, 2]);
+        assert_eq!(
+            view.find_duplicate_subtrees(),
+            tree.find_duplicate_subtrees()
+        );
+        assert!(view.orphans().is_empty());
+        assert!(view.iter_with_parent_id().eq(tree.iter_with_parent_id()));
+        assert!(view.iter_with_parent().eq(tree.iter_with_parent()));
+
+        let mut formatted = String::new();
+        view.write_formatted(&mut formatted).unwrap();
+        assert_eq!(formatted, tree.to_string());
+
+        // borrowed results outlive the view
+        let pre_order = {
+            let view = tree.as_view();
+            view.cached_pre_order()
+        };
+        assert_eq!(pre_order.len(), 4);
+    }
+}